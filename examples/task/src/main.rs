@@ -1,4 +1,4 @@
-use gotcha::{ConfigWrapper, GotchaApp, GotchaContext, GotchaRouter, Responder, State, TaskScheduler};
+use gotcha::{task, ConfigWrapper, GotchaApp, GotchaContext, GotchaRouter, Responder, State, TaskScheduler};
 use serde::{Deserialize, Serialize};
 
 pub async fn hello_world(_state: State<ConfigWrapper<Config>>) -> impl Responder {
@@ -24,7 +24,7 @@ impl GotchaApp for App {
     }
 
     async fn tasks(&self, task_scheduler: &mut TaskScheduler<Self::State, Self::Config>) -> Result<(), gotcha::GotchaError> {
-        task_scheduler.interval("interval task", std::time::Duration::from_secs(1), interval_task);
+        task_scheduler.interval("interval task", std::time::Duration::from_secs(1), interval_task)?;
         Ok(())
     }
 }
@@ -33,6 +33,12 @@ async fn interval_task(_: GotchaContext<(), Config>) {
     println!("interval task");
 }
 
+/// Declared tasks need no registration: `run` starts them.
+#[task(cron = "*/5 * * * * *")]
+async fn declared_task(State(_config): State<Config>) {
+    println!("declared task");
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     App {}.run().await?;
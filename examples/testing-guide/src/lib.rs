@@ -246,16 +246,11 @@ impl GotchaApp for App {
 
 // ========== Helper function for testing ==========
 pub async fn create_test_app() -> axum::Router {
-    use gotcha::config::ServerConfig;
-
     let app = App;
-    let config = ConfigWrapper {
-        server: ServerConfig::default(),
-        app: AppConfig::default(),
-    };
+    let config = ConfigWrapper::<AppConfig>::default();
 
     let state = app.state(&config).await.unwrap();
-    let context = GotchaContext { state, config };
+    let context = GotchaContext::new(config, state);
 
     app.build_router(context).await.unwrap()
 }
@@ -0,0 +1,296 @@
+//! # Actor Module
+//!
+//! Long-lived actors that own their state. [`Messager::send`](crate::Messager::send) runs a
+//! message on the caller's task, so state shared between messages needs a lock in the
+//! application state. An actor instead handles its messages one at a time, on a task of its own,
+//! so its state needs none.
+//!
+//! An actor is created when the application starts, from the application context. It is
+//! addressed by an [`Addr<A>`], which the context keeps and handlers extract like any other
+//! argument. Messages wait in a bounded mailbox; when it is full, senders wait for room, so a busy
+//! actor slows its callers down rather than queueing without limit. An actor that panics, while
+//! being created or handling a message, is recreated, and keeps handling its mailbox. It stops
+//! once its context and every address of it are gone.
+//!
+//! ```rust,ignore
+//! use gotcha::actor::{Actor, ActorContext, ActorError, Addr, Handles};
+//!
+//! #[derive(Default)]
+//! struct Visits { counts: HashMap<String, u64> }
+//!
+//! impl Actor for Visits {
+//!     type State = AppState;
+//!     type Config = Config;
+//!     fn create(_context: &ActorContext<Self>) -> Self {
+//!         Self::default()
+//!     }
+//! }
+//!
+//! struct Visit(String);
+//!
+//! #[async_trait]
+//! impl Handles<Visit> for Visits {
+//!     type Reply = u64;
+//!     async fn handle(&mut self, Visit(page): Visit, _context: &ActorContext<Self>) -> u64 {
+//!         let count = self.counts.entry(page).or_default();
+//!         *count += 1;
+//!         *count
+//!     }
+//! }
+//!
+//! // In `GotchaApp::actors`, or the builder's `actors`:
+//! actors.start::<Visits>();
+//!
+//! async fn page(visits: Addr<Visits>, Path(page): Path<String>) -> Result<String, ActorError> {
+//!     Ok(format!("{} visits", visits.send(Visit(page)).await?))
+//! }
+//! ```
+
+use std::any::type_name;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use futures_util::FutureExt;
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::message::payload_message;
+use crate::{GotchaConfig, GotchaContext};
+
+/// The application context of an actor.
+pub type ActorContext<A> = GotchaContext<<A as Actor>::State, <A as Actor>::Config>;
+
+/// A long-lived owner of state, which handles the messages it [`Handles`] one at a time.
+pub trait Actor: Send + Sized + 'static {
+    /// The application state.
+    type State: Clone + Send + Sync + 'static;
+    /// The application configuration.
+    type Config: GotchaConfig;
+
+    /// Create the actor when the application starts, and again after it panics.
+    fn create(context: &ActorContext<Self>) -> Self;
+}
+
+/// An [`Actor`] that handles messages of type `M`.
+#[async_trait]
+pub trait Handles<M: Send + 'static>: Actor {
+    /// What the sender gets back.
+    type Reply: Send + 'static;
+
+    /// Handle a message. No other message is handled until this returns.
+    async fn handle(&mut self, message: M, context: &ActorContext<Self>) -> Self::Reply;
+}
+
+/// Why a message did not get a reply.
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum ActorError {
+    /// No actor of the type was started.
+    #[error("the actor {0} is not started")]
+    NotStarted(&'static str),
+
+    /// The actor stopped, having panicked more often than its options allow.
+    #[error("the actor {0} has stopped")]
+    Stopped(&'static str),
+
+    /// The mailbox was full; only [`Addr::try_send`] fails this way.
+    #[error("the mailbox of the actor {0} is full")]
+    Full(&'static str),
+
+    /// The actor panicked while handling the message, or stopped before reaching it.
+    #[error("the actor {0} did not reply")]
+    NoReply(&'static str),
+}
+
+impl IntoResponse for ActorError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ActorError::Full(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+/// How an actor is run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActorOptions {
+    /// How many messages can wait for the actor before senders have to wait too.
+    pub mailbox: usize,
+    /// Recreate the actor after a panic at most this many times within `restart_window`; one
+    /// panic more stops it for good.
+    pub max_restarts: u32,
+    /// The window `max_restarts` counts restarts in.
+    pub restart_window: Duration,
+}
+
+impl Default for ActorOptions {
+    fn default() -> Self {
+        Self {
+            mailbox: 64,
+            max_restarts: 3,
+            restart_window: Duration::from_secs(5),
+        }
+    }
+}
+
+/// A message in a mailbox, ready to be handled by the actor.
+type Envelope<A> = Box<dyn for<'a> FnOnce(&'a mut A, &'a ActorContext<A>) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> + Send>;
+
+fn envelope<A: Handles<M>, M: Send + 'static>(message: M, reply: Option<oneshot::Sender<A::Reply>>) -> Envelope<A> {
+    Box::new(move |actor, context| {
+        Box::pin(async move {
+            let replied = actor.handle(message, context).await;
+            if let Some(reply) = reply {
+                // The sender may have stopped waiting.
+                let _ = reply.send(replied);
+            }
+        })
+    })
+}
+
+/// The address of a running actor of type `A`. Cheap to clone.
+///
+/// Extract it in a handler as `Addr<A>`; a request fails with `500 Internal Server Error` when no
+/// `A` was started with the application's context.
+pub struct Addr<A: Actor> {
+    mailbox: mpsc::Sender<Envelope<A>>,
+}
+
+impl<A: Actor> Clone for Addr<A> {
+    fn clone(&self) -> Self {
+        Self { mailbox: self.mailbox.clone() }
+    }
+}
+
+impl<A: Actor> Addr<A> {
+    /// The address of the `A` started with `context`, or a clone of it, if there is one.
+    pub fn get(context: &ActorContext<A>) -> Option<Self> {
+        context.extensions.get()
+    }
+
+    /// Send a message and wait for the reply, waiting first for room in the mailbox.
+    pub async fn send<M: Send + 'static>(&self, message: M) -> Result<A::Reply, ActorError>
+    where
+        A: Handles<M>,
+    {
+        let (reply, replied) = oneshot::channel();
+        self.mailbox
+            .send(envelope(message, Some(reply)))
+            .await
+            .map_err(|_| ActorError::Stopped(type_name::<A>()))?;
+        replied.await.map_err(|_| ActorError::NoReply(type_name::<A>()))
+    }
+
+    /// Send a message and wait for the reply, or fail with [`ActorError::Full`] straight away if
+    /// the mailbox is full.
+    pub async fn try_send<M: Send + 'static>(&self, message: M) -> Result<A::Reply, ActorError>
+    where
+        A: Handles<M>,
+    {
+        let (reply, replied) = oneshot::channel();
+        self.mailbox.try_send(envelope(message, Some(reply))).map_err(|error| match error {
+            mpsc::error::TrySendError::Full(_) => ActorError::Full(type_name::<A>()),
+            mpsc::error::TrySendError::Closed(_) => ActorError::Stopped(type_name::<A>()),
+        })?;
+        replied.await.map_err(|_| ActorError::NoReply(type_name::<A>()))
+    }
+
+    /// Send a message without waiting for the reply, waiting only for room in the mailbox.
+    pub async fn tell<M: Send + 'static>(&self, message: M) -> Result<(), ActorError>
+    where
+        A: Handles<M>,
+    {
+        self.mailbox
+            .send(envelope(message, None))
+            .await
+            .map_err(|_| ActorError::Stopped(type_name::<A>()))
+    }
+
+    /// Whether the actor is still running.
+    pub fn is_alive(&self) -> bool {
+        !self.mailbox.is_closed()
+    }
+}
+
+impl<A: Actor> FromRequestParts<ActorContext<A>> for Addr<A> {
+    type Rejection = ActorError;
+
+    async fn from_request_parts(_parts: &mut Parts, context: &ActorContext<A>) -> Result<Self, Self::Rejection> {
+        Self::get(context).ok_or(ActorError::NotStarted(type_name::<A>()))
+    }
+}
+
+/// Starts an application's actors. `GotchaApp::actors` and the builder's `actors` receive one
+/// when the server starts.
+pub struct Actors<S: Clone + Send + Sync + 'static, C: GotchaConfig> {
+    context: GotchaContext<S, C>,
+}
+
+impl<S: Clone + Send + Sync + 'static, C: GotchaConfig> Actors<S, C> {
+    /// Start actors with `context`.
+    pub fn new(context: GotchaContext<S, C>) -> Self {
+        Self { context }
+    }
+
+    /// Start an `A` with the default [`ActorOptions`].
+    pub fn start<A: Actor<State = S, Config = C>>(&mut self) -> Addr<A> {
+        self.start_with(ActorOptions::default())
+    }
+
+    /// Start an `A`. It replaces any `A` started before with this context as the one
+    /// [`Addr::get`] and handlers reach; that one keeps running for the addresses already handed
+    /// out.
+    pub fn start_with<A: Actor<State = S, Config = C>>(&mut self, options: ActorOptions) -> Addr<A> {
+        let (mailbox, envelopes) = mpsc::channel(options.mailbox.max(1));
+        let addr = Addr { mailbox };
+        // The actor's own context does not keep the addresses alive, or its mailbox would never
+        // close.
+        let context = GotchaContext {
+            extensions: self.context.extensions.downgrade(),
+            ..self.context.clone()
+        };
+        tokio::spawn(supervise::<A>(context, envelopes, options));
+        self.context.extensions.insert(addr.clone());
+        addr
+    }
+}
+
+/// Run an actor over its mailbox, recreating it when it panics.
+async fn supervise<A: Actor>(context: ActorContext<A>, mut envelopes: mpsc::Receiver<Envelope<A>>, options: ActorOptions) {
+    let mut restarts = VecDeque::new();
+    loop {
+        let panic = match std::panic::catch_unwind(AssertUnwindSafe(|| A::create(&context))) {
+            Ok(mut actor) => loop {
+                let Some(envelope) = envelopes.recv().await else {
+                    // Every address is gone.
+                    return;
+                };
+                // The actor is dropped after a panic, so state it left half-changed is never seen.
+                if let Err(panic) = AssertUnwindSafe(envelope(&mut actor, &context)).catch_unwind().await {
+                    break panic;
+                }
+            },
+            Err(panic) => panic,
+        };
+        let message = payload_message(&*panic);
+
+        let now = Instant::now();
+        restarts.retain(|restart: &Instant| now.duration_since(*restart) < options.restart_window);
+        if restarts.len() >= options.max_restarts as usize {
+            tracing::error!("actor {} panicked: {message}; stopping it after {} restarts", type_name::<A>(), restarts.len());
+            // Senders waiting for a reply get `NoReply`, later ones `Stopped`.
+            envelopes.close();
+            return;
+        }
+        restarts.push_back(now);
+        tracing::error!("actor {} panicked: {message}; restarting it", type_name::<A>());
+    }
+}
@@ -30,15 +30,21 @@ use serde::{Deserialize, Serialize};
 use tower_layer::Layer;
 use tower_service::Service;
 
+use crate::actor::Actors;
 use crate::config::{Config, ConfigBuilder, ConfigState, ConfigWrapper, GotchaConfigLoader, ServerConfig};
 use crate::error::{GotchaError, GotchaResult};
+use crate::message::{MessageLayer, MessageLayers};
 use crate::router::{GotchaRouter, Responder};
 use crate::GotchaContext;
 
+/// A one-shot closure that starts actors with the application's context when the
+/// server starts.
+type ActorStarter<S, C> = Box<dyn FnOnce(&mut Actors<S, C>) + Send>;
+
 /// A one-shot closure that registers background tasks on the scheduler when the
 /// server starts.
 #[cfg(feature = "task")]
-type TaskRegistrar<S, C> = Box<dyn FnOnce(&mut crate::TaskScheduler<S, C>) + Send>;
+type TaskRegistrar<S, C> = Box<dyn FnOnce(&mut crate::TaskScheduler<S, C>) -> GotchaResult<()> + Send>;
 
 /// Default empty configuration for simple applications
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -62,6 +68,8 @@ where
     config_builder: Option<ConfigState>,
     #[cfg(feature = "task")]
     tasks: Vec<TaskRegistrar<S, C>>,
+    message_layers: MessageLayers,
+    actors: Vec<ActorStarter<S, C>>,
 }
 
 impl Default for Gotcha<EmptyState, EmptyConfig> {
@@ -90,6 +98,8 @@ impl Gotcha<EmptyState, EmptyConfig> {
             config_builder: None,
             #[cfg(feature = "task")]
             tasks: Vec::new(),
+            message_layers: MessageLayers::new(),
+            actors: Vec::new(),
         }
     }
 }
@@ -129,6 +139,8 @@ impl Gotcha {
             config_builder: None,
             #[cfg(feature = "task")]
             tasks: Vec::new(),
+            message_layers: MessageLayers::new(),
+            actors: Vec::new(),
         }
     }
 
@@ -161,6 +173,8 @@ impl Gotcha {
             config_builder: None,
             #[cfg(feature = "task")]
             tasks: Vec::new(),
+            message_layers: MessageLayers::new(),
+            actors: Vec::new(),
         }
     }
 
@@ -196,6 +210,8 @@ impl Gotcha {
             config_builder: None,
             #[cfg(feature = "task")]
             tasks: Vec::new(),
+            message_layers: MessageLayers::new(),
+            actors: Vec::new(),
         }
     }
 }
@@ -602,11 +618,41 @@ where
         self
     }
 
+    /// Assemble the OpenAPI document for the routes registered so far, without serving it.
+    ///
+    /// Like serving, this applies the configuration's `[server.docs]` and `[server.openapi]`
+    /// sections. See [`GotchaRouter::openapi_spec`](crate::GotchaRouter::openapi_spec).
+    #[cfg(feature = "openapi")]
+    pub fn openapi_spec(self) -> serde_json::Value {
+        let config = self.resolve_config();
+        self.router.configured(&config.server).openapi_spec()
+    }
+
+    /// Serve the spec as the given OpenAPI version. Defaults to 3.0.
+    ///
+    /// See [`GotchaRouter::openapi_version`](crate::GotchaRouter::openapi_version).
+    #[cfg(feature = "openapi")]
+    pub fn openapi_version(mut self, version: crate::openapi::OpenApiVersion) -> Self {
+        self.router = self.router.openapi_version(version);
+        self
+    }
+
+    /// Document types that differ between requests and responses as two components.
+    ///
+    /// See [`GotchaRouter::split_schemas`](crate::GotchaRouter::split_schemas).
+    #[cfg(feature = "openapi")]
+    pub fn split_schemas(mut self, split: bool) -> Self {
+        self.router = self.router.split_schemas(split);
+        self
+    }
+
     /// Register background tasks (requires the `task` feature).
     ///
     /// The closure receives a [`TaskScheduler`](crate::TaskScheduler) when the
     /// server starts, on which you can register `cron` / `interval` jobs. This
-    /// brings the builder to parity with `GotchaApp::tasks`.
+    /// brings the builder to parity with `GotchaApp::tasks`; an error (such as an
+    /// invalid cron expression) stops the server from starting. Tasks declared with
+    /// `#[task]` need no registration.
     ///
     /// # Example
     /// ```no_run
@@ -616,18 +662,63 @@ where
     /// let app = Gotcha::new().tasks(|scheduler| {
     ///     scheduler.interval("heartbeat", Duration::from_secs(60), |_ctx| async move {
     ///         tracing::info!("tick");
-    ///     });
+    ///     })?;
+    ///     Ok(())
     /// });
     /// ```
     #[cfg(feature = "task")]
     pub fn tasks<F>(mut self, register: F) -> Self
     where
-        F: FnOnce(&mut crate::TaskScheduler<S, C>) + Send + 'static,
+        F: FnOnce(&mut crate::TaskScheduler<S, C>) -> GotchaResult<()> + Send + 'static,
     {
         self.tasks.push(Box::new(register));
         self
     }
 
+    /// Mount the task admin routes under `path`.
+    ///
+    /// See [`GotchaRouter::task_admin`](crate::GotchaRouter::task_admin).
+    #[cfg(feature = "task")]
+    pub fn task_admin(mut self, path: &str) -> Self {
+        self.router = self.router.task_admin(path);
+        self
+    }
+
+    /// Add a [`MessageLayer`] around every message the application's
+    /// [`Messager`](crate::Messager)s dispatch. Layers added first run outermost.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use gotcha::message::{MetricsLayer, TracingLayer};
+    /// use gotcha::prelude::*;
+    ///
+    /// let app = Gotcha::new().message_layer(TracingLayer).message_layer(MetricsLayer::new());
+    /// ```
+    pub fn message_layer(mut self, layer: impl MessageLayer) -> Self {
+        self.message_layers = self.message_layers.layer(layer);
+        self
+    }
+
+    /// Start actors when the server starts.
+    ///
+    /// The closure receives the [`Actors`] of the application context; handlers then extract the
+    /// started actors' [`Addr`](crate::actor::Addr)esses. This is the builder's
+    /// `GotchaApp::actors`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let app = Gotcha::with_state::<AppState>().actors(|actors| {
+    ///     actors.start::<Visits>();
+    /// });
+    /// ```
+    pub fn actors<F>(mut self, start: F) -> Self
+    where
+        F: FnOnce(&mut Actors<S, C>) + Send + 'static,
+    {
+        self.actors.push(Box::new(start));
+        self
+    }
+
     /// Add CORS support (requires "cors" feature)
     #[cfg(feature = "cors")]
     pub fn with_cors(self) -> Self {
@@ -635,6 +726,40 @@ where
         self.layer(CorsLayer::permissive())
     }
 
+    /// Set the spec's metadata: title, version, description, servers, tags and so on.
+    ///
+    /// See [`GotchaRouter::info`](crate::GotchaRouter::info).
+    #[cfg(feature = "openapi")]
+    pub fn info(mut self, info: crate::openapi::ApiInfo) -> Self {
+        self.router = self.router.info(info);
+        self
+    }
+
+    /// Choose where the spec and documentation UIs are mounted, or turn them off.
+    ///
+    /// See [`GotchaRouter::docs`](crate::GotchaRouter::docs).
+    #[cfg(feature = "openapi")]
+    pub fn docs(mut self, docs: crate::openapi::DocsConfig) -> Self {
+        self.router = self.router.docs(docs);
+        self
+    }
+
+    /// Wrap only the spec and documentation routes in `layer`, e.g. for authentication.
+    ///
+    /// See [`GotchaRouter::docs_layer`](crate::GotchaRouter::docs_layer).
+    #[cfg(feature = "openapi")]
+    pub fn docs_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<axum::routing::Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: Responder + 'static,
+        <L::Service as Service<Request>>::Error: Into<std::convert::Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.router = self.router.docs_layer(layer);
+        self
+    }
+
     /// Add OpenAPI support (requires "openapi" feature)  
     #[cfg(feature = "openapi")]
     pub fn with_openapi(self) -> Self {
@@ -670,20 +795,27 @@ where
     pub async fn listen_on(self, addr: SocketAddr) -> GotchaResult<()> {
         tracing::info!("🚀 Starting Gotcha server on {}", addr);
 
-        let context = self.build_context().await?;
+        let context = self.build_context().await?.with_message_layers(self.message_layers);
+        let mut actors = Actors::new(context.clone());
+        for start in self.actors {
+            start(&mut actors);
+        }
 
         #[cfg(feature = "task")]
-        {
-            let tasks = self.tasks;
-            if !tasks.is_empty() {
-                let mut scheduler = crate::TaskScheduler::new(context.clone());
-                for register in tasks {
-                    register(&mut scheduler);
-                }
-            }
+        let mut scheduler = crate::TaskScheduler::new(context.clone());
+        #[cfg(feature = "task")]
+        for register in self.tasks {
+            register(&mut scheduler)?;
         }
+        #[cfg(feature = "task")]
+        scheduler.register_declared()?;
 
-        let app_router = self.router.into_axum_router(context);
+        let router = self.router;
+        #[cfg(feature = "openapi")]
+        let router = router.configured(&context.config.server);
+        let app_router = router.into_axum_router(context);
+        #[cfg(feature = "task")]
+        let app_router = app_router.layer(axum::Extension(scheduler.registry()));
 
         let listener = tokio::net::TcpListener::bind(addr).await.map_err(|source| GotchaError::Bind {
             addr: addr.to_string(),
@@ -691,8 +823,10 @@ where
         })?;
         tracing::info!("✅ Server listening on http://{}", addr);
 
-        axum::serve(listener, app_router).await.map_err(GotchaError::Io)?;
-        Ok(())
+        let served = axum::serve(listener, app_router).await.map_err(GotchaError::Io);
+        #[cfg(feature = "task")]
+        scheduler.shutdown().await;
+        served
     }
 
     /// Start the server using the configured host and port
@@ -703,11 +837,22 @@ where
     }
 
     /// Build the application context (loads configuration and resolves state).
+    async fn build_context(&self) -> GotchaResult<GotchaContext<S, C>> {
+        let config = self.resolve_config();
+        let state = match &self.state {
+            Some(state) => state.clone(),
+            None => S::default(),
+        };
+
+        Ok(GotchaContext::new(config, state))
+    }
+
+    /// Load the configuration.
     ///
     /// On configuration failure this logs a warning and falls back to defaults,
     /// keeping the builder lenient. Use the trait API for strict config loading.
-    async fn build_context(&self) -> GotchaResult<GotchaContext<S, C>> {
-        let config = match (&self.config, &self.config_builder) {
+    fn resolve_config(&self) -> ConfigWrapper<C> {
+        match (&self.config, &self.config_builder) {
             // Explicit config wins
             (Some(config), _) => config.clone(),
             // Accumulated configuration sources
@@ -724,8 +869,14 @@ where
                             server: ServerConfig {
                                 host: self.host.clone(),
                                 port: self.port,
+                                #[cfg(feature = "openapi")]
+                                docs: None,
+                                #[cfg(feature = "openapi")]
+                                openapi: None,
                             },
                             app: C::default(),
+                            #[cfg(feature = "task")]
+                            tasks: Default::default(),
                         }
                     }
                 }
@@ -739,19 +890,18 @@ where
                         server: ServerConfig {
                             host: self.host.clone(),
                             port: self.port,
+                            #[cfg(feature = "openapi")]
+                            docs: None,
+                            #[cfg(feature = "openapi")]
+                            openapi: None,
                         },
                         app: C::default(),
+                        #[cfg(feature = "task")]
+                        tasks: Default::default(),
                     }
                 }
             },
-        };
-
-        let state = match &self.state {
-            Some(state) => state.clone(),
-            None => S::default(),
-        };
-
-        Ok(GotchaContext { config, state })
+        }
     }
 }
 
@@ -1,5 +1,7 @@
 //! Simplified configuration system built on mofa
 
+#[cfg(feature = "task")]
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use mofa::{ConfigLoader, EnvironmentSource, FileSource};
@@ -21,7 +23,8 @@ pub type ConfigResult<T> = Result<T, ConfigError>;
 /// The loaded configuration: the application's own settings plus the framework's.
 ///
 /// The application's settings are **flattened to the top level** of the file, so they read as the
-/// primary content and the framework's own settings sit in a reserved `[server]` section:
+/// primary content and the framework's own settings sit in reserved sections: `[server]`, and
+/// `[tasks]` with the `task` feature.
 ///
 /// ```toml
 /// name = "my-app"
@@ -41,6 +44,13 @@ pub struct ConfigWrapper<T: DeserializeOwned + Serialize + Default> {
     #[serde(default)]
     pub server: ServerConfig,
 
+    /// The `[tasks.<name>]` sections, which disable or reschedule registered tasks. See
+    /// [`TaskConfig`](crate::task::TaskConfig).
+    #[cfg(feature = "task")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "task")))]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tasks: BTreeMap<String, crate::task::TaskConfig>,
+
     /// The application's own settings, living at the top level of the file.
     #[serde(bound = "", flatten)]
     pub app: T,
@@ -60,6 +70,18 @@ pub struct ServerConfig {
     pub host: String,
     /// Port the server listens on.
     pub port: u16,
+    /// The `[server.docs]` section. When present it replaces the router's
+    /// [`docs`](crate::GotchaRouter::docs) settings.
+    #[cfg(feature = "openapi")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "openapi")))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<crate::openapi::DocsConfig>,
+    /// The `[server.openapi]` section: the spec's title, version, servers, tags and so on,
+    /// layered over the router's [`info`](crate::GotchaRouter::info).
+    #[cfg(feature = "openapi")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "openapi")))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openapi: Option<crate::openapi::ApiInfo>,
 }
 
 impl Default for ServerConfig {
@@ -67,6 +89,10 @@ impl Default for ServerConfig {
         Self {
             host: "127.0.0.1".to_string(),
             port: 3000,
+            #[cfg(feature = "openapi")]
+            docs: None,
+            #[cfg(feature = "openapi")]
+            openapi: None,
         }
     }
 }
@@ -240,10 +266,7 @@ mod tests {
 
     #[test]
     fn test_config_wrapper() {
-        let wrapper = ConfigWrapper {
-            server: ServerConfig::default(),
-            app: TestConfig::default(),
-        };
+        let wrapper = ConfigWrapper::<TestConfig>::default();
 
         assert_eq!(wrapper.app.name, "");
         // Deref reaches the application config without going through a wrapper level.
@@ -32,6 +32,16 @@ pub enum GotchaError {
     #[error(transparent)]
     Io(std::io::Error),
 
+    /// A background task could not be scheduled.
+    #[cfg(feature = "task")]
+    #[error(transparent)]
+    Task(#[from] crate::task::TaskError),
+
+    /// A background job could not be enqueued, or the job queue could not start.
+    #[cfg(feature = "job")]
+    #[error(transparent)]
+    Job(#[from] crate::job::JobError),
+
     /// A generic, framework-level error message.
     #[error("{0}")]
     Message(String),
@@ -0,0 +1,310 @@
+//! # Event Module
+//!
+//! A publish/subscribe event bus on top of the message system. A [`Message`](crate::Message) has
+//! exactly one handler; an event published with [`Messager::publish`] reaches every subscriber of
+//! its type, and every open [`EventStream`] for it.
+//!
+//! Subscribers are async functions declared with `#[subscribe]`. The first argument is the event,
+//! and the optional second one is the application's `GotchaContext<S, C>` or `Messager<S, C>`. A
+//! subscriber that takes a context only receives events published with a context of that type.
+//!
+//! ```rust,ignore
+//! use gotcha::prelude::*;
+//! use gotcha::event::EventStream;
+//! use gotcha::{subscribe, Messager};
+//!
+//! #[derive(Clone, Serialize)]
+//! struct OrderPlaced { id: u64, total: u64 }
+//!
+//! #[subscribe]
+//! async fn send_receipt(order: OrderPlaced, context: GotchaContext<AppState, Config>) -> Result<(), MailError> {
+//!     context.state.mailer.receipt(order.id).await
+//! }
+//!
+//! #[subscribe(name = "audit")]
+//! async fn audit(order: OrderPlaced) {
+//!     tracing::info!("order {} placed", order.id);
+//! }
+//!
+//! async fn place(State(messager): State<Messager<AppState, Config>>) -> impl Responder {
+//!     messager.publish(OrderPlaced { id: 7, total: 1200 });
+//! }
+//!
+//! // Forward large orders to browsers as server-sent events.
+//! async fn feed(orders: EventStream<OrderPlaced>) -> impl Responder {
+//!     orders.filter(|order| order.total > 1000).into_sse()
+//! }
+//! ```
+//!
+//! Each subscriber has its own worker, which handles the subscriber's events one at a time, in the
+//! order they were published. A subscriber that returns an `Err` or panics is logged, and its
+//! worker moves on to the next event; other subscribers and the publisher are unaffected. A slow
+//! subscriber only delays its own events, which queue up meanwhile — up to 256 of them. Events
+//! published while 256 are queued are dropped for that subscriber, with a warning, so a subscriber
+//! that cannot keep up never holds up the publisher or grows without bound.
+//!
+//! The workers and streams belong to the application's [`GotchaContext`]: an event reaches the
+//! streams opened with the context it was published with, and the subscribers' workers of one
+//! application never queue another's events.
+//!
+//! Delivery is in-process and in memory: events published while a subscriber is behind are lost
+//! if the process stops. Use a `JobQueue` (the `job` feature) for work that must not be lost.
+
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::{pin, Pin};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::ws::{self, WebSocket};
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::response::sse::{self, KeepAlive, Sse};
+use futures_util::future::{select, Either};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::mpsc;
+
+use crate::message::{panic_message, Messager};
+use crate::{GotchaConfig, GotchaContext};
+
+/// A delivery of an event to one subscriber: `Err` carries the failure message.
+pub type EventFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+/// Everything the `#[subscribe]` macro records about one subscriber, collected via `inventory`.
+pub struct SubscriberDeclaration {
+    /// The name the subscriber is logged under.
+    pub name: &'static str,
+    /// The type of event it subscribes to.
+    pub event: fn() -> TypeId,
+    /// Deliver an event, given as a `&E`, with the publisher's `&GotchaContext<S, C>`. `None`
+    /// when the subscriber takes a context of another type.
+    pub deliver: fn(&dyn Any, &dyn Any) -> Option<EventFuture>,
+}
+
+inventory::collect!(SubscriberDeclaration);
+
+/// The second argument of a `#[subscribe]` function.
+pub trait FromEventContext: Sized {
+    /// Extract the argument from the publisher's `&GotchaContext<S, C>`, or `None` if it is a
+    /// context of another application.
+    fn from_event_context(context: &dyn Any) -> Option<Self>;
+}
+
+impl<S: Clone + Send + Sync + 'static, C: GotchaConfig> FromEventContext for GotchaContext<S, C> {
+    fn from_event_context(context: &dyn Any) -> Option<Self> {
+        context.downcast_ref::<Self>().cloned()
+    }
+}
+
+impl<S: Clone + Send + Sync + 'static, C: GotchaConfig> FromEventContext for Messager<S, C> {
+    fn from_event_context(context: &dyn Any) -> Option<Self> {
+        GotchaContext::from_event_context(context).map(Messager::new)
+    }
+}
+
+/// The event bus of one application, kept on its [`GotchaContext`]. Its workers stop once every
+/// clone of the context is gone.
+#[derive(Clone, Default)]
+struct Bus(Arc<Channels>);
+
+#[derive(Default)]
+struct Channels {
+    /// The worker of each subscriber, keyed by the address of its declaration.
+    workers: Mutex<HashMap<usize, mpsc::Sender<EventFuture>>>,
+    /// The `broadcast::Sender<E>` behind the [`EventStream`]s of each event type `E`.
+    streams: Mutex<HashMap<TypeId, Box<dyn Any + Send>>>,
+}
+
+impl Bus {
+    fn of<S: Clone + Send + Sync + 'static, C: GotchaConfig>(context: &GotchaContext<S, C>) -> Self {
+        context.extensions.get_or_insert_with(Bus::default)
+    }
+}
+
+/// How many events a subscriber can fall behind by before the next ones are dropped for it.
+const SUBSCRIBER_CAPACITY: usize = 256;
+
+/// How many events an [`EventStream`] can fall behind by before it misses some.
+const STREAM_CAPACITY: usize = 256;
+
+/// Hand `event` to its subscribers' workers and to its streams. Returns how many subscribers
+/// it was handed to.
+pub(crate) fn publish<S: Clone + Send + Sync + 'static, C: GotchaConfig, E: Clone + Send + Sync + 'static>(context: &GotchaContext<S, C>, event: E) -> usize {
+    let bus = Bus::of(context);
+    let mut delivered = 0;
+    for declaration in inventory::iter::<SubscriberDeclaration> {
+        if (declaration.event)() != TypeId::of::<E>() {
+            continue;
+        }
+        if let Some(delivery) = (declaration.deliver)(&event, context) {
+            delivered += usize::from(deliver::<E>(&bus, declaration, delivery));
+        }
+    }
+    let streams = bus.0.streams.lock().expect("event streams poisoned");
+    if let Some(sender) = streams.get(&TypeId::of::<E>()).and_then(|sender| sender.downcast_ref::<broadcast::Sender<E>>()) {
+        // Fails only when no stream is open.
+        let _ = sender.send(event);
+    }
+    delivered
+}
+
+/// Queue a delivery on the subscriber's worker, starting the worker if it has none. Returns
+/// whether it was queued, rather than dropped because the worker is too far behind.
+fn deliver<E>(bus: &Bus, declaration: &'static SubscriberDeclaration, delivery: EventFuture) -> bool {
+    let key = declaration as *const SubscriberDeclaration as usize;
+    let mut workers = bus.0.workers.lock().expect("event workers poisoned");
+    let delivery = match workers.get(&key) {
+        Some(worker) => match worker.try_send(delivery) {
+            Ok(()) => return true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                tracing::warn!(
+                    "event subscriber {:?} is {SUBSCRIBER_CAPACITY} events behind; dropping a {} event for it",
+                    declaration.name,
+                    event_name::<E>()
+                );
+                return false;
+            }
+            // The worker stopped with the runtime it was running on.
+            Err(mpsc::error::TrySendError::Closed(delivery)) => delivery,
+        },
+        None => delivery,
+    };
+    let (worker, deliveries) = mpsc::channel(SUBSCRIBER_CAPACITY);
+    tokio::spawn(work(declaration.name, deliveries));
+    let _ = worker.try_send(delivery);
+    workers.insert(key, worker);
+    true
+}
+
+/// Run a subscriber's deliveries one at a time.
+async fn work(name: &'static str, mut deliveries: mpsc::Receiver<EventFuture>) {
+    while let Some(delivery) = deliveries.recv().await {
+        // Spawned, so a panic fails this delivery instead of stopping the worker.
+        match tokio::spawn(delivery).await {
+            Ok(Ok(())) => {}
+            Ok(Err(error)) => tracing::error!("event subscriber {name:?} failed: {error}"),
+            Err(join_error) => tracing::error!("event subscriber {name:?} panicked: {}", panic_message(join_error)),
+        }
+    }
+}
+
+/// Selects the events an [`EventStream`] receives.
+type Filter<E> = Box<dyn Fn(&E) -> bool + Send + Sync>;
+
+/// The events of type `E` published from now on, for forwarding to clients over SSE or a
+/// WebSocket.
+///
+/// Extract it in a handler as `EventStream<E>` to receive the application's events.
+///
+/// A stream that falls more than 256 events behind misses the oldest ones, and logs how many.
+pub struct EventStream<E> {
+    events: broadcast::Receiver<E>,
+    keep: Option<Filter<E>>,
+}
+
+impl<E: Clone + Send + Sync + 'static> EventStream<E> {
+    /// Start receiving the events of type `E` published with `context`, or a clone of it.
+    pub fn new<S: Clone + Send + Sync + 'static, C: GotchaConfig>(context: &GotchaContext<S, C>) -> Self {
+        let bus = Bus::of(context);
+        let mut streams = bus.0.streams.lock().expect("event streams poisoned");
+        let sender = streams
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(broadcast::channel::<E>(STREAM_CAPACITY).0));
+        let sender = sender.downcast_ref::<broadcast::Sender<E>>().expect("streams are keyed by their event type");
+        Self {
+            events: sender.subscribe(),
+            keep: None,
+        }
+    }
+
+    /// Only receive the events `keep` returns `true` for.
+    pub fn filter(mut self, keep: impl Fn(&E) -> bool + Send + Sync + 'static) -> Self {
+        self.keep = Some(Box::new(keep));
+        self
+    }
+
+    /// The next event.
+    pub async fn recv(&mut self) -> Option<E> {
+        loop {
+            match self.events.recv().await {
+                Ok(event) if self.keep.as_ref().is_none_or(|keep| keep(&event)) => return Some(event),
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => tracing::warn!("an event stream fell behind and missed {missed} {} events", type_name::<E>()),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// The events as a [`Stream`].
+    pub fn into_stream(self) -> impl Stream<Item = E> + Send {
+        stream::unfold(self, |mut events| async move { events.recv().await.map(|event| (event, events)) })
+    }
+}
+
+impl<S, C, E> FromRequestParts<GotchaContext<S, C>> for EventStream<E>
+where
+    S: Clone + Send + Sync + 'static,
+    C: GotchaConfig,
+    E: Clone + Send + Sync + 'static,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(_parts: &mut Parts, context: &GotchaContext<S, C>) -> Result<Self, Self::Rejection> {
+        Ok(Self::new(context))
+    }
+}
+
+impl<E: Clone + Send + Sync + Serialize + 'static> EventStream<E> {
+    /// An SSE response sending each event as JSON, named after its type: an `OrderPlaced` event
+    /// arrives in the browser as `event: OrderPlaced`.
+    pub fn into_sse(self) -> Sse<impl Stream<Item = Result<sse::Event, Infallible>>> {
+        let name = event_name::<E>();
+        let events = self.into_stream().filter_map(move |event| async move {
+            match sse::Event::default().event(name).json_data(&event) {
+                Ok(event) => Some(Ok(event)),
+                Err(error) => {
+                    tracing::warn!("could not serialize a {name} event: {error}");
+                    None
+                }
+            }
+        });
+        Sse::new(events).keep_alive(KeepAlive::default())
+    }
+
+    /// Send each event to `socket` as a JSON text message, until the client closes it.
+    pub async fn forward(mut self, mut socket: WebSocket) {
+        loop {
+            let next = match select(pin!(self.recv()), pin!(socket.recv())).await {
+                Either::Left((event, _)) => Either::Left(event),
+                Either::Right((incoming, _)) => Either::Right(incoming),
+            };
+            match next {
+                Either::Left(Some(event)) => {
+                    let text = match serde_json::to_string(&event) {
+                        Ok(text) => text,
+                        Err(error) => {
+                            tracing::warn!("could not serialize a {} event: {error}", event_name::<E>());
+                            continue;
+                        }
+                    };
+                    if socket.send(ws::Message::Text(text.into())).await.is_err() {
+                        return;
+                    }
+                }
+                Either::Left(None) | Either::Right(None | Some(Err(_) | Ok(ws::Message::Close(_)))) => return,
+                // Anything else the client sends is ignored.
+                Either::Right(Some(Ok(_))) => {}
+            }
+        }
+    }
+}
+
+/// The name of `E` without its path or generic parameters.
+fn event_name<E>() -> &'static str {
+    let name = type_name::<E>();
+    let name = &name[..name.find('<').unwrap_or(name.len())];
+    name.rsplit("::").next().unwrap_or(name)
+}
@@ -0,0 +1,70 @@
+//! Values the framework keeps per application, alongside its state: the event bus, the message
+//! layers and the actors' addresses. They live on the [`GotchaContext`](crate::GotchaContext), so
+//! two applications in one process — or two tests — never see each other's.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, Weak};
+
+type Map = RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>;
+
+/// A map from a type to one value of it, shared by every clone of a context.
+#[derive(Clone, Default)]
+pub(crate) struct Extensions(Handle);
+
+#[derive(Clone)]
+enum Handle {
+    Strong(Arc<Map>),
+    /// Held by what the values themselves own, such as an actor's task, so that they do not keep
+    /// each other alive once the application is gone.
+    Weak(Weak<Map>),
+}
+
+impl Default for Handle {
+    fn default() -> Self {
+        Handle::Strong(Arc::default())
+    }
+}
+
+impl Extensions {
+    fn map(&self) -> Option<Arc<Map>> {
+        match &self.0 {
+            Handle::Strong(map) => Some(map.clone()),
+            Handle::Weak(map) => map.upgrade(),
+        }
+    }
+
+    /// The `T` stored, if any.
+    pub(crate) fn get<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        let map = self.map()?;
+        let map = map.read().expect("context extensions poisoned");
+        map.get(&TypeId::of::<T>()).and_then(|value| value.downcast_ref::<T>()).cloned()
+    }
+
+    /// Store `value`, replacing the `T` stored before. Once the application is gone, `value` is
+    /// dropped instead.
+    pub(crate) fn insert<T: Send + Sync + 'static>(&self, value: T) {
+        if let Some(map) = self.map() {
+            map.write().expect("context extensions poisoned").insert(TypeId::of::<T>(), Arc::new(value));
+        }
+    }
+
+    /// The `T` stored, storing `init()` first if there is none. Once the application is gone,
+    /// `init()` is returned without being stored.
+    pub(crate) fn get_or_insert_with<T: Clone + Send + Sync + 'static>(&self, init: impl FnOnce() -> T) -> T {
+        let Some(map) = self.map() else {
+            return init();
+        };
+        let mut map = map.write().expect("context extensions poisoned");
+        let value = map.entry(TypeId::of::<T>()).or_insert_with(|| Arc::new(init()));
+        value.downcast_ref::<T>().expect("extensions are keyed by their type").clone()
+    }
+
+    /// The same values, without keeping them alive.
+    pub(crate) fn downgrade(&self) -> Self {
+        match &self.0 {
+            Handle::Strong(map) => Extensions(Handle::Weak(Arc::downgrade(map))),
+            Handle::Weak(map) => Extensions(Handle::Weak(map.clone())),
+        }
+    }
+}
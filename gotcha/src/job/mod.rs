@@ -0,0 +1,374 @@
+//! # Job Module
+//!
+//! A durable queue of background jobs. A job is a [`Message`] that is serialized into a
+//! [`JobStore`] when enqueued and handled later by a pool of workers.
+//!
+//! Compared with [`Messager::spawn`](crate::Messager::spawn):
+//!
+//! - an enqueued job survives a restart when the store does ([`FileStore`])
+//! - a failed job is retried with exponential backoff, then kept as a dead letter
+//! - a job can be delayed, or scheduled for a given time
+//! - workers run a bounded number of jobs at once, and the queue can refuse new jobs when full
+//!
+//! ```rust,ignore
+//! use gotcha::job::{FileStore, JobQueue};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct SendEmail { to: String }
+//!
+//! #[async_trait]
+//! impl Message<AppState, Config> for SendEmail {
+//!     type Output = Result<(), MailError>;
+//!     async fn handle(self, messager: Messager<AppState, Config>) -> Self::Output {
+//!         messager.state().mailer.send(&self.to).await
+//!     }
+//! }
+//!
+//! let jobs = JobQueue::new(FileStore::new("data/jobs.json")).register::<SendEmail>("send_email");
+//! jobs.start(context.clone()).await?;
+//! jobs.enqueue(SendEmail { to: "ada@example.com".into() }).await?;
+//! ```
+
+use std::any::{type_name, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
+use uuid::Uuid;
+
+use crate::message::panic_message;
+use crate::message::{Message, Messager};
+use crate::outcome::Outcome;
+use crate::retry::RetryPolicy;
+use crate::{GotchaConfig, GotchaContext};
+
+pub mod store;
+
+pub use store::{FileStore, JobStore, MemoryStore};
+
+/// Identifies an enqueued job.
+pub type JobId = Uuid;
+
+/// A job as a [`JobStore`] keeps it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobRecord {
+    /// The job's id.
+    pub id: JobId,
+    /// The kind its message type was registered as.
+    pub kind: String,
+    /// The serialized message.
+    pub payload: serde_json::Value,
+    /// When it was enqueued.
+    pub enqueued_at: DateTime<Utc>,
+    /// When it is due: when it was scheduled for, or when its next retry is.
+    pub run_at: DateTime<Utc>,
+    /// How many times it has run.
+    pub attempts: u32,
+    /// Why its last attempt failed.
+    pub last_error: Option<String>,
+}
+
+/// Why a job could not be enqueued.
+#[derive(Debug, Error)]
+pub enum JobError {
+    /// The message type was not [`register`](JobQueue::register)ed.
+    #[error("no job kind is registered for {0}")]
+    Unregistered(&'static str),
+
+    /// The message could not be serialized.
+    #[error("a {kind:?} job could not be serialized: {source}")]
+    Serialize {
+        /// The kind of the job.
+        kind: String,
+        /// Why serialization failed.
+        source: serde_json::Error,
+    },
+
+    /// The job's delay puts it past the latest time that can be represented.
+    #[error("a job cannot be delayed by {0:?}")]
+    Delay(Duration),
+
+    /// The queue already holds [`JobOptions::capacity`] pending jobs.
+    #[error("the job queue is full ({capacity} jobs pending)")]
+    Full {
+        /// The queue's capacity.
+        capacity: usize,
+    },
+
+    /// The store failed.
+    #[error("the job store failed: {0}")]
+    Store(#[from] io::Error),
+}
+
+/// How a [`JobQueue`] runs its jobs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobOptions {
+    /// How many jobs run at once.
+    pub concurrency: usize,
+    /// How failed jobs are retried before they become dead letters. A job fails by returning an
+    /// `Err` or panicking.
+    pub retry: RetryPolicy,
+    /// Refuse new jobs while this many are pending. `None` never refuses.
+    pub capacity: Option<usize>,
+    /// How often workers look for jobs enqueued by other processes sharing the store.
+    pub poll_interval: Duration,
+}
+
+impl Default for JobOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            retry: RetryPolicy::default(),
+            capacity: None,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A run of a job: `Err` carries the failure message.
+type JobFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+/// Deserializes a job's payload and handles it.
+type Handler<S, C> = Arc<dyn Fn(serde_json::Value, Messager<S, C>) -> JobFuture + Send + Sync>;
+
+struct Inner<S: Clone + Send + Sync + 'static, C: GotchaConfig> {
+    store: Arc<dyn JobStore>,
+    options: JobOptions,
+    handlers: RwLock<HashMap<String, Handler<S, C>>>,
+    kinds: RwLock<HashMap<TypeId, String>>,
+    /// Wakes the dispatcher when a job is enqueued or finishes, or the queue stops.
+    wake: Notify,
+    stopped: AtomicBool,
+    dispatcher: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// A durable queue of [`Message`]s, handled by a pool of workers once [`start`](Self::start)ed.
+///
+/// Cheap to clone; clones share the queue, so one can live in the application state for handlers
+/// to enqueue on.
+pub struct JobQueue<S: Clone + Send + Sync + 'static, C: GotchaConfig> {
+    inner: Arc<Inner<S, C>>,
+}
+
+impl<S: Clone + Send + Sync + 'static, C: GotchaConfig> Clone for JobQueue<S, C> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone() }
+    }
+}
+
+impl<S: Clone + Send + Sync + 'static, C: GotchaConfig> JobQueue<S, C> {
+    /// A queue keeping its jobs in `store`, with the default [`JobOptions`].
+    pub fn new(store: impl JobStore) -> Self {
+        Self::with_options(store, JobOptions::default())
+    }
+
+    /// A queue keeping its jobs in `store`.
+    pub fn with_options(store: impl JobStore, options: JobOptions) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                store: Arc::new(store),
+                options,
+                handlers: RwLock::new(HashMap::new()),
+                kinds: RwLock::new(HashMap::new()),
+                wake: Notify::new(),
+                stopped: AtomicBool::new(false),
+                dispatcher: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Accept messages of type `M`, stored under `kind`. The kind names the type in the store, so
+    /// it must stay the same across releases for stored jobs to be handled.
+    pub fn register<M>(self, kind: impl Into<String>) -> Self
+    where
+        M: Message<S, C> + Serialize + DeserializeOwned,
+        M::Output: Outcome,
+    {
+        let kind = kind.into();
+        let handler: Handler<S, C> = Arc::new(|payload, messager| {
+            Box::pin(async move {
+                let message: M = serde_json::from_value(payload).map_err(|error| format!("invalid payload: {error}"))?;
+                messager.try_send(message).await.map_err(|rejected| rejected.to_string())?.into_result()
+            })
+        });
+        self.inner.handlers.write().expect("job handlers poisoned").insert(kind.clone(), handler);
+        self.inner.kinds.write().expect("job handlers poisoned").insert(TypeId::of::<M>(), kind);
+        self
+    }
+
+    /// Enqueue `message` to run as soon as a worker is free.
+    pub async fn enqueue<M: Message<S, C> + Serialize>(&self, message: M) -> Result<JobId, JobError> {
+        self.enqueue_at(message, Utc::now()).await
+    }
+
+    /// Enqueue `message` to run once `delay` has passed.
+    pub async fn enqueue_in<M: Message<S, C> + Serialize>(&self, message: M, delay: Duration) -> Result<JobId, JobError> {
+        let run_at = chrono::Duration::from_std(delay)
+            .ok()
+            .and_then(|delay| Utc::now().checked_add_signed(delay))
+            .ok_or(JobError::Delay(delay))?;
+        self.enqueue_at(message, run_at).await
+    }
+
+    /// Enqueue `message` to run at `run_at`.
+    pub async fn enqueue_at<M: Message<S, C> + Serialize>(&self, message: M, run_at: DateTime<Utc>) -> Result<JobId, JobError> {
+        let kind = self
+            .inner
+            .kinds
+            .read()
+            .expect("job handlers poisoned")
+            .get(&TypeId::of::<M>())
+            .cloned()
+            .ok_or(JobError::Unregistered(type_name::<M>()))?;
+        let payload = serde_json::to_value(&message).map_err(|source| JobError::Serialize { kind: kind.clone(), source })?;
+        let id = Uuid::new_v4();
+        let capacity = self.inner.options.capacity;
+        let pushed = self
+            .inner
+            .store
+            .push(
+                JobRecord {
+                    id,
+                    kind,
+                    payload,
+                    enqueued_at: Utc::now(),
+                    run_at,
+                    attempts: 0,
+                    last_error: None,
+                },
+                capacity,
+            )
+            .await?;
+        if !pushed {
+            return Err(JobError::Full {
+                capacity: capacity.unwrap_or_default(),
+            });
+        }
+        self.inner.wake.notify_one();
+        Ok(id)
+    }
+
+    /// How many jobs are waiting to run.
+    pub async fn pending(&self) -> Result<usize, JobError> {
+        Ok(self.inner.store.pending().await?)
+    }
+
+    /// The jobs that ran out of retries, oldest first.
+    pub async fn dead_letters(&self) -> Result<Vec<JobRecord>, JobError> {
+        Ok(self.inner.store.dead_letters().await?)
+    }
+
+    /// Start the workers, which hand each job a [`Messager`] for `context`. Jobs left in flight by
+    /// a process that stopped while running them are run again. Starting a started queue does
+    /// nothing.
+    pub async fn start(&self, context: GotchaContext<S, C>) -> Result<(), JobError> {
+        if self.inner.dispatcher.lock().expect("job dispatcher poisoned").is_some() {
+            return Ok(());
+        }
+        self.inner.store.recover().await?;
+        self.inner.stopped.store(false, Ordering::SeqCst);
+        let dispatcher = tokio::spawn(dispatch(self.inner.clone(), context));
+        *self.inner.dispatcher.lock().expect("job dispatcher poisoned") = Some(dispatcher);
+        Ok(())
+    }
+
+    /// Stop taking jobs, and wait for the running ones to finish. Pending jobs stay in the store.
+    pub async fn shutdown(&self) {
+        self.inner.stopped.store(true, Ordering::SeqCst);
+        self.inner.wake.notify_one();
+        let dispatcher = self.inner.dispatcher.lock().expect("job dispatcher poisoned").take();
+        if let Some(dispatcher) = dispatcher {
+            if let Err(join_error) = dispatcher.await {
+                tracing::error!("the job dispatcher stopped abnormally: {join_error}");
+            }
+        }
+    }
+}
+
+/// Claim due jobs and run them, up to the concurrency limit, until the queue stops.
+async fn dispatch<S: Clone + Send + Sync + 'static, C: GotchaConfig>(inner: Arc<Inner<S, C>>, context: GotchaContext<S, C>) {
+    let permits = Arc::new(Semaphore::new(inner.options.concurrency.max(1)));
+    let mut running = JoinSet::new();
+    while !inner.stopped.load(Ordering::SeqCst) {
+        while running.try_join_next().is_some() {}
+        let permit = tokio::select! {
+            permit = permits.clone().acquire_owned() => permit.expect("the worker permits are never closed"),
+            _ = inner.wake.notified() => continue,
+        };
+        let wait = match inner.store.claim(Utc::now()).await {
+            Ok(Some(job)) => {
+                running.spawn(run(inner.clone(), Messager::new(context.clone()), job, permit));
+                continue;
+            }
+            Ok(None) => match inner.store.next_run_at().await {
+                Ok(Some(next)) => (next - Utc::now()).to_std().unwrap_or(Duration::ZERO).min(inner.options.poll_interval),
+                _ => inner.options.poll_interval,
+            },
+            Err(error) => {
+                tracing::error!("could not claim a job: {error}");
+                inner.options.poll_interval
+            }
+        };
+        drop(permit);
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = inner.wake.notified() => {}
+        }
+    }
+    while running.join_next().await.is_some() {}
+}
+
+/// Run one job, then complete, retry or bury it.
+async fn run<S: Clone + Send + Sync + 'static, C: GotchaConfig>(
+    inner: Arc<Inner<S, C>>, messager: Messager<S, C>, mut job: JobRecord, _permit: OwnedSemaphorePermit,
+) {
+    let handler = inner.handlers.read().expect("job handlers poisoned").get(&job.kind).cloned();
+    let outcome = match handler {
+        // Spawned, so a panic fails the job instead of taking the worker down.
+        Some(handler) => tokio::spawn(handler(job.payload.clone(), messager))
+            .await
+            .unwrap_or_else(|join_error| Err(panic_message(join_error))),
+        None => Err(format!("no handler is registered for {:?} jobs", job.kind)),
+    };
+    job.attempts += 1;
+    let stored = match outcome {
+        Ok(()) => inner.store.complete(job.id).await,
+        Err(error) => {
+            let retry = &inner.options.retry;
+            job.last_error = Some(error.clone());
+            if job.attempts <= retry.max_retries {
+                let backoff = retry.backoff(job.attempts);
+                tracing::warn!(
+                    "{:?} job {} failed: {error}; retry {}/{} in {backoff:?}",
+                    job.kind,
+                    job.id,
+                    job.attempts,
+                    retry.max_retries
+                );
+                // A backoff too long to represent waits until the end of time rather than failing.
+                job.run_at = chrono::Duration::from_std(backoff)
+                    .ok()
+                    .and_then(|backoff| Utc::now().checked_add_signed(backoff))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC);
+                inner.store.release(job).await
+            } else {
+                tracing::error!("{:?} job {} failed: {error}; moved to the dead letters", job.kind, job.id);
+                inner.store.bury(job).await
+            }
+        }
+    };
+    if let Err(error) = stored {
+        tracing::error!("could not record a job's outcome: {error}");
+    }
+    inner.wake.notify_one();
+}
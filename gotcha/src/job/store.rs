@@ -0,0 +1,221 @@
+//! Where a [`JobQueue`](super::JobQueue) keeps its jobs, behind the [`JobStore`] trait.
+
+use std::fs::OpenOptions;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{JobId, JobRecord};
+
+/// Keeps jobs between enqueueing and completion, so they survive the process. Every job is in one
+/// of three places: pending (waiting for its `run_at`), in flight (claimed by a worker), or dead
+/// (out of retries).
+#[async_trait]
+pub trait JobStore: Send + Sync + 'static {
+    /// Add a pending job, unless `capacity` jobs are already pending. Returns whether it was
+    /// added. The check and the addition are one step, so concurrent pushes never overshoot.
+    async fn push(&self, job: JobRecord, capacity: Option<usize>) -> io::Result<bool>;
+    /// Move the pending job that has been due the longest to in flight, and return it.
+    async fn claim(&self, now: DateTime<Utc>) -> io::Result<Option<JobRecord>>;
+    /// Forget an in-flight job that succeeded.
+    async fn complete(&self, id: JobId) -> io::Result<()>;
+    /// Move an in-flight job back to pending, replacing it with `job` (its next `run_at`,
+    /// attempt count and error).
+    async fn release(&self, job: JobRecord) -> io::Result<()>;
+    /// Move an in-flight job to the dead letters, replacing it with `job`.
+    async fn bury(&self, job: JobRecord) -> io::Result<()>;
+    /// Move every in-flight job back to pending. Called when a queue starts, for the jobs of a
+    /// process that stopped while running them.
+    async fn recover(&self) -> io::Result<()>;
+    /// When the earliest pending job is due.
+    async fn next_run_at(&self) -> io::Result<Option<DateTime<Utc>>>;
+    /// How many jobs are pending.
+    async fn pending(&self) -> io::Result<usize>;
+    /// The jobs that ran out of retries, oldest first.
+    async fn dead_letters(&self) -> io::Result<Vec<JobRecord>>;
+}
+
+/// The jobs of a store, and the operations on them that every store shares.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Jobs {
+    pending: Vec<JobRecord>,
+    in_flight: Vec<JobRecord>,
+    dead: Vec<JobRecord>,
+}
+
+impl Jobs {
+    fn push(&mut self, job: JobRecord, capacity: Option<usize>) -> bool {
+        if capacity.is_some_and(|capacity| self.pending.len() >= capacity) {
+            return false;
+        }
+        self.pending.push(job);
+        true
+    }
+
+    fn claim(&mut self, now: DateTime<Utc>) -> Option<JobRecord> {
+        // `min_by_key` keeps the first of equals, so jobs due at the same time run in order.
+        let (index, _) = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| job.run_at <= now)
+            .min_by_key(|(_, job)| job.run_at)?;
+        let job = self.pending.remove(index);
+        self.in_flight.push(job.clone());
+        Some(job)
+    }
+
+    fn complete(&mut self, id: JobId) {
+        self.in_flight.retain(|job| job.id != id);
+    }
+
+    fn release(&mut self, job: JobRecord) {
+        self.complete(job.id);
+        self.pending.push(job);
+    }
+
+    fn bury(&mut self, job: JobRecord) {
+        self.complete(job.id);
+        self.dead.push(job);
+    }
+
+    fn recover(&mut self) {
+        self.pending.append(&mut self.in_flight);
+    }
+
+    fn next_run_at(&self) -> Option<DateTime<Utc>> {
+        self.pending.iter().map(|job| job.run_at).min()
+    }
+}
+
+/// Implements [`JobStore`] for a type with an `update` method that applies a closure to its
+/// [`Jobs`].
+macro_rules! job_store {
+    ($store:ty) => {
+        #[async_trait]
+        impl JobStore for $store {
+            async fn push(&self, job: JobRecord, capacity: Option<usize>) -> io::Result<bool> {
+                self.update(move |jobs| jobs.push(job, capacity)).await
+            }
+
+            async fn claim(&self, now: DateTime<Utc>) -> io::Result<Option<JobRecord>> {
+                self.update(move |jobs| jobs.claim(now)).await
+            }
+
+            async fn complete(&self, id: JobId) -> io::Result<()> {
+                self.update(move |jobs| jobs.complete(id)).await
+            }
+
+            async fn release(&self, job: JobRecord) -> io::Result<()> {
+                self.update(move |jobs| jobs.release(job)).await
+            }
+
+            async fn bury(&self, job: JobRecord) -> io::Result<()> {
+                self.update(move |jobs| jobs.bury(job)).await
+            }
+
+            async fn recover(&self) -> io::Result<()> {
+                self.update(Jobs::recover).await
+            }
+
+            async fn next_run_at(&self) -> io::Result<Option<DateTime<Utc>>> {
+                self.update(|jobs| jobs.next_run_at()).await
+            }
+
+            async fn pending(&self) -> io::Result<usize> {
+                self.update(|jobs| jobs.pending.len()).await
+            }
+
+            async fn dead_letters(&self) -> io::Result<Vec<JobRecord>> {
+                self.update(|jobs| jobs.dead.clone()).await
+            }
+        }
+    };
+}
+
+/// A [`JobStore`] in memory: jobs do not survive the process, so it suits tests and work that may
+/// be lost.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    jobs: Arc<Mutex<Jobs>>,
+}
+
+impl MemoryStore {
+    /// An empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn update<R>(&self, apply: impl FnOnce(&mut Jobs) -> R) -> io::Result<R> {
+        Ok(apply(&mut self.jobs.lock().expect("job store poisoned")))
+    }
+}
+
+job_store!(MemoryStore);
+
+/// A [`JobStore`] in a JSON file, rewritten on every change under an exclusive lock on a
+/// `.lock` file beside it (`jobs.json.lock` for `jobs.json`), so several processes can share it.
+///
+/// Every operation reads and writes the whole file; it suits modest queues, such as a single
+/// server's background work, rather than high throughput. A queue that starts recovers every
+/// in-flight job, including any a sibling process is still running, so with a shared file a job
+/// may run twice.
+#[derive(Clone)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    /// Keep the jobs in the file at `path`, creating it and its directory on first use.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    async fn update<R: Send + 'static>(&self, apply: impl FnOnce(&mut Jobs) -> R + Send + 'static) -> io::Result<R> {
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                std::fs::create_dir_all(dir)?;
+            }
+            let lock = OpenOptions::new().write(true).create(true).truncate(false).open(beside(&path, "lock"))?;
+            // Released when `lock` is dropped.
+            lock.lock()?;
+            let before = match std::fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+                Err(error) => return Err(error),
+            };
+            let mut jobs = if before.is_empty() {
+                Jobs::default()
+            } else {
+                serde_json::from_slice(&before)?
+            };
+            let result = apply(&mut jobs);
+            let after = serde_json::to_vec(&jobs)?;
+            if after != before {
+                // Written beside the file and renamed over it, so a crash never leaves half a file.
+                let temporary = beside(&path, "tmp");
+                std::fs::write(&temporary, after)?;
+                std::fs::rename(&temporary, &path)?;
+            }
+            Ok(result)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+job_store!(FileStore);
+
+/// `path` with `.suffix` appended, so that `jobs.json` and `jobs.yaml` beside each other get files
+/// of their own.
+fn beside(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
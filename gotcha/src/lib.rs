@@ -8,6 +8,10 @@
 // skipped everywhere else. It puts a "requires feature X" badge on each gated item.
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+// Lets the crate use its own macros, whose output names `::gotcha::..` (the task admin handlers
+// carry `#[api]`).
+extern crate self as gotcha;
+
 pub use async_trait::async_trait;
 /// WebSocket upgrade and the socket itself. The frame type stays behind `ws::Message`, since
 /// [`Message`] is already the message-system trait.
@@ -38,6 +42,9 @@ pub use {axum, inventory, tracing};
 pub use crate::builder::{EmptyConfig, EmptyState, Gotcha};
 pub use crate::config::GotchaConfigLoader;
 pub use crate::error::{GotchaError, GotchaResult};
+/// Attribute macro that declares an async function as a subscriber to published events. See
+/// [`event`].
+pub use gotcha_macro::subscribe;
 /// Attribute macro that makes a struct usable as `State<T>` in handlers by
 /// generating a `FromRef<GotchaContext<T, C>>` impl. See [`GotchaContext`].
 pub use gotcha_macro::{config, state};
@@ -50,14 +57,16 @@ pub use gotcha_core::Responsible;
 #[cfg(feature = "openapi")]
 #[cfg_attr(docsrs, doc(cfg(feature = "openapi")))]
 pub use crate::openapi::schematic::{ParameterProvider, Schematic};
-#[cfg(feature = "openapi")]
-#[cfg_attr(docsrs, doc(cfg(feature = "openapi")))]
+/// Attribute macro that documents a handler in the OpenAPI spec, with the `openapi` feature, and
+/// bounds the API versions it is served in with `since` and `until`; see [`versioning`].
 pub use gotcha_macro::api;
 #[cfg(feature = "openapi")]
 #[cfg_attr(docsrs, doc(cfg(feature = "openapi")))]
 pub use oas;
 
 pub use crate::message::{Message, Messager};
+pub use crate::multipart::{MultipartForm, TypedMultipart, UploadedFile};
+pub use crate::negotiate::{Format, Negotiated};
 #[cfg(feature = "openapi")]
 #[cfg_attr(docsrs, doc(cfg(feature = "openapi")))]
 pub use crate::openapi::Operable;
@@ -70,14 +79,26 @@ pub use axum_extra::{headers, TypedHeader};
 /// Use with the [`Valid`] extractor.
 pub use validator::Validate;
 
+pub mod actor;
 pub mod builder;
 pub mod config;
 pub mod error;
+pub mod event;
+mod extensions;
+#[cfg(feature = "job")]
+#[cfg_attr(docsrs, doc(cfg(feature = "job")))]
+pub mod job;
+pub mod multipart;
+pub mod negotiate;
 #[cfg(feature = "openapi")]
 #[cfg_attr(docsrs, doc(cfg(feature = "openapi")))]
 pub mod openapi;
+pub mod outcome;
 pub mod params;
 pub mod prelude;
+#[cfg(any(feature = "task", feature = "job"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "task", feature = "job"))))]
+pub mod retry;
 /// The router that tracks OpenAPI operations alongside axum routes.
 pub mod router;
 
@@ -85,6 +106,7 @@ pub mod router;
 #[cfg_attr(docsrs, doc(cfg(feature = "task")))]
 pub mod task;
 pub mod validation;
+pub mod versioning;
 
 #[cfg(feature = "prometheus")]
 #[cfg_attr(docsrs, doc(cfg(feature = "prometheus")))]
@@ -101,12 +123,17 @@ pub mod layers {
 
 #[cfg(feature = "openapi")]
 #[cfg_attr(docsrs, doc(cfg(feature = "openapi")))]
-pub use crate::openapi::schematic::EnhancedSchema;
+pub use crate::openapi::schematic::{schematic_remote, EnhancedSchema, SchemaBuilder};
 
+/// Attribute macro that declares an async function as a scheduled task, started with the
+/// application. See [`task::declare`].
+#[cfg(feature = "task")]
+#[cfg_attr(docsrs, doc(cfg(feature = "task")))]
+pub use gotcha_macro::task;
 pub use serde_json;
 #[cfg(feature = "task")]
 #[cfg_attr(docsrs, doc(cfg(feature = "task")))]
-pub use task::TaskScheduler;
+pub use task::{TaskHandle, TaskScheduler};
 #[cfg(feature = "static_files")]
 #[cfg_attr(docsrs, doc(cfg(feature = "static_files")))]
 pub use tower_http::services::{ServeDir, ServeFile};
@@ -116,11 +143,31 @@ pub use tower_http::services::{ServeDir, ServeFile};
 ///
 /// Handlers rarely name this directly — `#[state]` and `#[config]` make `State<AppState>` and
 /// `State<AppConfig>` extractable instead.
+///
+/// Clones share the application's event bus, [`MessageLayers`](message::MessageLayers) and
+/// [`actor`] addresses, so an event published with one reaches the subscribers of all of them;
+/// contexts made with separate [`new`](Self::new) calls share nothing.
 pub struct GotchaContext<State: Clone + Send + Sync + 'static, Config: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default> {
     /// The loaded configuration.
     pub config: ConfigWrapper<Config>,
     /// The application state.
     pub state: State,
+    pub(crate) extensions: extensions::Extensions,
+}
+
+impl<State, Config> GotchaContext<State, Config>
+where
+    State: Clone + Send + Sync + 'static,
+    Config: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default,
+{
+    /// The context of a new application.
+    pub fn new(config: ConfigWrapper<Config>, state: State) -> Self {
+        Self {
+            config,
+            state,
+            extensions: Default::default(),
+        }
+    }
 }
 
 impl<State, Config> FromRef<GotchaContext<State, Config>> for ConfigWrapper<Config>
@@ -192,17 +239,41 @@ pub trait GotchaApp: Sized + Send + Sync {
     /// Build the application state, given the loaded configuration.
     fn state(&self, config: &ConfigWrapper<Self::Config>) -> impl std::future::Future<Output = GotchaResult<Self::State>> + Send;
 
+    /// Add [`MessageLayer`](message::MessageLayer)s around every message the application's
+    /// [`Messager`]s dispatch, outermost first. The default adds none.
+    fn message_layers(&self, layers: message::MessageLayers) -> message::MessageLayers {
+        layers
+    }
+
+    /// Start the application's [`actor`]s, whose addresses handlers then extract. The default
+    /// starts none.
+    fn actors(&self, _actors: &mut actor::Actors<Self::State, Self::Config>) {}
+
     #[cfg(feature = "task")]
-    /// Register background tasks. The default registers none.
+    /// Register background tasks. The default registers none; tasks declared with `#[task]` are
+    /// registered after these.
     fn tasks(&self, _task_scheduler: &mut TaskScheduler<Self::State, Self::Config>) -> impl std::future::Future<Output = GotchaResult<()>> + Send {
         async { Ok(()) }
     }
 
+    /// Assemble the application's OpenAPI document without starting it.
+    ///
+    /// This is what `/openapi.json` serves: the [`routes`](Self::routes) with the `[server.docs]`
+    /// and `[server.openapi]` sections of the [`config`](Self::config) applied. It needs no state.
+    /// See [`GotchaRouter::openapi_spec`].
+    #[cfg(feature = "openapi")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "openapi")))]
+    fn openapi_spec(&self) -> impl std::future::Future<Output = GotchaResult<serde_json::Value>> + Send {
+        async move {
+            let config = self.config().await?;
+            Ok(configured_routes(self, &config).openapi_spec())
+        }
+    }
+
     /// Assemble the final axum router. Override only to wrap the whole application.
     fn build_router(&self, context: GotchaContext<Self::State, Self::Config>) -> impl std::future::Future<Output = GotchaResult<axum::Router>> + Send {
         async move {
-            let router = GotchaRouter::<GotchaContext<Self::State, Self::Config>>::default();
-            let router = self.routes(router);
+            let router = configured_routes(self, &context.config);
             Ok(router.into_axum_router(context))
         }
     }
@@ -217,7 +288,8 @@ pub trait GotchaApp: Sized + Send + Sync {
             let config: ConfigWrapper<Self::Config> = self.config().await?;
             let state = self.state(&config).await?;
 
-            let context = GotchaContext { config: config.clone(), state };
+            let context = GotchaContext::new(config.clone(), state).with_message_layers(self.message_layers(message::MessageLayers::new()));
+            self.actors(&mut actor::Actors::new(context.clone()));
 
             let router = self.build_router(context.clone()).await?;
 
@@ -225,6 +297,8 @@ pub trait GotchaApp: Sized + Send + Sync {
                 if #[cfg(feature = "task")] {
                     let mut task_scheduler = TaskScheduler::new(context.clone());
                     self.tasks(&mut task_scheduler).await?;
+                    task_scheduler.register_declared()?;
+                    let router = router.layer(axum::Extension(task_scheduler.registry()));
                 }
             }
 
@@ -234,12 +308,24 @@ pub trait GotchaApp: Sized + Send + Sync {
                 addr: addr.to_string(),
                 source,
             })?;
-            axum::serve(listener, router).await.map_err(GotchaError::Io)?;
-            Ok(())
+            let served = axum::serve(listener, router).await.map_err(GotchaError::Io);
+            #[cfg(feature = "task")]
+            task_scheduler.shutdown().await;
+            served
         }
     }
 }
 
+/// The application's routes, with the configuration's docs and spec settings applied: the one
+/// path both serving and [`GotchaApp::openapi_spec`] take.
+#[allow(unused_variables)]
+fn configured_routes<A: GotchaApp>(app: &A, config: &ConfigWrapper<A::Config>) -> GotchaRouter<GotchaContext<A::State, A::Config>> {
+    let router = app.routes(GotchaRouter::default());
+    #[cfg(feature = "openapi")]
+    let router = router.configured(&config.server);
+    router
+}
+
 #[cfg(test)]
 mod test {
     #[test]
@@ -0,0 +1,275 @@
+//! Cross-cutting behaviour around message dispatch: [`MessageLayer`]s wrap every
+//! [`Messager::send`](super::Messager::send) and [`Messager::spawn`](super::Messager::spawn), as
+//! tower layers wrap requests.
+//!
+//! Layers are registered on the application, with `GotchaApp::message_layers` or the builder's
+//! `message_layer`, which keep them on its [`GotchaContext`](crate::GotchaContext); they apply to
+//! every `Messager` of that context. Two come built in:
+//! [`TracingLayer`] runs each message in a span named after its type, and [`MetricsLayer`] counts
+//! messages, failures and panics, and times them.
+//!
+//! ```rust,ignore
+//! use gotcha::message::{MessageLayers, MetricsLayer, TracingLayer};
+//!
+//! fn message_layers(&self, layers: MessageLayers) -> MessageLayers {
+//!     layers.layer(TracingLayer).layer(self.metrics.clone())
+//! }
+//! ```
+//!
+//! A layer of its own implements [`MessageLayer`]: it receives a [`Dispatch`] describing the
+//! message and the [`Next`] layer, which it runs at most once. Scoping a task-local around
+//! `next.run()` propagates context, such as the caller's identity, to the handler and the messages
+//! it dispatches in turn. A layer that returns without running `next` rejects the message:
+//! [`try_send`](super::Messager::try_send) returns [`Rejected`] with the error the layer returned,
+//! instead of the message's output, and `send` panics with it.
+//!
+//! ```rust,ignore
+//! #[async_trait]
+//! impl MessageLayer for RequireUser {
+//!     async fn handle(&self, _dispatch: Dispatch, next: Next<'_>) -> Result<(), String> {
+//!         match CURRENT_USER.try_with(Clone::clone) {
+//!             Ok(user) => CURRENT_USER.scope(user, next.run()).await,
+//!             Err(_) => Err("no signed-in user".to_string()),
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::any::type_name;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use futures_util::FutureExt;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::Instrument;
+
+/// How a message was dispatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DispatchMode {
+    /// With [`Messager::send`](super::Messager::send) or
+    /// [`try_send`](super::Messager::try_send), awaited by the caller.
+    Send,
+    /// With [`Messager::spawn`](super::Messager::spawn), in a task of its own.
+    Spawn,
+}
+
+/// The message a [`MessageLayer`] is wrapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dispatch {
+    /// The message's type name without its path, such as `SendEmail`.
+    pub message: &'static str,
+    /// The message's full type name.
+    pub type_name: &'static str,
+    /// How it was dispatched.
+    pub mode: DispatchMode,
+}
+
+impl Dispatch {
+    pub(crate) fn of<M>(mode: DispatchMode) -> Self {
+        let type_name = type_name::<M>();
+        let path = &type_name[..type_name.find('<').unwrap_or(type_name.len())];
+        Self {
+            message: path.rsplit("::").next().unwrap_or(path),
+            type_name,
+            mode,
+        }
+    }
+}
+
+/// Handling a message: `Err` carries its failure, as told by
+/// [`Message::failure`](super::Message::failure).
+pub type HandleFuture<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+/// Wraps the handling of every message. See the [module documentation](self).
+#[async_trait]
+pub trait MessageLayer: Send + Sync + 'static {
+    /// Handle the message described by `dispatch` by running `next`, doing whatever this layer
+    /// does around it. Returning without running `next` rejects the message.
+    async fn handle(&self, dispatch: Dispatch, next: Next<'_>) -> Result<(), String>;
+}
+
+#[async_trait]
+impl<L: MessageLayer + ?Sized> MessageLayer for Arc<L> {
+    async fn handle(&self, dispatch: Dispatch, next: Next<'_>) -> Result<(), String> {
+        (**self).handle(dispatch, next).await
+    }
+}
+
+/// The rest of the layers, and the handler inside them.
+pub struct Next<'a> {
+    layers: &'a [Arc<dyn MessageLayer>],
+    dispatch: Dispatch,
+    handler: HandleFuture<'a>,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(layers: &'a MessageLayers, dispatch: Dispatch, handler: HandleFuture<'a>) -> Self {
+        Self {
+            layers: &layers.layers,
+            dispatch,
+            handler,
+        }
+    }
+
+    /// Run the next layer, or the handler once every layer has run.
+    pub async fn run(self) -> Result<(), String> {
+        match self.layers.split_first() {
+            Some((layer, layers)) => {
+                layer
+                    .handle(
+                        self.dispatch,
+                        Next {
+                            layers,
+                            dispatch: self.dispatch,
+                            handler: self.handler,
+                        },
+                    )
+                    .await
+            }
+            None => self.handler.await,
+        }
+    }
+}
+
+/// The layers of an application's [`Messager`](super::Messager)s, outermost first.
+#[derive(Clone, Default)]
+pub struct MessageLayers {
+    layers: Arc<[Arc<dyn MessageLayer>]>,
+}
+
+impl MessageLayers {
+    /// No layers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `layer` inside the layers added so far.
+    pub fn layer(self, layer: impl MessageLayer) -> Self {
+        let mut layers = self.layers.to_vec();
+        layers.push(Arc::new(layer));
+        Self { layers: layers.into() }
+    }
+
+    /// Whether there are no layers.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+}
+
+/// A [`MessageLayer`] returned without running the message.
+///
+/// As a response it is `403 Forbidden`, since the layers that reject messages are usually
+/// checking who sent them.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("the message {message} was rejected: {reason}")]
+pub struct Rejected {
+    /// The message's type name without its path.
+    pub message: &'static str,
+    /// The error the rejecting layer returned.
+    pub reason: String,
+}
+
+impl IntoResponse for Rejected {
+    fn into_response(self) -> Response {
+        (StatusCode::FORBIDDEN, self.to_string()).into_response()
+    }
+}
+
+/// Runs each message in an `INFO` span named `message`, whose `otel.name` field is the message's
+/// type, so OpenTelemetry exporters name the span after it. A failure is recorded on the span's
+/// `error` field.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingLayer;
+
+#[async_trait]
+impl MessageLayer for TracingLayer {
+    async fn handle(&self, dispatch: Dispatch, next: Next<'_>) -> Result<(), String> {
+        let span = tracing::info_span!("message", otel.name = dispatch.message, message.type = dispatch.type_name, message.mode = ?dispatch.mode, error = tracing::field::Empty);
+        let result = next.run().instrument(span.clone()).await;
+        if let Err(error) = &result {
+            span.record("error", error.as_str());
+        }
+        result
+    }
+}
+
+/// What a [`MetricsLayer`] has measured of one message type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MessageStats {
+    /// How many were handled.
+    pub count: u64,
+    /// How many of those failed or panicked.
+    pub failures: u64,
+    /// How many of those panicked.
+    pub panics: u64,
+    /// Their total handling time.
+    pub total_duration: Duration,
+    /// The longest handling time.
+    pub max_duration: Duration,
+}
+
+/// Counts and times messages by type, with their failures and panics. [`snapshot`](Self::snapshot)
+/// reads the figures; with the `prometheus` feature they are also recorded as the
+/// `gotcha_messages_total` counter (labelled by `message` and `outcome`) and the
+/// `gotcha_message_duration_seconds` histogram.
+///
+/// A panicking message is counted, then the panic carries on, so `send` still panics.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsLayer {
+    stats: Arc<Mutex<BTreeMap<&'static str, MessageStats>>>,
+}
+
+impl MetricsLayer {
+    /// A layer with nothing measured yet. Clones share their figures.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The figures so far, by message type name (without its path).
+    pub fn snapshot(&self) -> BTreeMap<&'static str, MessageStats> {
+        self.stats.lock().expect("message metrics poisoned").clone()
+    }
+
+    fn record(&self, dispatch: Dispatch, outcome: &'static str, elapsed: Duration) {
+        {
+            let mut stats = self.stats.lock().expect("message metrics poisoned");
+            let stats = stats.entry(dispatch.message).or_default();
+            stats.count += 1;
+            stats.failures += u64::from(outcome != "ok");
+            stats.panics += u64::from(outcome == "panicked");
+            stats.total_duration += elapsed;
+            stats.max_duration = stats.max_duration.max(elapsed);
+        }
+        #[cfg(feature = "prometheus")]
+        {
+            crate::prometheus::counter!("gotcha_messages_total", "message" => dispatch.message, "outcome" => outcome).increment(1);
+            crate::prometheus::histogram!("gotcha_message_duration_seconds", "message" => dispatch.message).record(elapsed.as_secs_f64());
+        }
+    }
+}
+
+#[async_trait]
+impl MessageLayer for MetricsLayer {
+    async fn handle(&self, dispatch: Dispatch, next: Next<'_>) -> Result<(), String> {
+        let started = Instant::now();
+        match AssertUnwindSafe(next.run()).catch_unwind().await {
+            Ok(result) => {
+                self.record(dispatch, if result.is_ok() { "ok" } else { "failed" }, started.elapsed());
+                result
+            }
+            Err(panic) => {
+                self.record(dispatch, "panicked", started.elapsed());
+                std::panic::resume_unwind(panic)
+            }
+        }
+    }
+}
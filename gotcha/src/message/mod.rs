@@ -0,0 +1,280 @@
+//! # Message Module
+//!
+//! A small message-dispatch system for background/async work with access to the
+//! application state.
+//!
+//! Implement [`Message`] for a unit of work, then dispatch it with a [`Messager`]:
+//! `send` awaits the result, `spawn` runs it fire-and-forget. Where a [`MessageLayer`] may
+//! reject a message, `try_send` returns the rejection instead. The handler receives
+//! the `Messager`, so it can read the application state/config
+//! ([`Messager::state`] / [`Messager::context`]) and dispatch further messages.
+//!
+//! Events, which reach any number of subscribers rather than one handler, are published with
+//! [`Messager::publish`]; see [`crate::event`]. [`MessageLayer`]s add behaviour around every
+//! dispatch, such as tracing and metrics; see [`layer`].
+//!
+//! The `Messager` is extractable in handlers as `State<Messager<S, C>>`, because it
+//! implements `FromRef<GotchaContext<S, C>>` (the context the framework injects as
+//! the axum state).
+//!
+//! ```ignore
+//! use gotcha::prelude::*;
+//! use gotcha::{async_trait, Message, Messager};
+//!
+//! struct Greet { name: String }
+//!
+//! #[async_trait]
+//! impl Message<AppState, AppConfig> for Greet {
+//!     type Output = String;
+//!     async fn handle(self, messager: Messager<AppState, AppConfig>) -> String {
+//!         format!("{}, {}!", messager.state().greeting, self.name)
+//!     }
+//! }
+//!
+//! async fn hello(State(messager): State<Messager<AppState, AppConfig>>) -> String {
+//!     messager.send(Greet { name: "world".into() }).await
+//! }
+//! ```
+
+use async_trait::async_trait;
+use axum::extract::FromRef;
+
+use crate::{GotchaConfig, GotchaContext};
+
+pub mod layer;
+
+pub use layer::{Dispatch, DispatchMode, MessageLayer, MessageLayers, MessageStats, MetricsLayer, Next, Rejected, TracingLayer};
+
+/// A unit of asynchronous work, dispatched by a [`Messager`].
+///
+/// The `handle` method receives the `Messager`, so a message can read the
+/// application state and dispatch further messages.
+#[async_trait]
+pub trait Message<S, C>: Send + 'static
+where
+    S: Clone + Send + Sync + 'static,
+    C: GotchaConfig,
+{
+    /// The value produced by handling this message.
+    type Output: Send + 'static;
+
+    /// Handle the message, producing its output.
+    async fn handle(self, messager: Messager<S, C>) -> Self::Output;
+
+    /// Whether an output is a failure, and why, for [`MessageLayer`]s such as
+    /// [`MetricsLayer`]. Every output succeeds by default; a message whose output is a `Result`
+    /// overrides this:
+    ///
+    /// ```ignore
+    /// fn failure(output: &Self::Output) -> Option<String> {
+    ///     output.as_ref().err().map(ToString::to_string)
+    /// }
+    /// ```
+    fn failure(_output: &Self::Output) -> Option<String> {
+        None
+    }
+}
+
+/// Dispatches [`Message`]s. It carries the application [`GotchaContext`], so
+/// messages can access the application state and configuration.
+///
+/// Extract it in a handler with `State<Messager<S, C>>`.
+pub struct Messager<S, C>
+where
+    S: Clone + Send + Sync + 'static,
+    C: GotchaConfig,
+{
+    context: GotchaContext<S, C>,
+    /// Layers of its own, instead of the context's.
+    layers: Option<MessageLayers>,
+}
+
+impl<S, C> Clone for Messager<S, C>
+where
+    S: Clone + Send + Sync + 'static,
+    C: GotchaConfig,
+{
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            layers: self.layers.clone(),
+        }
+    }
+}
+
+impl<S, C> Messager<S, C>
+where
+    S: Clone + Send + Sync + 'static,
+    C: GotchaConfig,
+{
+    /// Create a `Messager` bound to an application context, with the context's
+    /// [`MessageLayers`].
+    pub fn new(context: GotchaContext<S, C>) -> Self {
+        Self { context, layers: None }
+    }
+
+    /// Create a `Messager` bound to an application context, with its own layers.
+    pub fn with_layers(context: GotchaContext<S, C>, layers: MessageLayers) -> Self {
+        Self {
+            context,
+            layers: Some(layers),
+        }
+    }
+
+    /// The application context (state + config).
+    pub fn context(&self) -> &GotchaContext<S, C> {
+        &self.context
+    }
+
+    /// The application state.
+    pub fn state(&self) -> &S {
+        &self.context.state
+    }
+
+    /// Dispatch a message and await its output.
+    ///
+    /// # Panics
+    ///
+    /// If one of the layers returned without running the message; use [`try_send`](Self::try_send)
+    /// where a layer may reject it. Without layers, it never panics.
+    pub async fn send<M: Message<S, C>>(&self, message: M) -> M::Output {
+        match self.try_send(message).await {
+            Ok(output) => output,
+            Err(rejected) => panic!("{rejected}; use `Messager::try_send` to handle rejections"),
+        }
+    }
+
+    /// Dispatch a message and await its output, or [`Rejected`] if one of the layers returned
+    /// without running it.
+    pub async fn try_send<M: Message<S, C>>(&self, message: M) -> Result<M::Output, Rejected> {
+        self.dispatch(message, DispatchMode::Send).await
+    }
+
+    /// Dispatch a message as a detached background task (fire-and-forget).
+    ///
+    /// The message is lost if the process stops first, and nothing retries it when it fails; the
+    /// `job` feature's `JobQueue` stores messages and retries them.
+    pub fn spawn<M: Message<S, C, Output = ()>>(&self, message: M) {
+        let messager = self.clone();
+        tokio::spawn(async move {
+            if let Err(rejected) = messager.dispatch(message, DispatchMode::Spawn).await {
+                tracing::warn!("{rejected}");
+            }
+        });
+    }
+
+    /// Handle a message inside the layers.
+    async fn dispatch<M: Message<S, C>>(&self, message: M, mode: DispatchMode) -> Result<M::Output, Rejected> {
+        let layers = match &self.layers {
+            Some(layers) => layers.clone(),
+            None => self.context.message_layers(),
+        };
+        if layers.is_empty() {
+            return Ok(message.handle(self.clone()).await);
+        }
+        let mut output = None;
+        let handler = Box::pin(async {
+            let handled = message.handle(self.clone()).await;
+            let failure = M::failure(&handled);
+            output = Some(handled);
+            failure.map_or(Ok(()), Err)
+        });
+        let dispatch = Dispatch::of::<M>(mode);
+        let result = Next::new(&layers, dispatch, handler).run().await;
+        // A handled message's outcome was already reported to the layers; the caller gets the
+        // output itself.
+        output.ok_or_else(|| Rejected {
+            message: dispatch.message,
+            reason: result.err().unwrap_or_else(|| "a message layer did not run it".to_string()),
+        })
+    }
+
+    /// Publish an event to every `#[subscribe]`r of its type and every open
+    /// [`EventStream`](crate::event::EventStream) of this application for it, without waiting for
+    /// them. Returns how many subscribers it was delivered to; a subscriber too far behind misses
+    /// it. See [`event`](crate::event).
+    pub fn publish<E: Clone + Send + Sync + 'static>(&self, event: E) -> usize {
+        crate::event::publish(&self.context, event)
+    }
+}
+
+/// What a spawned task panicked with, when it is a string.
+pub(crate) fn panic_message(join_error: tokio::task::JoinError) -> String {
+    if !join_error.is_panic() {
+        return join_error.to_string();
+    }
+    payload_message(&*join_error.into_panic())
+}
+
+/// What a panic's payload says, when it is a string.
+pub(crate) fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panicked".to_string())
+}
+
+impl<S, C> GotchaContext<S, C>
+where
+    S: Clone + Send + Sync + 'static,
+    C: GotchaConfig,
+{
+    /// Make `layers` the [`MessageLayers`] of every [`Messager`] of this context and its clones.
+    /// `GotchaApp::run` and the builder set the application's layers this way.
+    pub fn with_message_layers(self, layers: MessageLayers) -> Self {
+        self.extensions.insert(layers);
+        self
+    }
+
+    /// The [`MessageLayers`] of this context's [`Messager`]s.
+    pub fn message_layers(&self) -> MessageLayers {
+        self.extensions.get().unwrap_or_default()
+    }
+}
+
+impl<S, C> FromRef<GotchaContext<S, C>> for Messager<S, C>
+where
+    S: Clone + Send + Sync + 'static,
+    C: GotchaConfig,
+{
+    fn from_ref(context: &GotchaContext<S, C>) -> Self {
+        Messager::new(context.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConfigWrapper, EmptyConfig};
+
+    #[derive(Clone, Default)]
+    struct AppState {
+        greeting: String,
+    }
+
+    struct Greet {
+        name: String,
+    }
+
+    #[async_trait]
+    impl Message<AppState, EmptyConfig> for Greet {
+        type Output = String;
+        async fn handle(self, messager: Messager<AppState, EmptyConfig>) -> String {
+            format!("{}, {}!", messager.state().greeting, self.name)
+        }
+    }
+
+    #[test]
+    fn send_dispatches_and_reads_state() {
+        let context = GotchaContext::new(ConfigWrapper::<EmptyConfig>::default(), AppState { greeting: "Hello".to_string() });
+        let messager = Messager::new(context);
+
+        let output = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(messager.send(Greet { name: "world".to_string() }));
+
+        assert_eq!(output, "Hello, world!");
+    }
+}
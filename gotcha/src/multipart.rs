@@ -0,0 +1,587 @@
+//! Typed `multipart/form-data` bodies.
+//!
+//! [`TypedMultipart<T>`] reads a multipart body into a struct that derives [`MultipartForm`]. Each
+//! field is one part, matched by name — the struct's serde names, so they agree with the schema
+//! `#[derive(Schematic)]` documents. Text parts are parsed with [`FromStr`](std::str::FromStr);
+//! [`UploadedFile`] parts are streamed into memory, or into a temporary file with
+//! `#[multipart(temp_file)]`. `Option<T>` makes a part optional and `Vec<T>` collects a repeated
+//! one. Parts the struct does not name are skipped.
+//!
+//! ```ignore
+//! use gotcha::multipart::{MultipartForm, TypedMultipart, UploadedFile};
+//! use gotcha::Schematic;
+//!
+//! #[derive(MultipartForm, Schematic)]
+//! struct Upload {
+//!     title: String,
+//!     #[multipart(limit = "5MiB", content_type = "image/png", content_type = "image/jpeg")]
+//!     avatar: UploadedFile,
+//!     #[multipart(temp_file, limit = "1GiB")]
+//!     attachments: Vec<UploadedFile>,
+//! }
+//!
+//! async fn upload(TypedMultipart(form): TypedMultipart<Upload>) -> String {
+//!     format!("{}: {} bytes", form.title, form.avatar.size())
+//! }
+//! ```
+//!
+//! `limit` accepts a byte count or a size with a unit (`KB`, `KiB`, `MB`, `MiB`, `GB`, `GiB`), and
+//! `content_type` may end in `/*`. axum caps every request body at 2 MB unless the route sets
+//! `axum::extract::DefaultBodyLimit`, so larger uploads need that as well as a field `limit`.
+//!
+//! With the `openapi` feature the request body is documented as `multipart/form-data` with the
+//! struct's schema (`UploadedFile` is `type: string, format: binary`) and an `encoding` entry
+//! giving each file part its accepted content types.
+
+use std::hash::{BuildHasher, RandomState};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use axum::body::Bytes;
+use axum::extract::multipart::{Field, MultipartError};
+use axum::extract::{FromRequest, Multipart, Request};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+/// Derives [`MultipartForm`] for a struct with named fields; see the [module docs](self).
+pub use gotcha_macro::MultipartForm;
+use tokio::io::AsyncWriteExt;
+
+/// Extractor reading a `multipart/form-data` body into `T`.
+#[derive(Debug, Clone, Default)]
+pub struct TypedMultipart<T>(pub T);
+
+impl<T> std::ops::Deref for TypedMultipart<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for TypedMultipart<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// A struct built from the parts of a multipart body. Derive it with `#[derive(MultipartForm)]`.
+pub trait MultipartForm: Sized {
+    /// The parts the struct reads, in field order.
+    fn parts() -> Vec<PartSpec>;
+    /// Build the struct from the values received for each part, indexed like [`parts`](Self::parts).
+    fn from_parts(values: Vec<Vec<PartValue>>) -> Result<Self, MultipartRejection>;
+}
+
+/// How one part is read.
+#[derive(Debug, Clone)]
+pub struct PartSpec {
+    /// The part's name in the body.
+    pub name: &'static str,
+    /// Whether the part is a file, kept as bytes, rather than text.
+    pub file: bool,
+    /// The largest size, in bytes, the part may have.
+    pub limit: Option<u64>,
+    /// The content types the part may have; empty accepts any. An entry may end in `/*`.
+    pub content_types: &'static [&'static str],
+    /// Stream a file part to a temporary file instead of memory.
+    pub temp_file: bool,
+}
+
+impl PartSpec {
+    fn accepts(&self, content_type: Option<&str>) -> bool {
+        if self.content_types.is_empty() {
+            return true;
+        }
+        let Some(content_type) = content_type else {
+            return false;
+        };
+        // Compare the essence only: `text/plain; charset=utf-8` is `text/plain`.
+        let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        self.content_types.iter().any(|allowed| {
+            let allowed = allowed.to_ascii_lowercase();
+            match allowed.strip_suffix("/*") {
+                Some(top) => essence.split_once('/').is_some_and(|(each, _)| each == top),
+                None => essence == allowed,
+            }
+        })
+    }
+}
+
+/// One received part, before it is converted to the field's type.
+#[derive(Debug)]
+pub enum PartValue {
+    /// A text part.
+    Text(String),
+    /// A file part.
+    File(UploadedFile),
+}
+
+/// A type one part converts to.
+pub trait MultipartField: Sized {
+    /// Whether the part is read as a file rather than as text.
+    const FILE: bool;
+    /// Convert the part named `name`.
+    fn from_part(value: PartValue, name: &str) -> Result<Self, MultipartRejection>;
+}
+
+/// A field's type: a [`MultipartField`] (required), `Option` of one, or `Vec` of one (repeated).
+pub trait MultipartValue: Sized {
+    /// Whether the parts are read as files rather than as text.
+    const FILE: bool;
+    /// Build the field from every part received under `name`, in order.
+    fn collect(values: Vec<PartValue>, name: &str) -> Result<Self, MultipartRejection>;
+}
+
+impl<T: MultipartField> MultipartValue for Option<T> {
+    const FILE: bool = T::FILE;
+    fn collect(values: Vec<PartValue>, name: &str) -> Result<Self, MultipartRejection> {
+        values.into_iter().last().map(|value| T::from_part(value, name)).transpose()
+    }
+}
+
+impl<T: MultipartField> MultipartValue for Vec<T> {
+    const FILE: bool = T::FILE;
+    fn collect(values: Vec<PartValue>, name: &str) -> Result<Self, MultipartRejection> {
+        values.into_iter().map(|value| T::from_part(value, name)).collect()
+    }
+}
+
+/// A required single part; when it is repeated, the last one wins.
+fn required<T: MultipartField>(values: Vec<PartValue>, name: &str) -> Result<T, MultipartRejection> {
+    let value = values.into_iter().last().ok_or_else(|| MultipartRejection::Missing(name.to_string()))?;
+    T::from_part(value, name)
+}
+
+macro_rules! impl_text_field {
+    ($($t:ty),* $(,)?) => {$(
+        impl MultipartField for $t {
+            const FILE: bool = false;
+            fn from_part(value: PartValue, name: &str) -> Result<Self, MultipartRejection> {
+                match value {
+                    PartValue::Text(text) => text.parse().map_err(|error| MultipartRejection::Invalid {
+                        field: name.to_string(),
+                        message: format!("{error}"),
+                    }),
+                    PartValue::File(_) => Err(MultipartRejection::Invalid {
+                        field: name.to_string(),
+                        message: "expected a text part".to_string(),
+                    }),
+                }
+            }
+        }
+
+        impl MultipartValue for $t {
+            const FILE: bool = false;
+            fn collect(values: Vec<PartValue>, name: &str) -> Result<Self, MultipartRejection> {
+                required(values, name)
+            }
+        }
+    )*};
+}
+
+impl_text_field!(
+    String,
+    bool,
+    char,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    f32,
+    f64,
+    uuid::Uuid
+);
+
+impl MultipartField for UploadedFile {
+    const FILE: bool = true;
+    fn from_part(value: PartValue, name: &str) -> Result<Self, MultipartRejection> {
+        match value {
+            PartValue::File(file) => Ok(file),
+            PartValue::Text(_) => Err(MultipartRejection::Invalid {
+                field: name.to_string(),
+                message: "expected a file part".to_string(),
+            }),
+        }
+    }
+}
+
+impl MultipartValue for UploadedFile {
+    const FILE: bool = true;
+    fn collect(values: Vec<PartValue>, name: &str) -> Result<Self, MultipartRejection> {
+        required(values, name)
+    }
+}
+
+/// A file received in a multipart body, held in memory or in a temporary file.
+///
+/// A temporary file is deleted when the `UploadedFile` is dropped, unless it was
+/// [`persist`](Self::persist)ed first.
+#[derive(Debug)]
+pub struct UploadedFile {
+    file_name: Option<String>,
+    content_type: Option<String>,
+    size: u64,
+    contents: Contents,
+}
+
+#[derive(Debug)]
+enum Contents {
+    Memory(Bytes),
+    TempFile(PathBuf),
+    Persisted,
+}
+
+impl UploadedFile {
+    /// The file name the client sent, if any. It is untrusted input: do not use it as a path.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The part's content type, if the client sent one.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// The size in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// The contents, when the file is held in memory.
+    pub fn bytes(&self) -> Option<&Bytes> {
+        match &self.contents {
+            Contents::Memory(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// The temporary file holding the contents, when the field is `#[multipart(temp_file)]`.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.contents {
+            Contents::TempFile(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Read the whole contents, wherever they are held.
+    pub async fn read(&self) -> std::io::Result<Bytes> {
+        match &self.contents {
+            Contents::Memory(bytes) => Ok(bytes.clone()),
+            Contents::TempFile(path) => Ok(tokio::fs::read(path).await?.into()),
+            Contents::Persisted => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "the upload was already persisted")),
+        }
+    }
+
+    /// Store the contents at `path`. A temporary file is moved there rather than copied when it
+    /// can be, and is no longer deleted on drop.
+    pub async fn persist(&mut self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        match std::mem::replace(&mut self.contents, Contents::Persisted) {
+            Contents::Memory(bytes) => {
+                let written = tokio::fs::write(path, &bytes).await;
+                self.contents = Contents::Memory(bytes);
+                written
+            }
+            Contents::TempFile(temp) => {
+                if tokio::fs::rename(&temp, path).await.is_ok() {
+                    return Ok(());
+                }
+                // Renaming fails across file systems; fall back to a copy.
+                let copied = tokio::fs::copy(&temp, path).await;
+                let _ = tokio::fs::remove_file(&temp).await;
+                copied.map(|_| ())
+            }
+            Contents::Persisted => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "the upload was already persisted")),
+        }
+    }
+}
+
+impl Drop for UploadedFile {
+    fn drop(&mut self) {
+        if let Contents::TempFile(path) = &self.contents {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Create a fresh file in the system temporary directory, readable only by its owner.
+///
+/// The name is unpredictable and the file must not exist yet, so another user of a shared
+/// temporary directory can neither plant a symlink at it nor have an existing file truncated.
+async fn create_temp_file() -> std::io::Result<(PathBuf, tokio::fs::File)> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    loop {
+        // `RandomState` is seeded from the operating system's random source.
+        let random = RandomState::new().hash_one(COUNTER.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(format!("gotcha-upload-{random:016x}"));
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        match options.open(&path).await {
+            Ok(file) => return Ok((path, file)),
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Rejection produced by the [`TypedMultipart`] extractor.
+#[derive(Debug, thiserror::Error)]
+pub enum MultipartRejection {
+    /// The request is not a multipart body; delegates to axum's rejection response.
+    #[error(transparent)]
+    Multipart(#[from] axum::extract::multipart::MultipartRejection),
+    /// The body could not be read as multipart; delegates to axum's error response.
+    #[error(transparent)]
+    Read(#[from] MultipartError),
+    /// A required part is absent. `422 Unprocessable Entity`.
+    #[error("missing part `{0}`")]
+    Missing(String),
+    /// A part exceeds its `limit`. `413 Payload Too Large`.
+    #[error("part `{field}` is larger than {limit} bytes")]
+    TooLarge {
+        /// The part's name.
+        field: String,
+        /// The limit it exceeded, in bytes.
+        limit: u64,
+    },
+    /// A part's content type is not one of its `content_type`s. `415 Unsupported Media Type`.
+    #[error("part `{field}` has unsupported content type {}", content_type.as_deref().unwrap_or("(none)"))]
+    UnsupportedContentType {
+        /// The part's name.
+        field: String,
+        /// The content type the client sent, if any.
+        content_type: Option<String>,
+    },
+    /// A part could not be converted to its field's type. `422 Unprocessable Entity`.
+    #[error("part `{field}` is invalid: {message}")]
+    Invalid {
+        /// The part's name.
+        field: String,
+        /// What was wrong with it.
+        message: String,
+    },
+    /// Writing a temporary file failed. `500 Internal Server Error`.
+    #[error("failed to store an upload: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl IntoResponse for MultipartRejection {
+    fn into_response(self) -> Response {
+        let (status, field) = match &self {
+            MultipartRejection::Multipart(rejection) => return (rejection.status(), rejection.body_text()).into_response(),
+            MultipartRejection::Read(error) => return (error.status(), error.body_text()).into_response(),
+            MultipartRejection::Missing(field) | MultipartRejection::Invalid { field, .. } => (StatusCode::UNPROCESSABLE_ENTITY, field.clone()),
+            MultipartRejection::TooLarge { field, .. } => (StatusCode::PAYLOAD_TOO_LARGE, field.clone()),
+            MultipartRejection::UnsupportedContentType { field, .. } => (StatusCode::UNSUPPORTED_MEDIA_TYPE, field.clone()),
+            MultipartRejection::Io(error) => {
+                tracing::error!("failed to store an upload: {error}");
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+        (status, Json(serde_json::json!({ "field": field, "message": self.to_string() }))).into_response()
+    }
+}
+
+/// Read a part into memory, enforcing the spec's limit.
+async fn read_bytes(field: &mut Field<'_>, spec: &PartSpec) -> Result<Bytes, MultipartRejection> {
+    let mut buffer = Vec::new();
+    while let Some(chunk) = field.chunk().await? {
+        check_limit(spec, buffer.len() as u64 + chunk.len() as u64)?;
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(buffer.into())
+}
+
+fn check_limit(spec: &PartSpec, size: u64) -> Result<(), MultipartRejection> {
+    match spec.limit {
+        Some(limit) if size > limit => Err(MultipartRejection::TooLarge {
+            field: spec.name.to_string(),
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Stream a file part to a temporary file, enforcing the spec's limit.
+async fn write_temp_file(field: &mut Field<'_>, spec: &PartSpec) -> Result<(PathBuf, u64), MultipartRejection> {
+    let (path, mut file) = create_temp_file().await?;
+    let mut size = 0u64;
+    let written: Result<(), MultipartRejection> = async {
+        while let Some(chunk) = field.chunk().await? {
+            size += chunk.len() as u64;
+            check_limit(spec, size)?;
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        Ok(())
+    }
+    .await;
+    if let Err(error) = written {
+        drop(file);
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(error);
+    }
+    Ok((path, size))
+}
+
+async fn read_part(mut field: Field<'_>, spec: &PartSpec) -> Result<PartValue, MultipartRejection> {
+    let content_type = field.content_type().map(str::to_string);
+    if !spec.accepts(content_type.as_deref()) {
+        return Err(MultipartRejection::UnsupportedContentType {
+            field: spec.name.to_string(),
+            content_type,
+        });
+    }
+    if !spec.file {
+        let bytes = read_bytes(&mut field, spec).await?;
+        return String::from_utf8(bytes.to_vec()).map(PartValue::Text).map_err(|_| MultipartRejection::Invalid {
+            field: spec.name.to_string(),
+            message: "not valid UTF-8".to_string(),
+        });
+    }
+    let file_name = field.file_name().map(str::to_string);
+    let (contents, size) = if spec.temp_file {
+        let (path, size) = write_temp_file(&mut field, spec).await?;
+        (Contents::TempFile(path), size)
+    } else {
+        let bytes = read_bytes(&mut field, spec).await?;
+        let size = bytes.len() as u64;
+        (Contents::Memory(bytes), size)
+    };
+    Ok(PartValue::File(UploadedFile {
+        file_name,
+        content_type,
+        size,
+        contents,
+    }))
+}
+
+impl<S, T> FromRequest<S> for TypedMultipart<T>
+where
+    T: MultipartForm,
+    S: Send + Sync,
+{
+    type Rejection = MultipartRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let mut multipart = Multipart::from_request(req, state).await?;
+        let parts = T::parts();
+        let mut values: Vec<Vec<PartValue>> = parts.iter().map(|_| Vec::new()).collect();
+        while let Some(field) = multipart.next_field().await? {
+            let Some(index) = field.name().and_then(|name| parts.iter().position(|part| part.name == name)) else {
+                continue;
+            };
+            values[index].push(read_part(field, &parts[index]).await?);
+        }
+        T::from_parts(values).map(TypedMultipart)
+    }
+}
+
+#[cfg(feature = "openapi")]
+mod openapi {
+    use std::collections::BTreeMap;
+
+    use oas::{Encoding, MediaType, Referenceable, RequestBody};
+
+    use super::{MultipartForm, TypedMultipart, UploadedFile};
+
+    impl crate::Schematic for UploadedFile {
+        fn name() -> &'static str {
+            "binary"
+        }
+        fn required() -> bool {
+            true
+        }
+        fn type_() -> &'static str {
+            "string"
+        }
+        fn format() -> Option<String> {
+            Some("binary".to_string())
+        }
+    }
+
+    impl<T> crate::ParameterProvider for TypedMultipart<T>
+    where
+        T: MultipartForm + crate::Schematic,
+    {
+        fn generate(_url: String) -> crate::Either<Vec<oas::Parameter>, RequestBody> {
+            let schema = T::generate_schema();
+            // Text parts default to `text/plain`, which is what OpenAPI assumes anyway; only the
+            // parts with something to say get an entry.
+            let encoding: BTreeMap<String, Encoding> = T::parts()
+                .into_iter()
+                .filter(|part| part.file || !part.content_types.is_empty())
+                .map(|part| {
+                    let content_type = if part.content_types.is_empty() {
+                        "application/octet-stream".to_string()
+                    } else {
+                        part.content_types.join(", ")
+                    };
+                    let encoding = Encoding {
+                        content_type: Some(content_type),
+                        headers: None,
+                        style: None,
+                        explode: None,
+                        allow_reserved: None,
+                    };
+                    (part.name.to_string(), encoding)
+                })
+                .collect();
+            let media_type = MediaType {
+                schema: Some(Referenceable::Data(schema.schema)),
+                example: None,
+                examples: None,
+                encoding: (!encoding.is_empty()).then_some(encoding),
+            };
+            crate::Either::Right(RequestBody {
+                description: T::doc(),
+                required: Some(true),
+                content: BTreeMap::from([("multipart/form-data".to_string(), media_type)]),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_types_match_by_essence_and_wildcard() {
+        let spec = PartSpec {
+            name: "file",
+            file: true,
+            limit: None,
+            content_types: &["image/*", "application/pdf"],
+            temp_file: false,
+        };
+        assert!(spec.accepts(Some("image/png")));
+        assert!(spec.accepts(Some("Application/PDF; name=x")));
+        assert!(!spec.accepts(Some("text/plain")));
+        assert!(!spec.accepts(None));
+    }
+
+    #[tokio::test]
+    async fn temp_files_are_fresh_and_private() {
+        let (first, _) = create_temp_file().await.unwrap();
+        let (second, _) = create_temp_file().await.unwrap();
+        assert_ne!(first, second);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&first).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        std::fs::remove_file(first).unwrap();
+        std::fs::remove_file(second).unwrap();
+    }
+}
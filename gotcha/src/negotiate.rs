@@ -0,0 +1,391 @@
+//! Bodies in whichever format the client speaks: JSON, MessagePack or CBOR.
+//!
+//! [`Negotiated<T>`] is both an extractor and a responder. As an extractor it decodes the body by
+//! its `Content-Type` and notes the format the client wants back, from `Accept` — or, without an
+//! `Accept`, the format it sent. [`Negotiated::respond`] then answers in that format. A handler
+//! without a body takes [`Format`] to learn the response format.
+//!
+//! ```ignore
+//! use gotcha::negotiate::{Format, Negotiated};
+//!
+//! async fn create(user: Negotiated<NewUser>) -> Negotiated<User> {
+//!     let created = save(&user);
+//!     user.respond(created)
+//! }
+//!
+//! async fn show(format: Format) -> Negotiated<User> {
+//!     format.respond(load())
+//! }
+//! ```
+//!
+//! JSON is always available; MessagePack needs the `msgpack` feature and CBOR the `cbor` feature.
+//! A body in a format that is not enabled is rejected with `415 Unsupported Media Type`, and an
+//! `Accept` that allows none of them with `406 Not Acceptable`. With the `openapi` feature every
+//! enabled media type is documented, on the request body and on the response.
+
+use axum::body::Bytes;
+use axum::extract::rejection::BytesRejection;
+use axum::extract::{FromRequest, FromRequestParts, Request};
+use axum::http::header::{ACCEPT, CONTENT_TYPE, VARY};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A serialization format a [`Negotiated`] body can be in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `application/json`, and any `+json` type.
+    Json,
+    /// `application/msgpack`. Structs are encoded as maps, keyed by field name.
+    #[cfg(feature = "msgpack")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+    MessagePack,
+    /// `application/cbor`.
+    #[cfg(feature = "cbor")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
+    Cbor,
+}
+
+impl Format {
+    /// The enabled formats, in order of preference.
+    pub const ALL: &'static [Format] = &[
+        Format::Json,
+        #[cfg(feature = "msgpack")]
+        Format::MessagePack,
+        #[cfg(feature = "cbor")]
+        Format::Cbor,
+    ];
+
+    /// The media type the format is sent as.
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => "application/msgpack",
+            #[cfg(feature = "cbor")]
+            Format::Cbor => "application/cbor",
+        }
+    }
+
+    /// The format a media type names, if it is enabled. Parameters such as `charset` are ignored.
+    pub fn from_media_type(media_type: &str) -> Option<Format> {
+        let essence = media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "application/json" => Some(Format::Json),
+            #[cfg(feature = "msgpack")]
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => Some(Format::MessagePack),
+            #[cfg(feature = "cbor")]
+            "application/cbor" => Some(Format::Cbor),
+            _ if essence.starts_with("application/") && essence.ends_with("+json") => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    /// The format to answer in, given the request's `Accept` header: the acceptable format with
+    /// the highest quality, `fallback` for a wildcard or a missing header, or `None` when the
+    /// client accepts none of them. A format's quality comes from the most specific range that
+    /// matches it, so `application/json;q=0, */*` refuses JSON.
+    pub fn negotiate(headers: &HeaderMap, fallback: Format) -> Option<Format> {
+        let ranges: Vec<(String, f32)> = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|range| {
+                let mut params = range.split(';');
+                let media_range = params.next()?.trim().to_ascii_lowercase();
+                let quality = params
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (!media_range.is_empty()).then_some((media_range, quality))
+            })
+            .collect();
+        if ranges.is_empty() {
+            return Some(fallback);
+        }
+        let wildcard = |range: &str| range == "*/*" || range == "application/*";
+        // The client's order: a named format where it is named, every format at a wildcard,
+        // the fallback first.
+        let mut candidates: Vec<Format> = Vec::new();
+        for (range, _) in &ranges {
+            let named: Vec<Format> = if wildcard(range) {
+                std::iter::once(fallback).chain(Format::ALL.iter().copied()).collect()
+            } else {
+                Format::from_media_type(range).into_iter().collect()
+            };
+            for format in named {
+                if !candidates.contains(&format) {
+                    candidates.push(format);
+                }
+            }
+        }
+        let quality = |format: Format| {
+            let matching = |matches: &dyn Fn(&str) -> bool| ranges.iter().filter(|(range, _)| matches(range)).map(|(_, quality)| *quality).reduce(f32::max);
+            matching(&|range| !wildcard(range) && Format::from_media_type(range) == Some(format))
+                .or_else(|| matching(&|range| range == "application/*"))
+                .or_else(|| matching(&|range| range == "*/*"))
+                .unwrap_or(0.0)
+        };
+        let mut acceptable: Vec<(Format, f32)> = candidates.into_iter().map(|format| (format, quality(format))).filter(|(_, quality)| *quality > 0.0).collect();
+        // Stable, so equally good formats keep the client's order.
+        acceptable.sort_by(|a, b| b.1.total_cmp(&a.1));
+        acceptable.first().map(|(format, _)| *format)
+    }
+
+    /// Decode a body in this format.
+    pub fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, String> {
+        match self {
+            Format::Json => serde_json::from_slice(bytes).map_err(|error| error.to_string()),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::from_slice(bytes).map_err(|error| error.to_string()),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::from_reader(bytes).map_err(|error| error.to_string()),
+        }
+    }
+
+    /// Encode a value in this format.
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Format::Json => serde_json::to_vec(value).map_err(|error| error.to_string()),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::to_vec_named(value).map_err(|error| error.to_string()),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => {
+                let mut buffer = Vec::new();
+                ciborium::into_writer(value, &mut buffer).map_err(|error| error.to_string())?;
+                Ok(buffer)
+            }
+        }
+    }
+
+    /// Answer with `value` in this format.
+    pub fn respond<T>(self, value: T) -> Negotiated<T> {
+        Negotiated { value, format: self }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.media_type())
+    }
+}
+
+/// The response format from the request's `Accept` header, JSON when it accepts anything.
+impl<S: Send + Sync> FromRequestParts<S> for Format {
+    type Rejection = NegotiationRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Format::negotiate(&parts.headers, Format::Json).ok_or(NegotiationRejection::NotAcceptable)
+    }
+}
+
+/// A body in a negotiated [`Format`]; see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct Negotiated<T> {
+    /// The decoded body, or the value to send.
+    pub value: T,
+    /// The format to respond in. On an extracted body this is what the client asked for, not
+    /// necessarily the format the body came in.
+    pub format: Format,
+}
+
+impl<T> Negotiated<T> {
+    /// Answer in the format the client asked for.
+    pub fn respond<U>(&self, value: U) -> Negotiated<U> {
+        self.format.respond(value)
+    }
+
+    /// The decoded body.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> std::ops::Deref for Negotiated<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> std::ops::DerefMut for Negotiated<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<S, T> FromRequest<S> for Negotiated<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = NegotiationRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req.headers().get(CONTENT_TYPE).and_then(|value| value.to_str().ok());
+        let body_format = content_type
+            .and_then(Format::from_media_type)
+            .ok_or_else(|| NegotiationRejection::UnsupportedMediaType(content_type.map(str::to_string)))?;
+        let format = Format::negotiate(req.headers(), body_format).ok_or(NegotiationRejection::NotAcceptable)?;
+        let bytes = Bytes::from_request(req, state).await?;
+        let value = body_format
+            .decode(&bytes)
+            .map_err(|message| NegotiationRejection::Decode { format: body_format, message })?;
+        Ok(Negotiated { value, format })
+    }
+}
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        match self.format.encode(&self.value) {
+            Ok(body) => ([(CONTENT_TYPE, self.format.media_type()), (VARY, "accept")], body).into_response(),
+            Err(error) => {
+                tracing::error!("failed to encode a {} response: {error}", self.format);
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+/// Rejection produced by the [`Negotiated`] and [`Format`] extractors.
+#[derive(Debug, thiserror::Error)]
+pub enum NegotiationRejection {
+    /// The body's content type is missing or not an enabled format. `415 Unsupported Media Type`.
+    #[error("unsupported content type {}; expected one of {}", .0.as_deref().unwrap_or("(none)"), supported())]
+    UnsupportedMediaType(Option<String>),
+    /// `Accept` allows none of the enabled formats. `406 Not Acceptable`.
+    #[error("none of the accepted media types is available; expected one of {}", supported())]
+    NotAcceptable,
+    /// The body could not be read; delegates to axum's rejection response.
+    #[error(transparent)]
+    Body(#[from] BytesRejection),
+    /// The body is not valid in its format. `400 Bad Request`.
+    #[error("failed to parse the request body as {format}: {message}")]
+    Decode {
+        /// The format the body claimed to be in.
+        format: Format,
+        /// What was wrong with it.
+        message: String,
+    },
+}
+
+fn supported() -> String {
+    Format::ALL.iter().map(Format::media_type).collect::<Vec<_>>().join(", ")
+}
+
+impl IntoResponse for NegotiationRejection {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            NegotiationRejection::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            NegotiationRejection::NotAcceptable => StatusCode::NOT_ACCEPTABLE,
+            NegotiationRejection::Body(rejection) => return (rejection.status(), rejection.body_text()).into_response(),
+            NegotiationRejection::Decode { .. } => StatusCode::BAD_REQUEST,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+#[cfg(feature = "openapi")]
+mod openapi {
+    use std::collections::BTreeMap;
+
+    use oas::{MediaType, Referenceable, RequestBody, Response, Responses};
+
+    use super::{Format, Negotiated};
+
+    /// The same schema under every enabled media type.
+    fn content<T: crate::Schematic>() -> BTreeMap<String, MediaType> {
+        Format::ALL
+            .iter()
+            .map(|format| {
+                let media_type = MediaType {
+                    schema: Some(Referenceable::Data(T::generate_schema().schema)),
+                    example: T::example(),
+                    examples: None,
+                    encoding: None,
+                };
+                (format.media_type().to_string(), media_type)
+            })
+            .collect()
+    }
+
+    impl<T: crate::Schematic> crate::ParameterProvider for Negotiated<T> {
+        fn generate(_url: String) -> crate::Either<Vec<oas::Parameter>, RequestBody> {
+            crate::Either::Right(RequestBody {
+                description: T::doc(),
+                required: Some(T::required()),
+                content: content::<T>(),
+            })
+        }
+    }
+
+    /// `Accept` is content negotiation, which OpenAPI describes through the response's media
+    /// types rather than as a parameter.
+    impl crate::ParameterProvider for Format {}
+
+    impl<T: crate::Schematic> crate::Responsible for Negotiated<T> {
+        fn response() -> Responses {
+            let response = Response {
+                description: "default return".to_string(),
+                headers: None,
+                content: Some(content::<T>()),
+                links: None,
+            };
+            Responses {
+                default: None,
+                data: BTreeMap::from([("200".to_string(), Referenceable::Data(response))]),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn accept(value: &str) -> HeaderMap {
+        HeaderMap::from_iter([(ACCEPT, HeaderValue::from_str(value).unwrap())])
+    }
+
+    #[test]
+    fn accept_picks_the_best_available_format() {
+        assert_eq!(Format::negotiate(&HeaderMap::new(), Format::Json), Some(Format::Json));
+        assert_eq!(Format::negotiate(&accept("text/html, */*;q=0.1"), Format::Json), Some(Format::Json));
+        assert_eq!(Format::negotiate(&accept("application/problem+json"), Format::Json), Some(Format::Json));
+        assert_eq!(Format::negotiate(&accept("text/html"), Format::Json), None);
+        assert_eq!(Format::negotiate(&accept("application/json;q=0"), Format::Json), None);
+    }
+
+    #[test]
+    fn specific_ranges_override_wildcards() {
+        // A refused JSON leaves the next enabled format, if there is one.
+        assert_eq!(Format::negotiate(&accept("application/json;q=0, */*"), Format::Json), Format::ALL.get(1).copied());
+        assert_eq!(Format::negotiate(&accept("*/*, application/json;q=0"), Format::Json), Format::ALL.get(1).copied());
+        assert_eq!(Format::negotiate(&accept("application/*;q=0, */*"), Format::Json), None);
+        assert_eq!(Format::negotiate(&accept("*/*;q=0.5, application/json;q=0.8"), Format::Json), Some(Format::Json));
+    }
+
+    #[cfg(all(feature = "msgpack", feature = "cbor"))]
+    #[test]
+    fn quality_orders_the_formats() {
+        let headers = accept("application/json;q=0.5, application/cbor, application/msgpack;q=0.9");
+        assert_eq!(Format::negotiate(&headers, Format::Json), Some(Format::Cbor));
+        // A wildcard answers in the request's own format.
+        assert_eq!(Format::negotiate(&accept("*/*"), Format::MessagePack), Some(Format::MessagePack));
+    }
+
+    #[cfg(all(feature = "msgpack", feature = "cbor"))]
+    #[test]
+    fn every_format_round_trips() {
+        let value = serde_json::json!({"name": "Ada", "age": 36});
+        for format in Format::ALL {
+            let bytes = format.encode(&value).unwrap();
+            assert_eq!(format.decode::<serde_json::Value>(&bytes).unwrap(), value, "{format}");
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Where the spec and its documentation UIs are served, and whether they are served at all.
+//!
+//! Everything lives under one base path: with `path = "/docs"` the spec is at
+//! `/docs/openapi.json` and `/docs/openapi.yaml`, and each enabled UI at `/docs/redoc`,
+//! `/docs/scalar` or `/docs/swagger-ui`. The UI pages are rendered once, pointing at that spec URL.
+//! Swagger UI's scripts and stylesheet are bundled too, and served below its page at
+//! `/docs/swagger-ui/…`, so it works offline and under a CSP that only allows `'self'`.
+//!
+//! The settings come from code ([`GotchaRouter::docs`](crate::GotchaRouter::docs)) or from the
+//! `[server.docs]` configuration section, which wins when present — so a production profile can
+//! turn the docs off without a rebuild:
+//!
+//! ```toml
+//! [server.docs]
+//! enabled = false
+//! ```
+
+use axum::body::Bytes;
+use axum::response::Html;
+use axum::Router;
+use serde::{Deserialize, Serialize};
+
+/// A documentation UI that can be mounted next to the spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocsUi {
+    /// Redoc, bundled into the binary. Mounted at `{path}/redoc`.
+    Redoc,
+    /// Scalar, loaded from its CDN. Mounted at `{path}/scalar`.
+    Scalar,
+    /// Swagger UI, bundled into the binary. Mounted at `{path}/swagger-ui`, with its assets below
+    /// that.
+    SwaggerUi,
+}
+
+impl DocsUi {
+    /// The route segment the UI is mounted at, below the docs base path.
+    pub fn route(&self) -> &'static str {
+        match self {
+            DocsUi::Redoc => "redoc",
+            DocsUi::Scalar => "scalar",
+            DocsUi::SwaggerUi => "swagger-ui",
+        }
+    }
+
+    fn template(&self) -> &'static str {
+        match self {
+            DocsUi::Redoc => include_str!("../../statics/redoc.html"),
+            DocsUi::Scalar => include_str!("../../statics/scalar.html"),
+            DocsUi::SwaggerUi => include_str!("../../statics/swagger-ui.html"),
+        }
+    }
+
+    /// The files the page loads from below its own route, as name, content type and template.
+    fn asset_templates(&self) -> &'static [(&'static str, &'static str, &'static str)] {
+        match self {
+            DocsUi::Redoc | DocsUi::Scalar => &[],
+            DocsUi::SwaggerUi => &[
+                ("swagger-ui-bundle.js", "text/javascript", include_str!("../../statics/swagger-ui/swagger-ui-bundle.js")),
+                ("swagger-ui.css", "text/css", include_str!("../../statics/swagger-ui/swagger-ui.css")),
+                ("swagger-initializer.js", "text/javascript", include_str!("../../statics/swagger-ui/swagger-initializer.js")),
+            ],
+        }
+    }
+
+    /// The UI's page, pointed at `spec_url`.
+    pub fn render(&self, spec_url: &str) -> String {
+        fill(self.template(), spec_url)
+    }
+
+    /// The files the page loads, pointed at `spec_url`, as name, content type and content. They
+    /// are served at `{path}/{route}/{name}`.
+    pub fn assets(&self, spec_url: &str) -> Vec<(&'static str, &'static str, Bytes)> {
+        self.asset_templates()
+            .iter()
+            .map(|&(name, content_type, template)| {
+                // Only a template with a placeholder is copied; the bundles are served as they are.
+                let content = if template.contains("{{spec_url}}") {
+                    Bytes::from(fill(template, spec_url))
+                } else {
+                    Bytes::from_static(template.as_bytes())
+                };
+                (name, content_type, content)
+            })
+            .collect()
+    }
+}
+
+/// Put `spec_url` into a page or script template.
+fn fill(template: &str, spec_url: &str) -> String {
+    // The URL lands in an HTML attribute or a JS string depending on the UI; percent-encoding
+    // the characters that could end either keeps it a plain URL in both.
+    let spec_url = spec_url
+        .replace('%', "%25")
+        .replace('"', "%22")
+        .replace('\'', "%27")
+        .replace('<', "%3C")
+        .replace('>', "%3E")
+        .replace('\\', "%5C");
+    template.replace("{{spec_url}}", &spec_url)
+}
+
+/// How the spec and documentation UIs are served.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocsConfig {
+    /// Serve the spec and UIs at all. When off, none of the routes exist.
+    pub enabled: bool,
+    /// Base path the routes are mounted under. Empty (the default) mounts them at the root.
+    pub path: String,
+    /// The UIs to mount. Defaults to Redoc and Scalar.
+    pub ui: Vec<DocsUi>,
+}
+
+impl Default for DocsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: String::new(),
+            ui: vec![DocsUi::Redoc, DocsUi::Scalar],
+        }
+    }
+}
+
+impl DocsConfig {
+    /// Docs that are not served.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// The base path, normalised to either empty or `/segment` without a trailing slash.
+    pub fn base_path(&self) -> String {
+        let trimmed = self.path.trim_matches('/');
+        if trimmed.is_empty() {
+            String::new()
+        } else {
+            format!("/{trimmed}")
+        }
+    }
+
+    /// Where the JSON spec is served, which is what the UIs load.
+    pub fn spec_url(&self) -> String {
+        format!("{}/openapi.json", self.base_path())
+    }
+
+    /// The routes serving `spec` and the UIs, or an empty router when disabled.
+    pub(crate) fn router(&self, spec: serde_json::Value) -> Router {
+        if !self.enabled {
+            return Router::new();
+        }
+        let base = self.base_path();
+        // Rendered once here rather than per request; the document never changes.
+        let yaml = super::to_yaml(&spec);
+        let mut router = Router::new()
+            .route(&self.spec_url(), axum::routing::get(move || async move { axum::Json(spec.clone()) }))
+            .route(
+                &format!("{base}/openapi.yaml"),
+                axum::routing::get(move || async move { ([(axum::http::header::CONTENT_TYPE, "application/yaml")], yaml.clone()) }),
+            );
+        let spec_url = self.spec_url();
+        for ui in &self.ui {
+            // `Bytes` so each request clones a reference count, not the (megabyte-sized) page.
+            let page = Bytes::from(ui.render(&spec_url));
+            router = router.route(&format!("{base}/{}", ui.route()), axum::routing::get(move || async move { Html(page.clone()) }));
+            for (name, content_type, content) in ui.assets(&spec_url) {
+                router = router.route(
+                    &format!("{base}/{}/{name}", ui.route()),
+                    axum::routing::get(move || async move { ([(axum::http::header::CONTENT_TYPE, content_type)], content.clone()) }),
+                );
+            }
+        }
+        router
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_path_is_normalised() {
+        let with = |path: &str| DocsConfig {
+            path: path.to_string(),
+            ..DocsConfig::default()
+        };
+        assert_eq!(with("").spec_url(), "/openapi.json");
+        assert_eq!(with("/").spec_url(), "/openapi.json");
+        assert_eq!(with("docs").spec_url(), "/docs/openapi.json");
+        assert_eq!(with("/internal/docs/").spec_url(), "/internal/docs/openapi.json");
+    }
+
+    #[test]
+    fn templates_point_at_the_configured_spec() {
+        for ui in [DocsUi::Redoc, DocsUi::Scalar, DocsUi::SwaggerUi] {
+            let page = ui.render("/docs/openapi.json");
+            let assets = ui.assets("/docs/openapi.json");
+            let loads_spec = page.contains("/docs/openapi.json") || assets.iter().any(|(_, _, content)| std::str::from_utf8(content).unwrap().contains("/docs/openapi.json"));
+            assert!(loads_spec, "{ui:?} loads the configured spec");
+            assert!(!page.contains("{{spec_url}}"), "{ui:?} has no placeholder left");
+        }
+    }
+
+    #[test]
+    fn spec_url_cannot_break_out_of_the_page() {
+        let page = DocsUi::Redoc.render("/a\"</script>");
+        assert!(page.contains("/a%22%3C/script%3E"));
+    }
+
+    #[test]
+    fn swagger_ui_loads_nothing_from_elsewhere() {
+        let page = DocsUi::SwaggerUi.render("/openapi.json");
+        assert!(!page.contains("http"), "the page only loads its own assets");
+        assert!(!page.contains("<script>"), "and has no inline script a CSP would block");
+        let names: Vec<_> = DocsUi::SwaggerUi.assets("/openapi.json").into_iter().map(|(name, _, _)| name).collect();
+        for name in names {
+            assert!(page.contains(&format!("swagger-ui/{name}")), "{name} is loaded by the page");
+        }
+    }
+
+    #[test]
+    fn config_section_is_read_with_defaults() {
+        let config: DocsConfig = serde_json::from_value(serde_json::json!({"path": "/docs", "ui": ["swagger_ui"]})).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.ui, vec![DocsUi::SwaggerUi]);
+    }
+}
@@ -0,0 +1,313 @@
+//! The spec's `info` block, servers, tags and external docs.
+//!
+//! Three layers fill these in, each overriding the one before it field by field:
+//!
+//! 1. the `Cargo.toml` metadata of the application: its name, version, description, license,
+//!    authors and homepage, read where its `#[api]` handlers are compiled. When the routed
+//!    handlers come from several crates, the one most of them are in wins. Only without any
+//!    `#[api]` handler does the spec fall back to a `"Gotcha"` title at version `1.0.0`;
+//! 2. what the code sets with [`GotchaRouter::info`](crate::GotchaRouter::info) — with
+//!    [`api_info!`](crate::api_info) to name a crate explicitly;
+//! 3. the `[server.openapi]` configuration section.
+//!
+//! The [`openapi`](crate::GotchaRouter::openapi) transform runs after all three.
+//!
+//! ```toml
+//! [server.openapi]
+//! title = "Pet Store"
+//! servers = [{ url = "https://api.example.com", description = "production" }]
+//! tags = [{ name = "pets", description = "Everything about your pets" }]
+//! ```
+
+use oas::{Contact, ExternalDocumentation, License, OpenAPIV3, Server, Tag};
+use serde::{Deserialize, Serialize};
+
+/// Metadata for the generated spec. Every field is optional so that a configuration section only
+/// needs the fields it changes; see the [module docs](self) for how the layers combine.
+///
+/// `contact`, `license`, `servers`, `tags` and `external_docs` are the OpenAPI objects themselves,
+/// so inside them the keys are OpenAPI's (`externalDocs` on a tag, for instance).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiInfo {
+    /// The API's title.
+    pub title: Option<String>,
+    /// The API's version — of the document, not of OpenAPI.
+    pub version: Option<String>,
+    /// A description of the API. CommonMark is allowed.
+    pub description: Option<String>,
+    /// A URL to the terms of service.
+    pub terms_of_service: Option<String>,
+    /// Who to contact about the API.
+    pub contact: Option<Contact>,
+    /// The API's license.
+    pub license: Option<License>,
+    /// The servers the API is reachable at. Replaces, rather than extends, the layer below.
+    pub servers: Vec<Server>,
+    /// Tags with their descriptions, in the order documentation UIs should list them. A tag of
+    /// the same name in a later layer replaces the earlier one.
+    pub tags: Vec<Tag>,
+    /// Documentation for the API as a whole, hosted elsewhere.
+    pub external_docs: Option<ExternalDocumentation>,
+}
+
+impl ApiInfo {
+    /// Metadata with a title and version, the two fields OpenAPI requires.
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            title: Some(title.into()),
+            version: Some(version.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Set the description.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add a server the API is reachable at.
+    pub fn server(mut self, url: impl Into<String>, description: impl Into<Option<String>>) -> Self {
+        self.servers.push(Server {
+            url: url.into(),
+            description: description.into(),
+            variables: None,
+        });
+        self
+    }
+
+    /// Declare a tag and its description. Operations join a tag through `#[api(group = "..")]`.
+    pub fn tag(mut self, name: impl Into<String>, description: impl Into<String>) -> Self {
+        self.tags.push(Tag::new(name, Some(description.into())));
+        self
+    }
+
+    /// Overlay `other` on top of `self`: each field `other` sets wins.
+    pub fn merge(mut self, other: ApiInfo) -> Self {
+        self.title = other.title.or(self.title);
+        self.version = other.version.or(self.version);
+        self.description = other.description.or(self.description);
+        self.terms_of_service = other.terms_of_service.or(self.terms_of_service);
+        self.contact = other.contact.or(self.contact);
+        self.license = other.license.or(self.license);
+        self.external_docs = other.external_docs.or(self.external_docs);
+        if !other.servers.is_empty() {
+            self.servers = other.servers;
+        }
+        for tag in other.tags {
+            match self.tags.iter_mut().find(|each| each.name == tag.name) {
+                Some(existing) => *existing = tag,
+                None => self.tags.push(tag),
+            }
+        }
+        self
+    }
+
+    /// Write this metadata into `spec`, keeping whatever it leaves unset.
+    ///
+    /// Declared tags come first, in declaration order, followed by the tags operations use that
+    /// were not declared.
+    pub fn apply(self, spec: &mut OpenAPIV3) {
+        if let Some(title) = self.title {
+            spec.info.title = title;
+        }
+        if let Some(version) = self.version {
+            spec.info.version = version;
+        }
+        if self.description.is_some() {
+            spec.info.description = self.description;
+        }
+        if self.terms_of_service.is_some() {
+            spec.info.terms_of_service = self.terms_of_service;
+        }
+        if self.contact.is_some() {
+            spec.info.contact = self.contact;
+        }
+        if self.license.is_some() {
+            spec.info.license = self.license;
+        }
+        if self.external_docs.is_some() {
+            spec.external_docs = self.external_docs;
+        }
+        if !self.servers.is_empty() {
+            spec.servers = Some(self.servers);
+        }
+        if !self.tags.is_empty() {
+            let mut tags = self.tags;
+            for used in spec.tags.take().into_iter().flatten() {
+                if !tags.iter().any(|each| each.name == used.name) {
+                    tags.push(used);
+                }
+            }
+            spec.tags = Some(tags);
+        }
+    }
+}
+
+/// [`ApiInfo`] from the calling crate's `Cargo.toml`: its name, version and description, its
+/// license, its authors as the contact, and its homepage (or repository) as external docs.
+///
+/// A macro because the `CARGO_PKG_*` variables have to be read where the application is compiled,
+/// not where gotcha is. The spec already defaults to the crate its `#[api]` handlers are in; call
+/// this where that is not the crate to describe, such as a binary routing a library's handlers.
+///
+/// ```rust,no_run
+/// use gotcha::GotchaRouter;
+///
+/// let router: GotchaRouter<()> = GotchaRouter::default().info(gotcha::api_info!().tag("pets", "Everything about your pets"));
+/// ```
+#[macro_export]
+macro_rules! api_info {
+    () => {
+        $crate::openapi::ApiInfo::from_package($crate::__package!())
+    };
+}
+
+/// The calling crate's [`Package`], for `#[api]` to record on each handler.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __package {
+    () => {
+        $crate::openapi::info::Package {
+            name: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            description: env!("CARGO_PKG_DESCRIPTION"),
+            license: env!("CARGO_PKG_LICENSE"),
+            authors: env!("CARGO_PKG_AUTHORS"),
+            homepage: env!("CARGO_PKG_HOMEPAGE"),
+            repository: env!("CARGO_PKG_REPOSITORY"),
+        }
+    };
+}
+
+/// The `CARGO_PKG_*` values [`api_info!`](crate::api_info) reads. Cargo sets an unset manifest
+/// field to an empty string.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Package {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub description: &'static str,
+    pub license: &'static str,
+    pub authors: &'static str,
+    pub homepage: &'static str,
+    pub repository: &'static str,
+}
+
+impl ApiInfo {
+    #[doc(hidden)]
+    pub fn from_package(package: Package) -> Self {
+        let present = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        // Authors are `:`-separated, each `Name <email>`; the first one is the contact.
+        let contact = package
+            .authors
+            .split(':')
+            .map(str::trim)
+            .find(|author| !author.is_empty())
+            .map(|author| match author.split_once('<') {
+                Some((name, email)) => Contact {
+                    name: present(name.trim()),
+                    url: None,
+                    email: present(email.trim_end_matches('>').trim()),
+                },
+                None => Contact {
+                    name: Some(author.to_string()),
+                    url: None,
+                    email: None,
+                },
+            });
+        Self {
+            title: present(package.name),
+            version: present(package.version),
+            description: present(package.description),
+            contact,
+            license: present(package.license).map(|name| License { name, url: None }),
+            external_docs: present(package.homepage)
+                .or_else(|| present(package.repository))
+                .map(|url| ExternalDocumentation { description: None, url }),
+            ..Self::default()
+        }
+    }
+
+    /// The metadata of the package most of `operations` are declared in; ties go to the name that
+    /// sorts first. Empty when there are no operations.
+    pub(crate) fn from_operations<'a>(operations: impl IntoIterator<Item = &'a super::Operable>) -> Self {
+        let mut counts: Vec<(Package, usize)> = Vec::new();
+        for operable in operations {
+            match counts.iter_mut().find(|(package, _)| package.name == operable.package.name) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((operable.package, 1)),
+            }
+        }
+        counts
+            .into_iter()
+            .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then_with(|| b.name.cmp(a.name)))
+            .map(|(package, _)| Self::from_package(package))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn later_layers_win_field_by_field() {
+        let code = ApiInfo::new("pets", "0.3.0")
+            .description("from code")
+            .tag("pets", "from code")
+            .tag("owners", "from code");
+        let config: ApiInfo = serde_json::from_value(serde_json::json!({
+            "title": "Pet Store",
+            "tags": [{"name": "pets", "description": "from config"}]
+        }))
+        .unwrap();
+
+        let merged = code.merge(config);
+        assert_eq!(merged.title.as_deref(), Some("Pet Store"));
+        assert_eq!(merged.version.as_deref(), Some("0.3.0"));
+        assert_eq!(merged.description.as_deref(), Some("from code"));
+        let tags: Vec<_> = merged.tags.iter().map(|tag| (tag.name.as_str(), tag.description.as_deref())).collect();
+        assert_eq!(tags, vec![("pets", Some("from config")), ("owners", Some("from code"))]);
+    }
+
+    #[test]
+    fn declared_tags_lead_and_used_ones_follow() {
+        let mut spec = super::super::generate_openapi(HashMap::new());
+        spec.tags = Some(vec![Tag::new("zebra", None), Tag::new("pets", None)]);
+
+        ApiInfo::default().tag("pets", "Everything about your pets").apply(&mut spec);
+
+        let tags: Vec<_> = spec.tags.unwrap().into_iter().map(|tag| (tag.name, tag.description)).collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("pets".to_string(), Some("Everything about your pets".to_string())),
+                ("zebra".to_string(), None)
+            ]
+        );
+    }
+
+    #[test]
+    fn package_metadata_fills_the_info_block() {
+        let info = ApiInfo::from_package(Package {
+            name: "petstore",
+            version: "1.2.3",
+            description: "",
+            license: "MIT",
+            authors: "Ada Lovelace <ada@example.com>:Charles Babbage",
+            homepage: "",
+            repository: "https://example.com/petstore",
+        });
+        assert_eq!(info.title.as_deref(), Some("petstore"));
+        assert_eq!(info.description, None, "an empty manifest field is unset");
+        let contact = info.contact.unwrap();
+        assert_eq!(contact.name.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(contact.email.as_deref(), Some("ada@example.com"));
+        assert_eq!(info.license.unwrap().name, "MIT");
+        assert_eq!(info.external_docs.unwrap().url, "https://example.com/petstore");
+    }
+}
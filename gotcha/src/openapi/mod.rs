@@ -7,7 +7,7 @@
 //!
 //! - Automatic OpenAPI spec generation from route definitions
 //! - Support for operation parameters, request bodies, and responses
-//! - Built-in Redoc and Scalar UI for API documentation viewing
+//! - Built-in Redoc, Scalar and Swagger UI for API documentation viewing
 //! - Grouping operations by tags
 //! - Parameter validation and type information
 //!
@@ -27,22 +27,29 @@
 //! }
 //! ```
 //!
-//! The generated spec is served at `/openapi.json`, with the Redoc UI at `/redoc`
-//! and the Scalar UI at `/scalar` when the feature is enabled.
+//! The generated spec is served at `/openapi.json` and `/openapi.yaml`, with the Redoc UI at `/redoc`
+//! and the Scalar UI at `/scalar` when the feature is enabled; [`docs`] moves, trims or disables
+//! those routes. It is OpenAPI 3.0 unless the
+//! router selects 3.1 with [`GotchaRouter::openapi_version`](crate::GotchaRouter::openapi_version).
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use axum::http::Method;
-use axum::response::Html;
 use convert_case::{Case, Casing};
 use either::Either;
-use oas::{Components, Info, OpenAPIV3, Operation, Parameter, PathItem, Referenceable, RequestBody, Responses, SecurityRequirement, Tag};
+use gotcha_core::registry::{self, Direction};
+use oas::{Components, Example, Info, MediaType, OpenAPIV3, Operation, Parameter, PathItem, Referenceable, RequestBody, Responses, SecurityRequirement, Tag};
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::Responder;
-
+pub mod docs;
+pub mod info;
 pub mod schematic;
+pub mod version;
+
+pub use docs::{DocsConfig, DocsUi};
+pub use info::ApiInfo;
+pub use version::OpenApiVersion;
 
 /// Match a `{name}` path parameter.
 ///
@@ -52,14 +59,6 @@ pub mod schematic;
 /// names. Before 0.8 axum used `:id`, and every path had to be translated here.
 pub(crate) static PATH_VARIABLE_PATTERN: &str = r"\{([^}]+)\}";
 
-pub(crate) async fn openapi_html() -> impl Responder {
-    Html(include_str!("../../statics/redoc.html"))
-}
-
-pub(crate) async fn scalar_html() -> impl Responder {
-    Html(include_str!("../../statics/scalar.html"))
-}
-
 /// What one handler argument contributes: either operation parameters or the request body.
 pub type ParamType = Either<Vec<Parameter>, RequestBody>;
 
@@ -99,10 +98,46 @@ pub struct Operable {
     pub deprecated: bool,
     /// Name of a security scheme this operation requires.
     pub security: Option<&'static str>,
+    /// Named request and response examples, from `#[api(example(..))]`.
+    pub examples: &'static [OperationExample],
     /// One constructor per handler argument.
     pub parameters: &'static Lazy<Vec<ParamConstructor>>,
     /// Builds the operation's responses from the handler's return type.
     pub responses: &'static Lazy<Box<dyn Fn() -> Responses + Sync + Send + 'static>>,
+    /// The `Cargo.toml` metadata of the crate the handler is in, which the spec's `info` block
+    /// defaults to.
+    pub package: info::Package,
+}
+
+/// A named example of an operation's request or response body, from
+/// `#[api(example(name = "...", value = ...))]`.
+pub struct OperationExample {
+    /// The key the example is listed under.
+    pub name: &'static str,
+    /// A short description of the example.
+    pub summary: Option<&'static str>,
+    /// The response status (or `"default"`) whose body this is; `None` for the request body.
+    pub response: Option<&'static str>,
+    /// The value, serialized through serde.
+    pub value: fn() -> serde_json::Value,
+}
+
+impl OperationExample {
+    /// Add this example to every media type in `content`.
+    fn apply(&self, content: &mut BTreeMap<String, MediaType>) {
+        for media_type in content.values_mut() {
+            let example = Example {
+                summary: self.summary.map(str::to_string),
+                description: None,
+                value: Some((self.value)()),
+                external_value: None,
+            };
+            media_type
+                .examples
+                .get_or_insert_with(BTreeMap::new)
+                .insert(self.name.to_string(), Referenceable::Data(example));
+        }
+    }
 }
 
 impl Operable {
@@ -112,14 +147,15 @@ impl Operable {
         let mut params = vec![];
         let mut request_body = None;
         for item in self.parameters.iter() {
-            match item(path.clone()) {
+            match registry::directed(Direction::Request, || item(path.clone())) {
                 Either::Left(params_vec) => {
                     params.extend(params_vec.into_iter().map(|param| Referenceable::Data(param.clone())));
                 }
                 Either::Right(req_body) => request_body = Some(Referenceable::Data(req_body.clone())),
             }
         }
-        let responses = (self.responses)();
+        let mut responses = registry::directed(Direction::Response, || (self.responses)());
+        self.apply_examples(&mut request_body, &mut responses);
 
         // An explicit `#[api(summary = "...")]` wins; otherwise derive it from the id in Title Case.
         let summary = self.summary.map(|s| s.to_string()).or_else(|| Some(self.id.to_case(Case::Title)));
@@ -145,6 +181,40 @@ impl Operable {
             servers: None,
         }
     }
+
+    /// Attach each `#[api(example(..))]` to the body it describes.
+    ///
+    /// # Panics
+    ///
+    /// When an example names a body the operation does not have — a request example on an
+    /// operation without a request body, or a response status it does not document.
+    fn apply_examples(&self, request_body: &mut Option<Referenceable<RequestBody>>, responses: &mut Responses) {
+        for example in self.examples {
+            let content = match example.response {
+                None => match request_body {
+                    Some(Referenceable::Data(body)) => Some(&mut body.content),
+                    _ => None,
+                },
+                Some("default") => match &mut responses.default {
+                    Some(Referenceable::Data(response)) => response.content.as_mut(),
+                    _ => None,
+                },
+                Some(status) => match responses.data.get_mut(status) {
+                    Some(Referenceable::Data(response)) => response.content.as_mut(),
+                    _ => None,
+                },
+            };
+            match content {
+                Some(content) => example.apply(content),
+                None => panic!(
+                    "example `{}` of operation `{}` is for a {} body the operation does not have",
+                    example.name,
+                    self.id,
+                    example.response.map_or_else(|| "request".to_string(), |status| format!("`{status}` response"))
+                ),
+            }
+        }
+    }
 }
 
 inventory::collect!(Operable);
@@ -154,16 +224,41 @@ inventory::collect!(Operable);
 /// Every operation is generated inside a single [`registry::collect`](gotcha_core::registry::collect)
 /// scope, so each named schema is emitted once under `components/schemas` and referenced by `$ref`
 /// at its use sites (which is also what lets recursive types produce a finite spec).
+///
+/// # Panics
+///
+/// When two different types register the same component name — say two `User` structs from
+/// different modules — the way axum panics on overlapping routes: one of the schemas would
+/// otherwise be documented for both. Rename one with `#[schematic(rename = "...")]`.
 pub fn generate_openapi(operables: HashMap<(String, Method), &'static Operable>) -> OpenAPIV3 {
-    let (operations, schemas) = gotcha_core::registry::collect(|| {
+    assemble(operables, false)
+}
+
+/// [`generate_openapi`], with separate request and response components for types whose
+/// `readOnly` / `writeOnly` fields make the two differ when `split_schemas` is set; see
+/// [`GotchaRouter::split_schemas`](crate::GotchaRouter::split_schemas).
+pub(crate) fn assemble(operables: HashMap<(String, Method), &'static Operable>, split_schemas: bool) -> OpenAPIV3 {
+    let generate = || {
         operables
-            .into_iter()
+            .iter()
             .map(|((path, method), operable)| {
                 let operation = operable.generate(path.clone());
-                ((path, method), operation)
+                ((path.clone(), method.clone()), operation)
             })
             .collect::<HashMap<(String, Method), Operation>>()
-    });
+    };
+    let collected = if split_schemas {
+        registry::try_collect_by_direction(generate)
+    } else {
+        registry::try_collect(generate)
+    };
+    let (operations, schemas) = match collected {
+        Ok(collected) => collected,
+        Err(collisions) => {
+            let collisions = collisions.iter().map(ToString::to_string).collect::<Vec<_>>();
+            panic!("conflicting OpenAPI schema names:\n{}", collisions.join("\n"))
+        }
+    };
 
     let components = (!schemas.is_empty()).then(|| Components {
         schemas: Some(schemas.into_iter().map(|(name, schema)| (name, Referenceable::Data(schema))).collect()),
@@ -191,21 +286,16 @@ pub fn generate_openapi(operables: HashMap<(String, Method), &'static Operable>)
         components,
         security: None,
         tags: None,
-        openapi: "3.0.0".to_string(),
+        openapi: OpenApiVersion::V3_0.as_str().to_string(),
         external_docs: None,
         extras: None,
     };
+    // Sorted, since the operations come out of a `HashMap`. Declared tags (with descriptions, in
+    // their own order) are put in front of these by [`ApiInfo::apply`].
+    let mut used_tags = BTreeSet::new();
     for ((path, method), operation) in operations {
         let path = replace_path_variable(path);
-        if let Some(added_tags) = &operation.tags {
-            added_tags.iter().for_each(|tag| {
-                if let Some(tags) = &mut spec.tags {
-                    if !tags.iter().any(|each| each.name.eq(tag)) {
-                        tags.push(Tag::new(tag, None))
-                    }
-                }
-            })
-        }
+        used_tags.extend(operation.tags.iter().flatten().cloned());
         let entry = spec.paths.entry(path.to_string()).or_insert_with(|| PathItem {
             _ref: None,
             summary: None,
@@ -233,9 +323,46 @@ pub fn generate_openapi(operables: HashMap<(String, Method), &'static Operable>)
             _ => {}
         }
     }
+    if !used_tags.is_empty() {
+        spec.tags = Some(used_tags.into_iter().map(|tag| Tag::new(tag, None)).collect());
+    }
     spec
 }
 
+/// Render an assembled spec as YAML, as served at `/openapi.yaml`.
+pub fn to_yaml(spec: &serde_json::Value) -> String {
+    // A `serde_json::Value` always has a YAML representation, so this cannot fail.
+    serde_yaml::to_string(spec).expect("a JSON value is representable as YAML")
+}
+
+/// Write `spec` to `path`, returning whether the file's contents changed.
+///
+/// The format follows the extension: `.yaml` / `.yml` get YAML, anything else pretty-printed
+/// JSON. The file is only touched when the rendered spec differs, so a test can keep a committed
+/// copy up to date *and* fail when it was stale:
+///
+/// ```rust,no_run
+/// use gotcha::GotchaRouter;
+///
+/// # fn routes() -> GotchaRouter<()> { GotchaRouter::default() }
+/// let spec = routes().openapi_spec();
+/// let changed = gotcha::openapi::write_spec("openapi.yaml", &spec).unwrap();
+/// assert!(!changed, "openapi.yaml was stale; the regenerated file needs committing");
+/// ```
+pub fn write_spec(path: impl AsRef<std::path::Path>, spec: &serde_json::Value) -> std::io::Result<bool> {
+    let path = path.as_ref();
+    let rendered = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml") => to_yaml(spec),
+        _ => serde_json::to_string_pretty(spec).map_err(std::io::Error::other)? + "\n",
+    };
+    match std::fs::read_to_string(path) {
+        Ok(existing) if existing == rendered => Ok(false),
+        Ok(_) => std::fs::write(path, rendered).map(|_| true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => std::fs::write(path, rendered).map(|_| true),
+        Err(e) => Err(e),
+    }
+}
+
 #[cfg(all(test, feature = "openapi"))]
 mod tests {
     use super::*;
@@ -4,4 +4,4 @@
 //! `gotcha::openapi::schematic::Schematic` keep resolving unchanged. `ParameterProvider`
 //! and the axum-facing impls come from `gotcha_core`'s `axum` feature, which the `gotcha`
 //! `openapi` feature enables.
-pub use gotcha_core::{EnhancedSchema, ParameterProvider, Schematic};
+pub use gotcha_core::{schematic_remote, EnhancedSchema, ParameterProvider, SchemaBuilder, Schematic};
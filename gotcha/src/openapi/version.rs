@@ -0,0 +1,286 @@
+//! The OpenAPI version a served document follows, and the pass that turns the generated 3.0
+//! document into a 3.1 one.
+//!
+//! Everything upstream — the `oas` types, `Schematic`, the derive — speaks OpenAPI 3.0, so the
+//! spec is always *assembled* as 3.0 and only converted on the way out. That keeps a single
+//! generator and lets the [`GotchaRouter::openapi`](crate::GotchaRouter::openapi) transform keep
+//! working on the typed [`OpenAPIV3`] regardless of the version being served.
+//!
+//! 3.1 adopts JSON Schema 2020-12 for its Schema Object, which changes a handful of keywords:
+//!
+//! - `nullable: true` is gone; a nullable schema admits `"null"` in its `type` (`["string",
+//!   "null"]`), or — when it has no `type`, as with a `$ref` — becomes `anyOf: [.., {type: null}]`.
+//! - a schema's single `example` becomes the `examples` array.
+//! - `exclusiveMinimum` / `exclusiveMaximum` are numbers, not flags on `minimum` / `maximum`.
+//! - a one-value `enum` is written as `const`.
+//! - `$ref` may carry sibling keywords, so a description next to a reference is kept as is.
+
+use oas::OpenAPIV3;
+use serde_json::{Map, Value};
+
+/// Which OpenAPI version the generated document is served as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpenApiVersion {
+    /// OpenAPI 3.0.0, the historical output.
+    #[default]
+    V3_0,
+    /// OpenAPI 3.1.0, whose schemas are JSON Schema 2020-12.
+    V3_1,
+}
+
+impl OpenApiVersion {
+    /// The value of the document's `openapi` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OpenApiVersion::V3_0 => "3.0.0",
+            OpenApiVersion::V3_1 => "3.1.0",
+        }
+    }
+}
+
+/// Serialize `spec` as a document of the requested `version`.
+pub fn render(spec: &OpenAPIV3, version: OpenApiVersion) -> Value {
+    let mut document = spec.to_value();
+    if version == OpenApiVersion::V3_1 {
+        upgrade_to_3_1(&mut document);
+    }
+    document
+}
+
+/// Rewrite a 3.0 document in place into its 3.1 equivalent.
+pub fn upgrade_to_3_1(document: &mut Value) {
+    let Some(root) = document.as_object_mut() else {
+        return;
+    };
+    root.insert("openapi".to_string(), Value::from(OpenApiVersion::V3_1.as_str()));
+
+    if let Some(schemas) = root.get_mut("components").and_then(|c| c.get_mut("schemas")).and_then(Value::as_object_mut) {
+        schemas.values_mut().for_each(upgrade_schema);
+    }
+    if let Some(paths) = root.get_mut("paths").and_then(Value::as_object_mut) {
+        for path_item in paths.values_mut() {
+            let Some(path_item) = path_item.as_object_mut() else {
+                continue;
+            };
+            for (key, value) in path_item.iter_mut() {
+                match key.as_str() {
+                    "parameters" => upgrade_parameters(value),
+                    "get" | "put" | "post" | "delete" | "options" | "head" | "patch" | "trace" => upgrade_operation(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+fn upgrade_operation(operation: &mut Value) {
+    if let Some(parameters) = operation.get_mut("parameters") {
+        upgrade_parameters(parameters);
+    }
+    if let Some(body) = operation.get_mut("requestBody") {
+        upgrade_content(body);
+    }
+    if let Some(responses) = operation.get_mut("responses").and_then(Value::as_object_mut) {
+        for response in responses.values_mut() {
+            upgrade_content(response);
+            if let Some(headers) = response.get_mut("headers").and_then(Value::as_object_mut) {
+                for header in headers.values_mut() {
+                    if let Some(schema) = header.get_mut("schema") {
+                        upgrade_schema(schema);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn upgrade_parameters(parameters: &mut Value) {
+    for parameter in parameters.as_array_mut().into_iter().flatten() {
+        if let Some(schema) = parameter.get_mut("schema") {
+            upgrade_schema(schema);
+        }
+        upgrade_content(parameter);
+    }
+}
+
+/// The schemas under a request body's, response's or parameter's `content` map.
+fn upgrade_content(holder: &mut Value) {
+    if let Some(content) = holder.get_mut("content").and_then(Value::as_object_mut) {
+        for media_type in content.values_mut() {
+            if let Some(schema) = media_type.get_mut("schema") {
+                upgrade_schema(schema);
+            }
+        }
+    }
+}
+
+/// Keywords that describe a schema rather than constrain it. When a typeless nullable schema is
+/// split into `anyOf`, these stay on the outside so the description still reads as the field's.
+const ANNOTATIONS: &[&str] = &["title", "description", "default", "examples", "deprecated", "readOnly", "writeOnly"];
+
+/// Convert one Schema Object, and every schema nested in it, from 3.0 to 3.1.
+pub fn upgrade_schema(schema: &mut Value) {
+    let Some(object) = schema.as_object_mut() else {
+        return;
+    };
+
+    for key in ["properties", "patternProperties"] {
+        if let Some(properties) = object.get_mut(key).and_then(Value::as_object_mut) {
+            properties.values_mut().for_each(upgrade_schema);
+        }
+    }
+    for key in ["items", "additionalProperties", "not"] {
+        if let Some(nested) = object.get_mut(key) {
+            upgrade_schema(nested);
+        }
+    }
+    for key in ["allOf", "anyOf", "oneOf", "prefixItems"] {
+        if let Some(branches) = object.get_mut(key).and_then(Value::as_array_mut) {
+            branches.iter_mut().for_each(upgrade_schema);
+        }
+    }
+
+    if let Some(example) = object.remove("example") {
+        object.entry("examples").or_insert_with(|| Value::Array(vec![example]));
+    }
+
+    for (flag, bound) in [("exclusiveMinimum", "minimum"), ("exclusiveMaximum", "maximum")] {
+        if let Some(Value::Bool(exclusive)) = object.get(flag) {
+            if *exclusive {
+                match object.remove(bound) {
+                    Some(limit) => object.insert(flag.to_string(), limit),
+                    None => object.remove(flag),
+                };
+            } else {
+                object.remove(flag);
+            }
+        }
+    }
+
+    let nullable = matches!(object.remove("nullable"), Some(Value::Bool(true)));
+    if nullable {
+        make_nullable(object);
+    }
+
+    if let Some(Value::Array(values)) = object.get("enum") {
+        if let [only] = values.as_slice() {
+            let only = only.clone();
+            object.remove("enum");
+            object.insert("const".to_string(), only);
+        }
+    }
+}
+
+/// Admit `null` into a schema that 3.0 marked `nullable`.
+fn make_nullable(object: &mut Map<String, Value>) {
+    match object.get_mut("type") {
+        Some(Value::String(ty)) => {
+            let ty = std::mem::take(ty);
+            object.insert("type".to_string(), Value::Array(vec![Value::String(ty), Value::from("null")]));
+            // A nullable enum must list `null` too, or the `type` admits a value the enum rejects.
+            if let Some(Value::Array(values)) = object.get_mut("enum") {
+                if !values.contains(&Value::Null) {
+                    values.push(Value::Null);
+                }
+            }
+        }
+        Some(Value::Array(types)) => {
+            if !types.iter().any(|ty| ty == "null") {
+                types.push(Value::from("null"));
+            }
+        }
+        // No `type` to extend (`$ref`, `oneOf`, …): keep the schema as one branch and `null` as
+        // the other.
+        _ => {
+            let (annotations, inner): (Map<String, Value>, Map<String, Value>) =
+                std::mem::take(object).into_iter().partition(|(key, _)| ANNOTATIONS.contains(&key.as_str()));
+            *object = annotations;
+            let null = Value::Object(Map::from_iter([("type".to_string(), Value::from("null"))]));
+            object.insert("anyOf".to_string(), Value::Array(vec![Value::Object(inner), null]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn upgraded(mut schema: Value) -> Value {
+        upgrade_schema(&mut schema);
+        schema
+    }
+
+    #[test]
+    fn nullable_type_becomes_a_type_array() {
+        assert_eq!(upgraded(json!({"type": "string", "nullable": true})), json!({"type": ["string", "null"]}));
+        // `nullable: false` is simply dropped.
+        assert_eq!(upgraded(json!({"type": "integer", "nullable": false})), json!({"type": "integer"}));
+    }
+
+    #[test]
+    fn nullable_reference_keeps_its_description_outside_the_any_of() {
+        assert_eq!(
+            upgraded(json!({"$ref": "#/components/schemas/User", "description": "the owner", "nullable": true})),
+            json!({
+                "description": "the owner",
+                "anyOf": [{"$ref": "#/components/schemas/User"}, {"type": "null"}]
+            })
+        );
+    }
+
+    #[test]
+    fn nullable_enum_admits_null() {
+        assert_eq!(
+            upgraded(json!({"type": "string", "enum": ["a", "b"], "nullable": true})),
+            json!({"type": ["string", "null"], "enum": ["a", "b", null]})
+        );
+    }
+
+    #[test]
+    fn example_becomes_examples() {
+        assert_eq!(
+            upgraded(json!({"type": "integer", "example": 42})),
+            json!({"type": "integer", "examples": [42]})
+        );
+    }
+
+    #[test]
+    fn exclusive_bounds_become_numbers() {
+        assert_eq!(
+            upgraded(json!({"type": "number", "minimum": 0.0, "exclusiveMinimum": true, "maximum": 10.0, "exclusiveMaximum": false})),
+            json!({"type": "number", "exclusiveMinimum": 0.0, "maximum": 10.0})
+        );
+    }
+
+    #[test]
+    fn single_value_enum_becomes_const() {
+        assert_eq!(
+            upgraded(json!({"type": "string", "enum": ["Circle"]})),
+            json!({"type": "string", "const": "Circle"})
+        );
+    }
+
+    #[test]
+    fn nested_schemas_are_converted() {
+        let schema = upgraded(json!({
+            "type": "object",
+            "properties": {
+                "tags": {"type": "array", "items": {"type": "string", "nullable": true}},
+                "extra": {"type": "object", "additionalProperties": {"type": "integer", "example": 1}}
+            },
+            "oneOf": [{"type": "string", "enum": ["only"]}]
+        }));
+        assert_eq!(schema["properties"]["tags"]["items"]["type"], json!(["string", "null"]));
+        assert_eq!(schema["properties"]["extra"]["additionalProperties"]["examples"], json!([1]));
+        assert_eq!(schema["oneOf"][0]["const"], json!("only"));
+    }
+
+    #[test]
+    fn a_property_named_like_a_keyword_is_left_alone() {
+        // `nullable` here is a field of the object, not the 3.0 keyword.
+        let schema = upgraded(json!({"type": "object", "properties": {"nullable": {"type": "boolean"}}}));
+        assert_eq!(schema["properties"]["nullable"], json!({"type": "boolean"}));
+    }
+}
@@ -0,0 +1,21 @@
+//! What background work returns: scheduled tasks, queued jobs and event subscribers alike.
+
+/// What a task body, a job's message or a subscriber returns: `()` for work that cannot fail, or
+/// a `Result` whose error is logged — and, for tasks and jobs, retried according to their
+/// `RetryPolicy`.
+pub trait Outcome: Send + 'static {
+    /// The failure message, if the run failed.
+    fn into_result(self) -> Result<(), String>;
+}
+
+impl Outcome for () {
+    fn into_result(self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl<E: std::fmt::Display + Send + 'static> Outcome for Result<(), E> {
+    fn into_result(self) -> Result<(), String> {
+        self.map_err(|error| error.to_string())
+    }
+}
@@ -51,15 +51,17 @@ pub use crate::async_trait;
 /// A `Result` whose error defaults to a boxed `std::error::Error`, for `main` and quick handlers.
 pub type Result<T, E = Box<dyn std::error::Error + Send + Sync>> = std::result::Result<T, E>;
 
+pub use crate::api;
+
 // Feature-specific exports
 #[cfg(feature = "openapi")]
-pub use crate::{api, Responsible, Schematic};
+pub use crate::{Responsible, Schematic};
 
 #[cfg(feature = "cors")]
 pub use crate::layers::CorsLayer;
 
 #[cfg(feature = "task")]
-pub use crate::TaskScheduler;
+pub use crate::{task, TaskHandle, TaskScheduler};
 
 // Utility macros for common patterns
 #[macro_export]
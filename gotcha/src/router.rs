@@ -27,6 +27,10 @@ macro_rules! implement_method {
     };
 }
 
+/// Applies the [`GotchaRouter::docs_layer`] middleware to the docs routes.
+#[cfg(feature = "openapi")]
+type DocsLayer = std::sync::Arc<dyn Fn(Router) -> Router + Send + Sync>;
+
 /// # GotchaRouter
 ///
 /// A router for Gotcha web applications.
@@ -41,6 +45,27 @@ pub struct GotchaRouter<State = ()> {
     /// `security`, `components`, etc.
     #[cfg(feature = "openapi")]
     pub(crate) openapi_transform: Option<Box<dyn FnOnce(oas::OpenAPIV3) -> oas::OpenAPIV3 + Send>>,
+    /// The OpenAPI version the spec is served as, set via [`GotchaRouter::openapi_version`].
+    #[cfg(feature = "openapi")]
+    pub(crate) openapi_version: crate::openapi::OpenApiVersion,
+    /// Whether types differing between requests and responses get a component for each, set via
+    /// [`GotchaRouter::split_schemas`].
+    #[cfg(feature = "openapi")]
+    pub(crate) split_schemas: bool,
+    /// Where and whether the spec and UIs are mounted, set via [`GotchaRouter::docs`].
+    #[cfg(feature = "openapi")]
+    pub(crate) docs: crate::openapi::DocsConfig,
+    /// Middleware wrapping only the docs routes, set via [`GotchaRouter::docs_layer`].
+    #[cfg(feature = "openapi")]
+    pub(crate) docs_layer: Option<DocsLayer>,
+    /// The spec's metadata, set via [`GotchaRouter::info`].
+    #[cfg(feature = "openapi")]
+    pub(crate) info: crate::openapi::ApiInfo,
+    /// The version this router is being built for, inside a [`GotchaRouter::versions`] closure.
+    pub(crate) version_scope: Option<crate::versioning::VersionScope>,
+    /// The per-version routers registered with [`GotchaRouter::versions`], including those of
+    /// nested and merged routers.
+    pub(crate) versions: Vec<crate::versioning::VersionedRoutes<State>>,
     pub(crate) router: Router<State>,
 }
 impl<State: Clone + Send + Sync + 'static> Default for GotchaRouter<State> {
@@ -50,6 +75,18 @@ impl<State: Clone + Send + Sync + 'static> Default for GotchaRouter<State> {
             operations: Default::default(),
             #[cfg(feature = "openapi")]
             openapi_transform: None,
+            #[cfg(feature = "openapi")]
+            openapi_version: Default::default(),
+            #[cfg(feature = "openapi")]
+            split_schemas: false,
+            #[cfg(feature = "openapi")]
+            docs: Default::default(),
+            #[cfg(feature = "openapi")]
+            docs_layer: None,
+            #[cfg(feature = "openapi")]
+            info: Default::default(),
+            version_scope: None,
+            versions: Vec::new(),
             router: Router::new(),
         }
     }
@@ -57,6 +94,9 @@ impl<State: Clone + Send + Sync + 'static> Default for GotchaRouter<State> {
 
 impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
     /// add a route to the router
+    ///
+    /// Inside [`versions`](Self::versions), the route is served as is in every version: a
+    /// `MethodRouter` hides its handlers, so their `#[api(since, until)]` bounds are not applied.
     /// # Examples
     ///
     /// ```rust,no_run
@@ -75,6 +115,18 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
             operations: self.operations,
             #[cfg(feature = "openapi")]
             openapi_transform: self.openapi_transform,
+            #[cfg(feature = "openapi")]
+            openapi_version: self.openapi_version,
+            #[cfg(feature = "openapi")]
+            split_schemas: self.split_schemas,
+            #[cfg(feature = "openapi")]
+            docs: self.docs,
+            #[cfg(feature = "openapi")]
+            docs_layer: self.docs_layer,
+            #[cfg(feature = "openapi")]
+            info: self.info,
+            version_scope: self.version_scope,
+            versions: self.versions,
             router: self.router.route(path, method_router),
         }
     }
@@ -100,6 +152,19 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
         H: Handler<T, State>,
         T: 'static,
     {
+        // Inside `versions(..)`, a handler outside its `since`/`until` range is left out of this
+        // version altogether, and one this version deprecates announces the version's sunset.
+        let mut sunset = None;
+        if let Some(scope) = &self.version_scope {
+            let bounds = crate::versioning::VersionBounds::of::<H>();
+            let (since, until) = bounds.map_or((None, None), |bounds| (bounds.since, bounds.until));
+            if !scope.includes(since, until) {
+                return self;
+            }
+            if scope.deprecates(until) {
+                sunset = scope.version.sunset.clone();
+            }
+        }
         #[cfg(feature = "openapi")]
         let handle_operable = extract_operable::<H, T, State>();
         #[cfg(feature = "openapi")]
@@ -128,12 +193,43 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
         }
 
         let router = MethodRouter::new().on(method, handler);
+        let sunset = match sunset {
+            Some(date) if crate::versioning::is_http_date(&date) => Some(axum::http::HeaderValue::from_str(&date).expect("an HTTP-date is a valid header value")),
+            // `ApiVersion::sunset` checks the date; this catches one written to the field directly.
+            Some(date) => {
+                tracing::warn!("the sunset {date:?} of {path} is not an HTTP-date; its responses go without a Sunset header");
+                None
+            }
+            None => None,
+        };
+        let router = match sunset {
+            Some(date) => router.layer(axum::middleware::map_response(move |mut response: axum::response::Response| {
+                let date = date.clone();
+                async move {
+                    response.headers_mut().insert("sunset", date);
+                    response
+                }
+            })),
+            None => router,
+        };
 
         Self {
             #[cfg(feature = "openapi")]
             operations: self.operations,
             #[cfg(feature = "openapi")]
             openapi_transform: self.openapi_transform,
+            #[cfg(feature = "openapi")]
+            openapi_version: self.openapi_version,
+            #[cfg(feature = "openapi")]
+            split_schemas: self.split_schemas,
+            #[cfg(feature = "openapi")]
+            docs: self.docs,
+            #[cfg(feature = "openapi")]
+            docs_layer: self.docs_layer,
+            #[cfg(feature = "openapi")]
+            info: self.info,
+            version_scope: self.version_scope,
+            versions: self.versions,
             router: self.router.route(path, router),
         }
     }
@@ -163,7 +259,7 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
             .into_iter()
             .map(|(key, value)| {
                 let (path_str, method) = key;
-                let new_path = format!("{}/{}", path, path_str);
+                let new_path = format!("{}/{}", path.trim_end_matches('/'), path_str.trim_start_matches('/'));
                 ((new_path, method), value)
             })
             .collect::<HashMap<(String, Method), &'static Operable>>();
@@ -172,6 +268,18 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
             operations: self.operations.into_iter().chain(operations).collect(),
             #[cfg(feature = "openapi")]
             openapi_transform: self.openapi_transform,
+            #[cfg(feature = "openapi")]
+            openapi_version: self.openapi_version,
+            #[cfg(feature = "openapi")]
+            split_schemas: self.split_schemas,
+            #[cfg(feature = "openapi")]
+            docs: self.docs,
+            #[cfg(feature = "openapi")]
+            docs_layer: self.docs_layer,
+            #[cfg(feature = "openapi")]
+            info: self.info,
+            version_scope: self.version_scope,
+            versions: self.versions.into_iter().chain(router.versions.into_iter().map(|versions| versions.nested(path))).collect(),
             router: self.router.nest(path, router.router),
         }
     }
@@ -191,6 +299,18 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
             operations: self.operations.into_iter().chain(other.operations).collect(),
             #[cfg(feature = "openapi")]
             openapi_transform: self.openapi_transform,
+            #[cfg(feature = "openapi")]
+            openapi_version: self.openapi_version,
+            #[cfg(feature = "openapi")]
+            split_schemas: self.split_schemas,
+            #[cfg(feature = "openapi")]
+            docs: self.docs,
+            #[cfg(feature = "openapi")]
+            docs_layer: self.docs_layer,
+            #[cfg(feature = "openapi")]
+            info: self.info,
+            version_scope: self.version_scope,
+            versions: self.versions.into_iter().chain(other.versions).collect(),
             router: self.router.merge(other.router),
         }
     }
@@ -217,6 +337,18 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
             operations: self.operations,
             #[cfg(feature = "openapi")]
             openapi_transform: self.openapi_transform,
+            #[cfg(feature = "openapi")]
+            openapi_version: self.openapi_version,
+            #[cfg(feature = "openapi")]
+            split_schemas: self.split_schemas,
+            #[cfg(feature = "openapi")]
+            docs: self.docs,
+            #[cfg(feature = "openapi")]
+            docs_layer: self.docs_layer,
+            #[cfg(feature = "openapi")]
+            info: self.info,
+            version_scope: self.version_scope,
+            versions: self.versions.into_iter().map(|versions| versions.layer(layer.clone())).collect(),
             router: self.router.layer(layer),
         }
     }
@@ -232,6 +364,18 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
             operations: self.operations,
             #[cfg(feature = "openapi")]
             openapi_transform: self.openapi_transform,
+            #[cfg(feature = "openapi")]
+            openapi_version: self.openapi_version,
+            #[cfg(feature = "openapi")]
+            split_schemas: self.split_schemas,
+            #[cfg(feature = "openapi")]
+            docs: self.docs,
+            #[cfg(feature = "openapi")]
+            docs_layer: self.docs_layer,
+            #[cfg(feature = "openapi")]
+            info: self.info,
+            version_scope: self.version_scope,
+            versions: self.versions,
             router: self.router.fallback(handler),
         }
     }
@@ -263,6 +407,18 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
             operations: self.operations,
             #[cfg(feature = "openapi")]
             openapi_transform: self.openapi_transform,
+            #[cfg(feature = "openapi")]
+            openapi_version: self.openapi_version,
+            #[cfg(feature = "openapi")]
+            split_schemas: self.split_schemas,
+            #[cfg(feature = "openapi")]
+            docs: self.docs,
+            #[cfg(feature = "openapi")]
+            docs_layer: self.docs_layer,
+            #[cfg(feature = "openapi")]
+            info: self.info,
+            version_scope: self.version_scope,
+            versions: self.versions,
             router: self.router.fallback_service(service),
         }
     }
@@ -291,30 +447,320 @@ impl<State: Clone + Send + Sync + 'static> GotchaRouter<State> {
         self
     }
 
-    /// Finalize this router into a plain `axum::Router`, injecting `state`.
+    /// Serve the spec as the given OpenAPI version. Defaults to 3.0.
     ///
-    /// When the `openapi` feature is enabled, this also mounts the generated
-    /// spec at `/openapi.json` and the Redoc / Scalar UIs at `/redoc` and
-    /// `/scalar`. This is the single assembly path shared by both the
-    /// [`GotchaApp`](crate::GotchaApp) trait and the [`Gotcha`](crate::Gotcha)
-    /// builder.
-    pub(crate) fn into_axum_router(self, state: State) -> Router {
-        cfg_if::cfg_if! {
-            if #[cfg(feature = "openapi")] {
-                let mut openapi_spec = crate::openapi::generate_openapi(self.operations);
+    /// The spec is assembled as 3.0 either way — the [`openapi`](Self::openapi) transform still
+    /// receives an [`oas::OpenAPIV3`] — and converted afterwards, so 3.1 output also rewrites
+    /// what the transform added.
+    ///
+    /// ```rust,no_run
+    /// use gotcha::openapi::OpenApiVersion;
+    /// use gotcha::GotchaRouter;
+    ///
+    /// let router: GotchaRouter<()> = GotchaRouter::default().openapi_version(OpenApiVersion::V3_1);
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn openapi_version(mut self, version: crate::openapi::OpenApiVersion) -> Self {
+        self.openapi_version = version;
+        self
+    }
 
-                if let Some(transform) = self.openapi_transform {
-                    openapi_spec = transform(openapi_spec);
+    /// Document a type used in both requests and responses as two components when its
+    /// `#[schematic(read_only)]` / `#[schematic(write_only)]` fields make them differ:
+    /// `UserRequest` without the read-only fields and `UserResponse` without the write-only ones.
+    /// Types holding such a type are split too; every other type keeps one component.
+    ///
+    /// Off by default, which documents one component with the fields flagged `readOnly` and
+    /// `writeOnly` and leaves clients to interpret them.
+    ///
+    /// ```rust,no_run
+    /// use gotcha::GotchaRouter;
+    ///
+    /// let router: GotchaRouter<()> = GotchaRouter::default().split_schemas(true);
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn split_schemas(mut self, split: bool) -> Self {
+        self.split_schemas = split;
+        self
+    }
+
+    /// Mount routes under `path` that list the scheduled tasks, show their run history and
+    /// trigger them, documented under the `tasks` tag. See [`crate::task::admin`].
+    ///
+    /// ```rust,no_run
+    /// use gotcha::GotchaRouter;
+    ///
+    /// let router: GotchaRouter<()> = GotchaRouter::default().task_admin("/admin/tasks");
+    /// ```
+    #[cfg(feature = "task")]
+    pub fn task_admin(self, path: &str) -> Self {
+        self.merge(crate::task::admin::routes(path))
+    }
+
+    /// Assemble the OpenAPI document for this router without serving anything.
+    ///
+    /// This is exactly what `/openapi.json` serves for this router: every documented route, the
+    /// [`openapi`](Self::openapi) transform, and the selected [`openapi_version`](Self::openapi_version).
+    /// A router knows no configuration; `GotchaApp::openapi_spec` and the builder's `openapi_spec`
+    /// apply its `[server.openapi]` section first, as serving does.
+    /// Pair it with [`openapi::write_spec`](crate::openapi::write_spec) to keep a committed copy
+    /// of the spec in sync from a test.
+    ///
+    /// ```rust,no_run
+    /// use gotcha::GotchaRouter;
+    ///
+    /// let router: GotchaRouter<()> = GotchaRouter::default();
+    /// let spec = router.openapi_spec();
+    /// assert_eq!(spec["openapi"], "3.0.0");
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn openapi_spec(self) -> serde_json::Value {
+        self.split_openapi().1
+    }
+
+    /// Separate the axum router from its finished OpenAPI document.
+    #[cfg(feature = "openapi")]
+    fn split_openapi(self) -> (Router<State>, serde_json::Value) {
+        let deprecated: Vec<(String, Method)> = match &self.version_scope {
+            Some(scope) => self
+                .operations
+                .iter()
+                .filter(|(_, operable)| scope.deprecates(crate::versioning::VersionBounds::named(operable.type_name).and_then(|bounds| bounds.until)))
+                .map(|(key, _)| key.clone())
+                .collect(),
+            None => Vec::new(),
+        };
+        let package = crate::openapi::ApiInfo::from_operations(self.operations.values().copied());
+        let mut openapi_spec = crate::openapi::assemble(self.operations, self.split_schemas);
+        if let Some(scope) = &self.version_scope {
+            scope.annotate(&mut openapi_spec, &deprecated);
+        }
+        if package.title.is_some() {
+            // The fallback description is gotcha's own, not the application's.
+            openapi_spec.info.description = None;
+        }
+        package.merge(self.info).apply(&mut openapi_spec);
+
+        if let Some(transform) = self.openapi_transform {
+            openapi_spec = transform(openapi_spec);
+        }
+        (self.router, crate::openapi::version::render(&openapi_spec, self.openapi_version))
+    }
+
+    /// Set the spec's title, version, description, contact, license, servers, tags and external
+    /// docs. Calls accumulate, each overriding the fields it sets; a `[server.openapi]`
+    /// configuration section is layered on top when the application runs.
+    ///
+    /// Without it, the spec describes the crate the routed `#[api]` handlers are in.
+    /// [`api_info!`](crate::api_info) names the calling crate's `Cargo.toml` metadata instead:
+    ///
+    /// ```rust,no_run
+    /// use gotcha::GotchaRouter;
+    ///
+    /// let router: GotchaRouter<()> = GotchaRouter::default().info(
+    ///     gotcha::api_info!()
+    ///         .server("https://api.example.com", None)
+    ///         .tag("pets", "Everything about your pets"),
+    /// );
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn info(mut self, info: crate::openapi::ApiInfo) -> Self {
+        self.info = self.info.merge(info);
+        self
+    }
+
+    /// Choose where the spec and documentation UIs are mounted, which UIs there are, or turn
+    /// them off. Defaults to the spec at `/openapi.json` and `/openapi.yaml` plus Redoc and Scalar.
+    ///
+    /// A `[server.docs]` section in the configuration replaces this when the application runs,
+    /// so the docs can differ per environment.
+    ///
+    /// ```rust,no_run
+    /// use gotcha::openapi::{DocsConfig, DocsUi};
+    /// use gotcha::GotchaRouter;
+    ///
+    /// let router: GotchaRouter<()> = GotchaRouter::default().docs(DocsConfig {
+    ///     path: "/docs".to_string(),
+    ///     ui: vec![DocsUi::SwaggerUi],
+    ///     ..DocsConfig::default()
+    /// });
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn docs(mut self, docs: crate::openapi::DocsConfig) -> Self {
+        self.docs = docs;
+        self
+    }
+
+    /// Wrap only the spec and documentation routes in `layer` — typically authentication, so the
+    /// docs can stay reachable internally without being public.
+    ///
+    /// Unlike [`layer`](Self::layer), this leaves the application's own routes untouched; and
+    /// layers added with [`layer`](Self::layer) do not reach the docs routes, which are mounted
+    /// afterwards.
+    ///
+    /// ```rust,no_run
+    /// use gotcha::GotchaRouter;
+    /// use gotcha::axum::{extract::Request, http::StatusCode, middleware::{self, Next}};
+    ///
+    /// async fn require_token(request: Request, next: Next) -> Result<gotcha::axum::response::Response, StatusCode> {
+    ///     match request.headers().get("x-docs-token") {
+    ///         Some(token) if token == "secret" => Ok(next.run(request).await),
+    ///         _ => Err(StatusCode::UNAUTHORIZED),
+    ///     }
+    /// }
+    ///
+    /// let router: GotchaRouter<()> = GotchaRouter::default().docs_layer(middleware::from_fn(require_token));
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn docs_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: Responder + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.docs_layer = Some(std::sync::Arc::new(move |router: Router| router.layer(layer.clone())));
+        self
+    }
+
+    /// Apply a configuration's `[server.docs]` and `[server.openapi]` sections, where present: the
+    /// first replaces the [`docs`](Self::docs) settings, the second is layered over the
+    /// [`info`](Self::info).
+    #[cfg(feature = "openapi")]
+    pub(crate) fn configured(mut self, server: &crate::config::ServerConfig) -> Self {
+        if let Some(docs) = &server.docs {
+            self = self.docs(docs.clone());
+        }
+        if let Some(info) = &server.openapi {
+            self = self.info(info.clone());
+        }
+        self
+    }
+
+    /// Serve `routes` once per version in `versioning`, each version with its own spec.
+    ///
+    /// `routes` runs once per declared version, oldest first, on a fresh router. With the `openapi`
+    /// feature, handlers whose `#[api(since, until)]` exclude the version are skipped and handlers
+    /// it deprecates are marked so; see [`versioning`](crate::versioning). Each version's spec
+    /// and docs are mounted under its prefix (`/v1/openapi.json`) and start from this router's
+    /// [`info`](Self::info) and [`docs`](Self::docs) settings, with the version as `info.version`.
+    /// The [`openapi`](Self::openapi) transform only applies to this router's own spec; set one
+    /// inside `routes` to customize a version's.
+    ///
+    /// With the header and media-type strategies, requests that match none of this router's own
+    /// routes are handed to the versioned routers, which take the place of any
+    /// [`fallback`](Self::fallback).
+    ///
+    /// Only routes added with [`get`](Self::get), [`post`](Self::post) and the other method
+    /// helpers are bounded, deprecated and sent with a `Sunset` header; one added with
+    /// [`route`](Self::route) is served as is in every version.
+    ///
+    /// ```rust,no_run
+    /// use gotcha::versioning::Versioning;
+    /// use gotcha::GotchaRouter;
+    ///
+    /// let router: GotchaRouter<()> = GotchaRouter::default().versions(Versioning::header("x-api-version").version("1").version("2"), |router| {
+    ///     router.get("/users", || async { "[]" })
+    /// });
+    /// ```
+    pub fn versions<F>(mut self, versioning: crate::versioning::Versioning, routes: F) -> Self
+    where
+        F: Fn(GotchaRouter<State>) -> GotchaRouter<State>,
+    {
+        self.versions.push(crate::versioning::VersionedRoutes::build(versioning, routes));
+        self
+    }
+
+    /// Take the spec and docs settings of `parent` for this router, the routes of `version`,
+    /// nested at `path`.
+    #[allow(unused_variables, unused_mut)]
+    pub(crate) fn inherit(mut self, parent: &Self, version: &crate::versioning::ApiVersion, strategy: &crate::versioning::VersionStrategy, path: &str) -> Self {
+        #[cfg(feature = "openapi")]
+        {
+            let mut info = parent.info.clone().merge(crate::openapi::ApiInfo {
+                version: Some(version.name.clone()),
+                ..Default::default()
+            });
+            // The spec lists `/users`, and its server carries where that lives: the nesting path,
+            // and with path prefixes the `/v1`.
+            let base = match strategy {
+                crate::versioning::VersionStrategy::PathPrefix => format!("{path}{}", version.prefix()),
+                _ => path.to_string(),
+            };
+            if !base.is_empty() {
+                if info.servers.is_empty() {
+                    info = info.server("", None);
                 }
-                self.router
-                    .with_state(state)
-                    .route("/openapi.json", axum::routing::get(move || async move { axum::Json(openapi_spec.clone()) }))
-                    .route("/redoc", axum::routing::get(crate::openapi::openapi_html))
-                    .route("/scalar", axum::routing::get(crate::openapi::scalar_html))
+                for server in &mut info.servers {
+                    server.url = format!("{}{base}", server.url.trim_end_matches('/'));
+                }
+            }
+            self.info = info.merge(self.info);
+            self.docs = crate::openapi::DocsConfig {
+                path: format!("{path}{}{}", version.prefix(), parent.docs.base_path()),
+                ..parent.docs.clone()
+            };
+            self.docs_layer = self.docs_layer.or_else(|| parent.docs_layer.clone());
+            self.openapi_version = parent.openapi_version;
+            self.split_schemas = parent.split_schemas;
+        }
+        self
+    }
+
+    /// Finalize into the application's routes and, separately, the routes serving its spec and
+    /// docs — which layers on the application do not wrap.
+    #[allow(unused_mut)]
+    pub(crate) fn assemble(mut self, state: State) -> (Router, Router) {
+        let versions = std::mem::take(&mut self.versions);
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "openapi")] {
+                let parent = Self {
+                    operations: Default::default(),
+                    openapi_transform: None,
+                    openapi_version: self.openapi_version,
+                    split_schemas: self.split_schemas,
+                    docs: self.docs.clone(),
+                    docs_layer: self.docs_layer.clone(),
+                    info: self.info.clone(),
+                    version_scope: None,
+                    versions: Vec::new(),
+                    router: Router::new(),
+                };
+                let (app, docs) = if self.docs.enabled {
+                    let docs_layer = self.docs_layer.take();
+                    let docs = self.docs.clone();
+                    let (router, openapi_spec) = self.split_openapi();
+                    let mut docs_router = docs.router(openapi_spec);
+                    if let Some(layer) = docs_layer {
+                        docs_router = layer(docs_router);
+                    }
+                    (router.with_state(state.clone()), docs_router)
+                } else {
+                    (self.router.with_state(state.clone()), Router::new())
+                };
             } else {
-                self.router.with_state(state)
+                let parent = Self::default();
+                let (app, docs) = (self.router.with_state(state.clone()), Router::new());
             }
         }
+        if versions.is_empty() {
+            (app, docs)
+        } else {
+            crate::versioning::VersionedRoutes::mount(versions, app, docs, &parent, state)
+        }
+    }
+
+    /// Finalize this router into a plain `axum::Router`, injecting `state`.
+    ///
+    /// When the `openapi` feature is enabled, this also mounts the generated
+    /// spec and the documentation UIs as set by [`docs`](Self::docs) — by default
+    /// `/openapi.json`, `/openapi.yaml`, `/redoc` and `/scalar`. This is the single
+    /// assembly path shared by both the [`GotchaApp`](crate::GotchaApp) trait and
+    /// the [`Gotcha`](crate::Gotcha) builder.
+    pub(crate) fn into_axum_router(self, state: State) -> Router {
+        let (app, docs) = self.assemble(state);
+        app.merge(docs)
     }
 }
 
@@ -371,4 +817,123 @@ mod tests {
 
         assert!(*ran.lock().unwrap(), "transform set before route()/fallback() must still apply");
     }
+
+    #[tokio::test]
+    async fn spec_is_served_as_yaml_too() {
+        use tower::ServiceExt;
+
+        let router: GotchaRouter<()> = GotchaRouter::default().openapi(|mut spec| {
+            spec.info.title = "Yaml API".to_string();
+            spec
+        });
+        let response = router
+            .into_axum_router(())
+            .oneshot(Request::builder().uri("/openapi.yaml").body(axum::body::Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        assert_eq!(response.headers()[axum::http::header::CONTENT_TYPE], "application/yaml");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let spec: serde_json::Value = serde_yaml::from_slice(&body).unwrap();
+        assert_eq!(spec["info"]["title"], "Yaml API");
+    }
+
+    #[test]
+    fn openapi_spec_matches_what_is_served() {
+        let spec = GotchaRouter::<()>::default()
+            .openapi_version(crate::openapi::OpenApiVersion::V3_1)
+            .openapi_spec();
+        assert_eq!(spec["openapi"], "3.1.0");
+    }
+
+    async fn status_of(router: Router, uri: &str) -> axum::http::StatusCode {
+        use tower::ServiceExt;
+
+        router
+            .oneshot(Request::builder().uri(uri).body(axum::body::Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn docs_move_under_the_configured_path() {
+        let router = GotchaRouter::<()>::default()
+            .docs(crate::openapi::DocsConfig {
+                path: "/docs".to_string(),
+                ui: vec![crate::openapi::DocsUi::SwaggerUi],
+                ..Default::default()
+            })
+            .into_axum_router(());
+
+        assert_eq!(status_of(router.clone(), "/docs/openapi.json").await, axum::http::StatusCode::OK);
+        assert_eq!(status_of(router.clone(), "/docs/swagger-ui").await, axum::http::StatusCode::OK);
+        assert_eq!(status_of(router.clone(), "/docs/swagger-ui/swagger-ui-bundle.js").await, axum::http::StatusCode::OK);
+        assert_eq!(status_of(router.clone(), "/docs/redoc").await, axum::http::StatusCode::NOT_FOUND);
+        assert_eq!(status_of(router, "/openapi.json").await, axum::http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn disabled_docs_mount_nothing() {
+        let router = GotchaRouter::<()>::default()
+            .route("/health", axum::routing::get(|| async { "ok" }))
+            .docs(crate::openapi::DocsConfig::disabled())
+            .into_axum_router(());
+
+        assert_eq!(status_of(router.clone(), "/health").await, axum::http::StatusCode::OK);
+        for uri in ["/openapi.json", "/openapi.yaml", "/redoc", "/scalar"] {
+            assert_eq!(status_of(router.clone(), uri).await, axum::http::StatusCode::NOT_FOUND, "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn docs_layer_guards_only_the_docs() {
+        let deny = axum::middleware::from_fn(|_: Request, _: axum::middleware::Next| async { axum::http::StatusCode::UNAUTHORIZED });
+        let router = GotchaRouter::<()>::default()
+            .route("/health", axum::routing::get(|| async { "ok" }))
+            .docs_layer(deny)
+            .into_axum_router(());
+
+        assert_eq!(status_of(router.clone(), "/health").await, axum::http::StatusCode::OK);
+        assert_eq!(status_of(router.clone(), "/openapi.json").await, axum::http::StatusCode::UNAUTHORIZED);
+        assert_eq!(status_of(router, "/redoc").await, axum::http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn config_section_replaces_the_router_setting() {
+        let server: crate::config::ServerConfig = serde_json::from_value(serde_json::json!({
+            "host": "127.0.0.1",
+            "port": 3000,
+            "docs": {"enabled": false}
+        }))
+        .unwrap();
+        let router = GotchaRouter::<()>::default().docs(crate::openapi::DocsConfig::default()).configured(&server);
+        assert!(!router.docs.enabled);
+
+        let without_section = crate::config::ServerConfig::default();
+        let router = GotchaRouter::<()>::default()
+            .docs(crate::openapi::DocsConfig::disabled())
+            .configured(&without_section);
+        assert!(!router.docs.enabled, "no section leaves the code's choice alone");
+    }
+
+    #[test]
+    fn spec_info_comes_from_code_then_config() {
+        let server: crate::config::ServerConfig = serde_json::from_value(serde_json::json!({
+            "host": "127.0.0.1",
+            "port": 3000,
+            "openapi": {"title": "Pet Store", "servers": [{"url": "https://api.example.com"}]}
+        }))
+        .unwrap();
+        let spec = GotchaRouter::<()>::default()
+            .info(crate::openapi::ApiInfo::new("pets", "0.3.0").tag("pets", "Everything about your pets"))
+            .configured(&server)
+            .openapi_spec();
+
+        assert_eq!(spec["info"]["title"], "Pet Store");
+        assert_eq!(spec["info"]["version"], "0.3.0");
+        assert_eq!(spec["servers"][0]["url"], "https://api.example.com");
+        assert_eq!(spec["tags"][0]["description"], "Everything about your pets");
+    }
 }
@@ -0,0 +1,102 @@
+//! Opt-in routes to list scheduled tasks, read their history and trigger them, mounted with
+//! [`GotchaRouter::task_admin`](crate::GotchaRouter::task_admin).
+//!
+//! The handlers read the [`TaskRegistry`] from a request extension, which `GotchaApp::run` and
+//! the builder install once tasks are registered. A router served some other way needs
+//! `.layer(Extension(scheduler.registry()))`.
+//!
+//! | Method | Path | |
+//! |---|---|---|
+//! | `GET` | `{path}` | every task, with its next and last run |
+//! | `GET` | `{path}/{name}/history` | the task's attempts, most recent first |
+//! | `POST` | `{path}/{name}/trigger` | run the task now |
+//!
+//! These routes pause nothing and cancel nothing, but they do let a caller run jobs on demand —
+//! put them behind authentication.
+
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{TaskHandle, TaskRegistry, TaskRun};
+use crate::GotchaRouter;
+#[cfg(feature = "openapi")]
+use crate::{api, Schematic};
+
+/// A scheduled task's state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(Schematic))]
+pub struct TaskSummary {
+    /// The name the task was registered under.
+    pub name: String,
+    /// Whether the task is paused.
+    pub paused: bool,
+    /// Whether the task has been cancelled.
+    pub cancelled: bool,
+    /// When the task fires next.
+    pub next_run: Option<DateTime<Utc>>,
+    /// The task's most recent attempt.
+    pub last_run: Option<TaskRun>,
+}
+
+impl From<&TaskHandle> for TaskSummary {
+    fn from(handle: &TaskHandle) -> Self {
+        Self {
+            name: handle.name().to_string(),
+            paused: handle.is_paused(),
+            cancelled: handle.is_cancelled(),
+            next_run: handle.next_run(),
+            last_run: handle.last_run(),
+        }
+    }
+}
+
+/// No task is registered under the requested name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(Schematic))]
+pub struct TaskNotFound {
+    /// The requested name.
+    pub task: String,
+}
+
+impl IntoResponse for TaskNotFound {
+    fn into_response(self) -> Response {
+        (StatusCode::NOT_FOUND, Json(self)).into_response()
+    }
+}
+
+fn find(registry: &TaskRegistry, name: String) -> Result<TaskHandle, TaskNotFound> {
+    registry.get(&name).ok_or(TaskNotFound { task: name })
+}
+
+/// Every scheduled task, with its next and last run.
+#[cfg_attr(feature = "openapi", api(id = "list_tasks", group = "tasks"))]
+pub async fn list_tasks(Extension(registry): Extension<TaskRegistry>) -> Json<Vec<TaskSummary>> {
+    Json(registry.tasks().iter().map(TaskSummary::from).collect())
+}
+
+/// A task's recorded attempts, most recent first.
+#[cfg_attr(feature = "openapi", api(id = "task_history", group = "tasks"))]
+pub async fn task_history(Extension(registry): Extension<TaskRegistry>, Path(name): Path<String>) -> Result<Json<Vec<TaskRun>>, TaskNotFound> {
+    Ok(Json(find(&registry, name)?.history()))
+}
+
+/// Run a task now, outside its schedule.
+#[cfg_attr(feature = "openapi", api(id = "trigger_task", group = "tasks"))]
+pub async fn trigger_task(Extension(registry): Extension<TaskRegistry>, Path(name): Path<String>) -> Result<Json<TaskSummary>, TaskNotFound> {
+    let handle = find(&registry, name)?;
+    handle.trigger();
+    Ok(Json(TaskSummary::from(&handle)))
+}
+
+/// The admin routes, under `path`.
+pub(crate) fn routes<State: Clone + Send + Sync + 'static>(path: &str) -> GotchaRouter<State> {
+    let base = path.trim_end_matches('/');
+    GotchaRouter::default()
+        .get(if base.is_empty() { "/" } else { base }, list_tasks)
+        .get(&format!("{base}/{{name}}/history"), task_history)
+        .post(&format!("{base}/{{name}}/trigger"), trigger_task)
+}
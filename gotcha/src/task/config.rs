@@ -0,0 +1,75 @@
+//! The `[tasks.<name>]` sections of the configuration file, which retune a registered task without
+//! a rebuild:
+//!
+//! ```toml
+//! [tasks.cleanup]
+//! enabled = false
+//!
+//! [tasks.report]
+//! cron = "0 0 2 * * *"
+//! timezone = "Europe/Berlin"
+//!
+//! [tasks.heartbeat]
+//! interval = "30s"
+//! ```
+//!
+//! A section only applies to a task registered under the same name; sections naming no task are
+//! ignored.
+
+use std::time::Duration;
+
+use chrono_tz::Tz;
+use gotcha_core::duration::parse_duration;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// One `[tasks.<name>]` section. Anything left out keeps what the code registered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskConfig {
+    /// Whether the task fires on schedule. A disabled task is still registered, paused: it is
+    /// listed by the admin routes and can be triggered or resumed.
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Replaces the task's schedule with this cron expression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>,
+    /// Replaces the task's schedule with this interval, such as `"30s"` or `"5m"`.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "duration")]
+    pub interval: Option<Duration>,
+    /// The time zone the task's cron expression is evaluated in, such as `"Europe/Berlin"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Default for TaskConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cron: None,
+            interval: None,
+            timezone: None,
+        }
+    }
+}
+
+/// Serializes an optional [`Duration`] the way [`parse_duration`] reads it.
+mod duration {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) if duration.subsec_millis() == 0 => serializer.serialize_str(&format!("{}s", duration.as_secs())),
+            Some(duration) => serializer.serialize_str(&format!("{}ms", duration.as_millis())),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|input| parse_duration(&input).map_err(|error| serde::de::Error::custom(format!("invalid duration {input:?}: {error}"))))
+            .transpose()
+    }
+}
@@ -0,0 +1,167 @@
+//! Tasks declared with the `#[task]` attribute, collected via `inventory` and registered by
+//! `GotchaApp::run` and the builder when the server starts.
+//!
+//! ```rust,ignore
+//! use gotcha::prelude::*;
+//! use gotcha::task;
+//!
+//! #[task(cron = "0 0 2 * * *", timezone = "Europe/Berlin")]
+//! async fn nightly_report(State(state): State<AppState>, State(config): State<Config>) -> Result<(), ReportError> {
+//!     state.reports.send(&config.recipients).await
+//! }
+//!
+//! #[task(interval = "30s")]
+//! async fn heartbeat(messager: Messager<AppState, Config>) {
+//!     messager.spawn(Heartbeat);
+//! }
+//! ```
+//!
+//! A task's arguments are extracted from the application context on every run, much like a
+//! handler's: anything implementing [`FromTaskContext`]. A task whose arguments the application
+//! cannot provide stops the application from starting.
+
+use std::any::{Any, TypeId};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use axum::extract::State;
+
+use super::{Every, TaskError, TaskHandle, TaskOptions, TaskScheduler, Tz};
+use crate::{ConfigWrapper, GotchaConfig, GotchaContext, Messager};
+
+/// A run of a declared task: `Err` carries the failure message.
+pub type TaskFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+/// Everything the `#[task]` macro records about one task function, collected via `inventory`.
+pub struct TaskDeclaration {
+    /// The name the task registers under.
+    pub name: &'static str,
+    /// Its cron expression, for a cron task.
+    pub cron: Option<&'static str>,
+    /// Its interval, for an interval task.
+    pub interval: Option<Duration>,
+    /// The time zone its cron expression is evaluated in.
+    pub timezone: Option<&'static str>,
+    /// Whether its arguments can be extracted from a context; `Err` names the first that cannot.
+    pub check: fn(&TaskContext) -> Result<(), &'static str>,
+    /// Extract the arguments and run the task.
+    pub run: fn(TaskContext) -> TaskFuture,
+}
+
+inventory::collect!(TaskDeclaration);
+
+/// The application context a declared task's arguments are extracted from. Its state and
+/// configuration types are erased, so a declaration need not name them.
+pub struct TaskContext {
+    context: Box<dyn ErasedContext>,
+}
+
+impl TaskContext {
+    /// Wrap an application context.
+    pub fn new<S: Clone + Send + Sync + 'static, C: GotchaConfig>(context: GotchaContext<S, C>) -> Self {
+        Self { context: Box::new(context) }
+    }
+
+    /// A `T` from the context, if it has one: the application state or config, the
+    /// [`ConfigWrapper`], the [`GotchaContext`] itself, or a [`Messager`] for it.
+    pub fn get<T: 'static>(&self) -> Option<T> {
+        self.context.extract(TypeId::of::<T>())?.downcast().ok().map(|value| *value)
+    }
+}
+
+trait ErasedContext: Send + Sync {
+    fn extract(&self, wanted: TypeId) -> Option<Box<dyn Any>>;
+}
+
+impl<S: Clone + Send + Sync + 'static, C: GotchaConfig> ErasedContext for GotchaContext<S, C> {
+    fn extract(&self, wanted: TypeId) -> Option<Box<dyn Any>> {
+        let value: Box<dyn Any> = if wanted == TypeId::of::<S>() {
+            Box::new(self.state.clone())
+        } else if wanted == TypeId::of::<C>() {
+            Box::new(self.config.app.clone())
+        } else if wanted == TypeId::of::<ConfigWrapper<C>>() {
+            Box::new(self.config.clone())
+        } else if wanted == TypeId::of::<Self>() {
+            Box::new(self.clone())
+        } else if wanted == TypeId::of::<Messager<S, C>>() {
+            Box::new(Messager::new(self.clone()))
+        } else {
+            return None;
+        };
+        Some(value)
+    }
+}
+
+/// An argument of a `#[task]` function.
+pub trait FromTaskContext: Sized {
+    /// Extract the argument, or `None` if the context has no such value.
+    fn from_task_context(context: &TaskContext) -> Option<Self>;
+}
+
+/// The application state or config, as `State<AppState>` or `State<Config>`, or anything else
+/// [`TaskContext::get`] provides.
+impl<T: 'static> FromTaskContext for State<T> {
+    fn from_task_context(context: &TaskContext) -> Option<Self> {
+        context.get().map(State)
+    }
+}
+
+impl<S: Clone + Send + Sync + 'static, C: GotchaConfig> FromTaskContext for GotchaContext<S, C> {
+    fn from_task_context(context: &TaskContext) -> Option<Self> {
+        context.get()
+    }
+}
+
+impl<C: GotchaConfig> FromTaskContext for ConfigWrapper<C> {
+    fn from_task_context(context: &TaskContext) -> Option<Self> {
+        context.get()
+    }
+}
+
+impl<S: Clone + Send + Sync + 'static, C: GotchaConfig> FromTaskContext for Messager<S, C> {
+    fn from_task_context(context: &TaskContext) -> Option<Self> {
+        context.get()
+    }
+}
+
+impl<T1, T2> TaskScheduler<T1, T2>
+where
+    T1: Clone + Send + Sync + 'static,
+    T2: GotchaConfig,
+{
+    /// Register every task declared with `#[task]`, in name order. `GotchaApp::run` and the
+    /// builder call this when the server starts.
+    ///
+    /// A task whose arguments this scheduler's context cannot provide is an error, as is an
+    /// unknown time zone.
+    pub fn register_declared(&self) -> Result<Vec<TaskHandle>, TaskError> {
+        let mut declarations: Vec<_> = inventory::iter::<TaskDeclaration>.into_iter().collect();
+        declarations.sort_by_key(|declaration| declaration.name);
+        declarations.into_iter().map(|declaration| self.declare(declaration)).collect()
+    }
+
+    fn declare(&self, declaration: &'static TaskDeclaration) -> Result<TaskHandle, TaskError> {
+        let name = declaration.name.to_string();
+        (declaration.check)(&TaskContext::new(self.context.clone())).map_err(|argument| TaskError::Argument { name: name.clone(), argument })?;
+        let timezone = match declaration.timezone {
+            Some(timezone) => Some(timezone.parse::<Tz>().map_err(|_| TaskError::InvalidTimezone {
+                name: name.clone(),
+                timezone: timezone.to_string(),
+            })?),
+            None => None,
+        };
+        let every = match (declaration.cron, declaration.interval) {
+            (Some(expression), _) => Every::Cron(expression.to_string()),
+            (None, Some(interval)) => Every::Interval(interval),
+            // `#[task]` requires one; a declaration written by hand without either is rejected.
+            (None, None) => Every::Interval(Duration::ZERO),
+        };
+        let options = TaskOptions {
+            timezone,
+            ..TaskOptions::default()
+        };
+        let run = declaration.run;
+        self.spawn(name, every, options, move |context| run(TaskContext::new(context)))
+    }
+}
@@ -0,0 +1,119 @@
+//! A record of every attempt a task makes, kept behind the [`TaskHistory`] trait.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "openapi")]
+use crate::Schematic;
+
+/// How an attempt ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(Schematic))]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    /// The task finished, returning `()` or `Ok`.
+    Succeeded,
+    /// The task returned an `Err`.
+    Failed,
+    /// The task panicked.
+    Panicked,
+    /// The task ran past its timeout and was aborted.
+    TimedOut,
+}
+
+/// One attempt of a task. A retried run records one entry per attempt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(Schematic))]
+pub struct TaskRun {
+    /// The task's name.
+    pub task: String,
+    /// When the attempt started.
+    pub started_at: DateTime<Utc>,
+    /// How long it took, in milliseconds.
+    pub duration_ms: u64,
+    /// `1` for the first attempt of a run, counting up with each retry.
+    pub attempt: u32,
+    /// How it ended.
+    pub outcome: RunOutcome,
+    /// The error or panic message, unless it succeeded.
+    pub error: Option<String>,
+}
+
+/// Where the scheduler records task runs. Implement it to keep history somewhere other than
+/// memory, and install it with [`TaskScheduler::set_history`](super::TaskScheduler::set_history).
+pub trait TaskHistory: Send + Sync + 'static {
+    /// Store one attempt.
+    fn record(&self, run: TaskRun);
+    /// The attempts recorded for `task`, most recent first.
+    fn runs(&self, task: &str) -> Vec<TaskRun>;
+}
+
+/// The default [`TaskHistory`]: the most recent attempts of each task, in memory.
+pub struct MemoryHistory {
+    capacity: usize,
+    runs: Mutex<HashMap<String, VecDeque<TaskRun>>>,
+}
+
+impl MemoryHistory {
+    /// Keep the last `capacity` attempts of each task.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            runs: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Default for MemoryHistory {
+    /// Keeps the last 100 attempts of each task.
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+impl TaskHistory for MemoryHistory {
+    fn record(&self, run: TaskRun) {
+        let mut runs = self.runs.lock().expect("task history poisoned");
+        let task = runs.entry(run.task.clone()).or_default();
+        task.push_front(run);
+        task.truncate(self.capacity);
+    }
+
+    fn runs(&self, task: &str) -> Vec<TaskRun> {
+        let runs = self.runs.lock().expect("task history poisoned");
+        runs.get(task).map(|runs| runs.iter().cloned().collect()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(task: &str, attempt: u32) -> TaskRun {
+        TaskRun {
+            task: task.to_string(),
+            started_at: Utc::now(),
+            duration_ms: 0,
+            attempt,
+            outcome: RunOutcome::Succeeded,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn keeps_the_most_recent_runs_per_task() {
+        let history = MemoryHistory::new(2);
+        for attempt in 1..=3 {
+            history.record(run("a", attempt));
+        }
+        history.record(run("b", 1));
+
+        let attempts: Vec<_> = history.runs("a").iter().map(|run| run.attempt).collect();
+        assert_eq!(attempts, [3, 2]);
+        assert_eq!(history.runs("b").len(), 1);
+        assert!(history.runs("c").is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! Run-once-per-trigger across replicas: the scheduler asks a [`TaskLock`] before every scheduled
+//! run, and only the replica that claims the trigger runs it.
+//!
+//! Replicas sharing a lock backend agree on what a trigger is by its due time. A cron trigger is
+//! the time its expression names, which every replica computes alike. While a lock is installed,
+//! interval tasks run on the wall clock's interval boundaries, counted from the Unix epoch, rather
+//! than one interval after the replica started: a 60-second task runs at the start of every clock
+//! minute, and is claimed by that minute.
+//!
+//! Every replica therefore claims the same due times, however far apart their clocks are; the
+//! clocks only decide when each replica asks. A replica whose clock is behind asks for a trigger
+//! after the others did, and skips it. With [`MemoryLock`] and [`FileLock`], which keep the latest
+//! trigger claimed, it also skips any trigger older than one already claimed, so a clock set back
+//! skips runs until it catches up rather than repeating them. Runs started with
+//! [`trigger`](super::TaskHandle::trigger) are not claimed; they run where they were asked for.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// Decides which replica runs a trigger. Install one with
+/// [`TaskScheduler::set_lock`](super::TaskScheduler::set_lock) on every replica, backed by
+/// something they share.
+#[async_trait]
+pub trait TaskLock: Send + Sync + 'static {
+    /// Claim the run of `task` due at `due`. Exactly one caller per `(task, due)` gets `true`.
+    ///
+    /// Replicas agree on `due` for cron tasks, and for interval tasks by running them on the wall
+    /// clock's interval boundaries; see the [module](self) docs.
+    ///
+    /// An error skips the run on this replica, on the assumption that another one claimed it.
+    async fn acquire(&self, task: &str, due: DateTime<Utc>) -> io::Result<bool>;
+}
+
+/// Shares one lock between several schedulers.
+#[async_trait]
+impl<L: TaskLock + ?Sized> TaskLock for Arc<L> {
+    async fn acquire(&self, task: &str, due: DateTime<Utc>) -> io::Result<bool> {
+        (**self).acquire(task, due).await
+    }
+}
+
+/// A [`TaskLock`] for schedulers in one process, such as tests. It remembers the latest trigger
+/// claimed for each task.
+#[derive(Default)]
+pub struct MemoryLock {
+    claimed: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl MemoryLock {
+    /// An empty lock.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TaskLock for MemoryLock {
+    async fn acquire(&self, task: &str, due: DateTime<Utc>) -> io::Result<bool> {
+        let mut claimed = self.claimed.lock().expect("task lock poisoned");
+        match claimed.get(task) {
+            Some(last) if *last >= due => Ok(false),
+            _ => {
+                claimed.insert(task.to_string(), due);
+                Ok(true)
+            }
+        }
+    }
+}
+
+/// A [`TaskLock`] for replicas sharing a directory: one file per task, holding the latest trigger
+/// claimed, read and written under an exclusive file lock.
+///
+/// The directory must be on a file system whose locks every replica sees, such as a local disk
+/// shared by processes on one host.
+pub struct FileLock {
+    dir: PathBuf,
+}
+
+impl FileLock {
+    /// Keep the lock files in `dir`, creating it on first use.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl TaskLock for FileLock {
+    async fn acquire(&self, task: &str, due: DateTime<Utc>) -> io::Result<bool> {
+        // Hex, so every task name maps to its own file, whatever characters it holds.
+        let file_name: String = task.bytes().map(|byte| format!("{byte:02x}")).collect();
+        let (dir, path) = (self.dir.clone(), self.dir.join(format!("{file_name}.lock")));
+        tokio::task::spawn_blocking(move || claim_file(&dir, &path, due))
+            .await
+            .map_err(io::Error::other)?
+    }
+}
+
+fn claim_file(dir: &Path, path: &Path, due: DateTime<Utc>) -> io::Result<bool> {
+    std::fs::create_dir_all(dir)?;
+    let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+    // Released when `file` is dropped.
+    file.lock()?;
+    let mut last = String::new();
+    file.read_to_string(&mut last)?;
+    let due = due.timestamp_millis();
+    if last.trim().parse::<i64>().is_ok_and(|last| last >= due) {
+        return Ok(false);
+    }
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    write!(file, "{due}")?;
+    Ok(true)
+}
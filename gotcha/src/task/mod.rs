@@ -0,0 +1,905 @@
+//! # Task Module
+//!
+//! This module provides task scheduling capabilities for Gotcha web applications.
+//! It supports both cron-based and interval-based task scheduling.
+//!
+//! ## Features
+//!
+//! - Cron expression based scheduling, in UTC or any [`Tz`]
+//! - Fixed interval scheduling
+//! - Async task execution
+//! - Access to application context in tasks
+//! - A [`TaskHandle`] per task to pause, resume, cancel or trigger it at runtime
+//! - Per-task [`TaskOptions`]: overlap policy, timeout, retries with backoff, and jitter
+//! - A [`history`] of every attempt, and opt-in [`admin`] routes to inspect and trigger tasks
+//! - `[tasks.<name>]` [`config`] sections to disable or reschedule a task without a rebuild
+//! - A [`lock`] shared by replicas, so each trigger runs on one of them only
+//! - Tasks [`declare`]d with `#[task]` on an async function, registered when the server starts
+//!
+//! ## Examples
+//!
+//! ```rust,no_run
+//! use gotcha::TaskScheduler;
+//! use std::time::Duration;
+//!
+//! # #[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+//! # struct Config {}
+//! fn setup(scheduler: &TaskScheduler<(), Config>) -> Result<(), gotcha::task::TaskError> {
+//!     // Schedule a cron task (the expression is a `String`)
+//!     scheduler.cron("daily-cleanup", "0 0 0 * * *".to_string(), |_ctx| async move {
+//!         // Task implementation
+//!     })?;
+//!
+//!     // Schedule an interval task, and keep its handle to control it later
+//!     let heartbeat = scheduler.interval("heartbeat", Duration::from_secs(60), |_ctx| async move {
+//!         // Task implementation
+//!     })?;
+//!     heartbeat.pause();
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Tasks have access to the application context and can be used for:
+//! - Periodic cleanup jobs
+//! - Data synchronization
+//! - Health checks
+//! - Background processing
+//! - Scheduled notifications
+//!
+
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use chrono::{DateTime, LocalResult, NaiveDateTime, TimeZone, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{Notify, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::Instant;
+use tracing::info;
+
+use crate::message::panic_message;
+use crate::outcome::Outcome;
+use crate::GotchaContext;
+
+pub mod admin;
+pub mod config;
+pub mod declare;
+pub mod history;
+pub mod lock;
+
+pub use chrono_tz::Tz;
+pub use config::TaskConfig;
+pub use crate::retry::RetryPolicy;
+pub use gotcha_core::duration::{parse_duration, DurationError};
+pub use declare::{FromTaskContext, TaskContext, TaskDeclaration, TaskFuture};
+pub use history::{MemoryHistory, RunOutcome, TaskHistory, TaskRun};
+pub use lock::{FileLock, MemoryLock, TaskLock};
+
+/// Why a task could not be scheduled.
+#[derive(Debug, Error)]
+pub enum TaskError {
+    /// The cron expression could not be parsed.
+    #[error("task {name:?} has an invalid cron schedule {expression:?}: {source}")]
+    InvalidSchedule {
+        /// The task's name.
+        name: String,
+        /// The expression as given.
+        expression: String,
+        /// Why it was rejected.
+        source: cron::error::Error,
+    },
+
+    /// An interval task was given a zero interval.
+    #[error("task {name:?} has a zero interval")]
+    ZeroInterval {
+        /// The task's name.
+        name: String,
+    },
+
+    /// Another task is already registered under this name.
+    #[error("a task named {0:?} is already scheduled")]
+    Duplicate(String),
+
+    /// A `#[task]` function takes an argument the application context cannot provide.
+    #[error("task {name:?} takes a `{argument}`, which the application cannot provide")]
+    Argument {
+        /// The task's name.
+        name: String,
+        /// The argument's type.
+        argument: &'static str,
+    },
+
+    /// A `#[task]` names a time zone that does not exist.
+    #[error("task {name:?} has an unknown time zone {timezone:?}")]
+    InvalidTimezone {
+        /// The task's name.
+        name: String,
+        /// The zone as given.
+        timezone: String,
+    },
+
+    /// The task's `[tasks.<name>]` section sets both `cron` and `interval`.
+    #[error("task {name:?} is configured with both a cron expression and an interval")]
+    ConflictingSchedule {
+        /// The task's name.
+        name: String,
+    },
+}
+
+/// What happens when a task is due while its previous run is still going.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overlap {
+    /// Drop the new run.
+    #[default]
+    Skip,
+    /// Start the new run once the previous ones finish, in order. At most 8 runs wait at a time;
+    /// one due beyond that is skipped.
+    Queue,
+    /// Start the new run alongside the previous ones.
+    Concurrent,
+}
+
+/// How a task runs: what to do on overlap, how long a run may take, whether failures are
+/// retried, and how much to spread runs out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskOptions {
+    /// What happens when the task is due while it is still running.
+    pub overlap: Overlap,
+    /// Abort an attempt that takes longer than this. It counts as a failure.
+    pub timeout: Option<Duration>,
+    /// Retry failed attempts: an `Err`, a panic or a timeout. `None` never retries.
+    pub retry: Option<RetryPolicy>,
+    /// Delay each scheduled run by a random amount up to this, so replicas sharing a schedule
+    /// do not all fire at once. Manual [`trigger`](TaskHandle::trigger)s run immediately.
+    pub jitter: Option<Duration>,
+    /// The time zone a cron expression is evaluated in, so `"0 0 2 * * *"` means 02:00 local
+    /// time on both sides of a daylight saving change. UTC when `None`; interval tasks ignore it.
+    pub timezone: Option<Tz>,
+}
+
+/// The schedule a task is registered with, before it is parsed and checked.
+enum Every {
+    Cron(String),
+    Interval(Duration),
+}
+
+/// When a task fires.
+enum Trigger {
+    Cron(Box<Schedule>, Tz),
+    Interval(Duration),
+}
+
+/// The [`TaskHistory`] of a scheduler, replaceable after tasks are registered.
+type HistorySlot = Arc<RwLock<Arc<dyn TaskHistory>>>;
+
+fn default_history() -> HistorySlot {
+    Arc::new(RwLock::new(Arc::new(MemoryHistory::default())))
+}
+
+/// The [`TaskLock`] of a scheduler, if it has one, replaceable after tasks are registered.
+type LockSlot = Arc<RwLock<Option<Arc<dyn TaskLock>>>>;
+
+/// State shared between a task's [`TaskHandle`]s and the loop driving it.
+struct TaskControl {
+    name: String,
+    trigger: Trigger,
+    options: TaskOptions,
+    history: HistorySlot,
+    lock: LockSlot,
+    paused: AtomicBool,
+    cancelled: AtomicBool,
+    triggered: AtomicBool,
+    next_run: Mutex<Option<DateTime<Utc>>>,
+    /// Wakes the loop after any of the flags above change.
+    wake: Notify,
+    /// Wakes every run waiting out a retry's backoff once the task is cancelled.
+    stopped: Notify,
+}
+
+impl TaskControl {
+    fn new(name: String, trigger: Trigger, options: TaskOptions, history: HistorySlot, lock: LockSlot) -> Arc<Self> {
+        Arc::new(Self {
+            name,
+            trigger,
+            options,
+            history,
+            lock,
+            paused: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+            triggered: AtomicBool::new(false),
+            next_run: Mutex::new(None),
+            wake: Notify::new(),
+            stopped: Notify::new(),
+        })
+    }
+
+    fn set_next_run(&self, next_run: Option<DateTime<Utc>>) {
+        *self.next_run.lock().expect("task state poisoned") = next_run;
+    }
+
+    fn history(&self) -> Arc<dyn TaskHistory> {
+        self.history.read().expect("task history poisoned").clone()
+    }
+
+    fn lock(&self) -> Option<Arc<dyn TaskLock>> {
+        self.lock.read().expect("task lock poisoned").clone()
+    }
+}
+
+/// Controls one scheduled task. Cheap to clone; every clone controls the same task.
+#[derive(Clone)]
+pub struct TaskHandle {
+    control: Arc<TaskControl>,
+}
+
+impl TaskHandle {
+    /// The name the task was registered under.
+    pub fn name(&self) -> &str {
+        &self.control.name
+    }
+
+    /// Stop firing on schedule until [`resume`](Self::resume)d. A run already in progress
+    /// finishes, and [`trigger`](Self::trigger) still runs the task.
+    pub fn pause(&self) {
+        self.control.paused.store(true, Ordering::SeqCst);
+        self.control.wake.notify_one();
+    }
+
+    /// Fire on schedule again. Triggers missed while paused are not made up.
+    pub fn resume(&self) {
+        self.control.paused.store(false, Ordering::SeqCst);
+        self.control.wake.notify_one();
+    }
+
+    /// Stop the task for good. A run already in progress finishes; queued runs and retries are
+    /// dropped.
+    pub fn cancel(&self) {
+        self.control.cancelled.store(true, Ordering::SeqCst);
+        self.control.wake.notify_one();
+        self.control.stopped.notify_waiters();
+    }
+
+    /// Run the task now, outside its schedule. The schedule itself is unchanged.
+    pub fn trigger(&self) {
+        self.control.triggered.store(true, Ordering::SeqCst);
+        self.control.wake.notify_one();
+    }
+
+    /// Whether the task is paused.
+    pub fn is_paused(&self) -> bool {
+        self.control.paused.load(Ordering::SeqCst)
+    }
+
+    /// Whether the task has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.control.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// When the task fires next, or `None` while it is paused or cancelled.
+    pub fn next_run(&self) -> Option<DateTime<Utc>> {
+        *self.control.next_run.lock().expect("task state poisoned")
+    }
+
+    /// The task's recorded attempts, most recent first.
+    pub fn history(&self) -> Vec<TaskRun> {
+        self.control.history().runs(&self.control.name)
+    }
+
+    /// The task's most recent attempt.
+    pub fn last_run(&self) -> Option<TaskRun> {
+        self.history().into_iter().next()
+    }
+}
+
+impl std::fmt::Debug for TaskHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskHandle")
+            .field("name", &self.name())
+            .field("paused", &self.is_paused())
+            .field("cancelled", &self.is_cancelled())
+            .field("next_run", &self.next_run())
+            .finish()
+    }
+}
+
+/// A registered task: its handle, and the loop driving it.
+struct ScheduledTask {
+    handle: TaskHandle,
+    driver: JoinHandle<()>,
+}
+
+/// The tasks registered on a [`TaskScheduler`] and their history, without the scheduler's context
+/// type. This is what the [`admin`] routes read; clones share the same tasks.
+#[derive(Clone)]
+pub struct TaskRegistry {
+    tasks: Arc<Mutex<Vec<ScheduledTask>>>,
+    history: HistorySlot,
+    lock: LockSlot,
+}
+
+impl TaskRegistry {
+    /// Handles of every registered task, in registration order.
+    pub fn tasks(&self) -> Vec<TaskHandle> {
+        self.lock().iter().map(|task| task.handle.clone()).collect()
+    }
+
+    /// The handle of the task registered as `name`.
+    pub fn get(&self, name: &str) -> Option<TaskHandle> {
+        self.lock().iter().find(|task| task.handle.name() == name).map(|task| task.handle.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<ScheduledTask>> {
+        self.tasks.lock().expect("task registry poisoned")
+    }
+}
+
+/// Registers background tasks that run alongside the server, each with access to the application
+/// context.
+///
+/// Clones share one set of tasks, so a clone kept by the application can list them and
+/// [`shutdown`](Self::shutdown) all of them.
+pub struct TaskScheduler<T1: Clone + Send + Sync + 'static, T2: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default> {
+    context: GotchaContext<T1, T2>,
+    registry: TaskRegistry,
+}
+
+impl<T1, T2> Clone for TaskScheduler<T1, T2>
+where
+    T1: Clone + Send + Sync + 'static,
+    T2: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default,
+{
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            registry: self.registry.clone(),
+        }
+    }
+}
+
+impl<T1, T2> TaskScheduler<T1, T2>
+where
+    T1: Clone + Send + Sync + 'static,
+    T2: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default,
+{
+    /// Create a scheduler bound to the application context tasks will receive.
+    pub fn new(context: GotchaContext<T1, T2>) -> Self {
+        Self {
+            context,
+            registry: TaskRegistry {
+                tasks: Arc::new(Mutex::new(Vec::new())),
+                history: default_history(),
+                lock: LockSlot::default(),
+            },
+        }
+    }
+
+    /// Record runs in `history` instead of the default [`MemoryHistory`], including those of tasks
+    /// already registered.
+    pub fn set_history(&self, history: impl TaskHistory) {
+        *self.registry.history.write().expect("task history poisoned") = Arc::new(history);
+    }
+
+    /// Claim every scheduled run from `lock` before starting it, including the runs of tasks
+    /// already registered. Replicas sharing the lock's backend then run each trigger once between
+    /// them; see [`lock`] for what counts as the same trigger.
+    pub fn set_lock(&self, lock: impl TaskLock) {
+        *self.registry.lock.write().expect("task lock poisoned") = Some(Arc::new(lock));
+        // Interval tasks plan their next run again, on the wall clock's boundaries.
+        for task in self.registry.lock().iter() {
+            task.handle.control.wake.notify_one();
+        }
+    }
+
+    /// The registered tasks, for the [`admin`] routes. The application installs it when the
+    /// server starts.
+    pub fn registry(&self) -> TaskRegistry {
+        self.registry.clone()
+    }
+
+    /// The application context tasks run with, for starting other background work alongside
+    /// them, such as a `JobQueue`.
+    pub fn context(&self) -> &GotchaContext<T1, T2> {
+        &self.context
+    }
+
+    /// Schedule a task on a cron expression.
+    ///
+    /// The expression has a seconds field (`"0 */5 * * * *"` is every five minutes) and is
+    /// evaluated in UTC unless [`TaskOptions::timezone`] says otherwise. An invalid one is an error
+    /// rather than a task that never runs.
+    ///
+    /// The task's [`TaskConfig`] section, if the configuration has one, takes precedence over the
+    /// schedule given here.
+    pub fn cron<F, FF>(&self, name: impl AsRef<str>, expression: String, task: F) -> Result<TaskHandle, TaskError>
+    where
+        F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+        FF: Future + Send + 'static,
+        FF::Output: Outcome,
+    {
+        self.cron_with_options(name, expression, TaskOptions::default(), task)
+    }
+
+    /// [`cron`](Self::cron) with explicit [`TaskOptions`].
+    pub fn cron_with_options<F, FF>(&self, name: impl AsRef<str>, expression: String, options: TaskOptions, task: F) -> Result<TaskHandle, TaskError>
+    where
+        F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+        FF: Future + Send + 'static,
+        FF::Output: Outcome,
+    {
+        self.spawn(name.as_ref().to_string(), Every::Cron(expression), options, task)
+    }
+
+    /// Run `task` every `interval`, starting one interval from now. While a [`lock`] is
+    /// installed, it runs on the wall clock's interval boundaries instead, so replicas agree on
+    /// each run.
+    ///
+    /// As with [`cron`](Self::cron), the task's [`TaskConfig`] section takes precedence.
+    pub fn interval<F, FF>(&self, name: impl AsRef<str>, interval: Duration, task: F) -> Result<TaskHandle, TaskError>
+    where
+        F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+        FF: Future + Send + 'static,
+        FF::Output: Outcome,
+    {
+        self.interval_with_options(name, interval, TaskOptions::default(), task)
+    }
+
+    /// [`interval`](Self::interval) with explicit [`TaskOptions`].
+    pub fn interval_with_options<F, FF>(&self, name: impl AsRef<str>, interval: Duration, options: TaskOptions, task: F) -> Result<TaskHandle, TaskError>
+    where
+        F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+        FF: Future + Send + 'static,
+        FF::Output: Outcome,
+    {
+        self.spawn(name.as_ref().to_string(), Every::Interval(interval), options, task)
+    }
+
+    /// The handle of the task registered as `name`.
+    pub fn get(&self, name: &str) -> Option<TaskHandle> {
+        self.registry.get(name)
+    }
+
+    /// Handles of every registered task, in registration order.
+    pub fn tasks(&self) -> Vec<TaskHandle> {
+        self.registry.tasks()
+    }
+
+    /// Cancel every task and wait for runs in progress to finish.
+    pub async fn shutdown(&self) {
+        let tasks = std::mem::take(&mut *self.registry.lock());
+        for task in &tasks {
+            task.handle.cancel();
+        }
+        for task in tasks {
+            if let Err(join_error) = task.driver.await {
+                tracing::error!("task {:?} stopped abnormally: {join_error}", task.handle.name());
+            }
+        }
+    }
+
+    /// Register a task firing `every`, or on the schedule its [`TaskConfig`] section sets instead.
+    fn spawn<F, FF>(&self, name: String, every: Every, options: TaskOptions, task: F) -> Result<TaskHandle, TaskError>
+    where
+        F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+        FF: Future + Send + 'static,
+        FF::Output: Outcome,
+    {
+        let settings = self.context.config.tasks.get(&name).cloned().unwrap_or_default();
+        let every = match (settings.cron, settings.interval) {
+            (Some(_), Some(_)) => return Err(TaskError::ConflictingSchedule { name }),
+            (Some(expression), None) => Every::Cron(expression),
+            (None, Some(interval)) => Every::Interval(interval),
+            (None, None) => every,
+        };
+        let trigger = match every {
+            Every::Cron(expression) => {
+                let schedule = Schedule::from_str(&expression).map_err(|source| TaskError::InvalidSchedule {
+                    name: name.clone(),
+                    expression,
+                    source,
+                })?;
+                let timezone = settings.timezone.or(options.timezone).unwrap_or(Tz::UTC);
+                info!("starting cron task: {name} ({timezone})");
+                Trigger::Cron(Box::new(schedule), timezone)
+            }
+            Every::Interval(interval) if interval.is_zero() => return Err(TaskError::ZeroInterval { name }),
+            Every::Interval(interval) => {
+                info!("starting interval task: {name}");
+                Trigger::Interval(interval)
+            }
+        };
+
+        let mut tasks = self.registry.lock();
+        if tasks.iter().any(|scheduled| scheduled.handle.name() == name) {
+            return Err(TaskError::Duplicate(name));
+        }
+        let control = TaskControl::new(name, trigger, options, self.registry.history.clone(), self.registry.lock.clone());
+        if !settings.enabled {
+            info!("task {:?} is disabled by configuration; registering it paused", control.name);
+            control.paused.store(true, Ordering::SeqCst);
+        }
+        let driver = tokio::spawn(drive(self.context.clone(), control.clone(), task));
+        let handle = TaskHandle { control };
+        tasks.push(ScheduledTask {
+            handle: handle.clone(),
+            driver,
+        });
+        Ok(handle)
+    }
+}
+
+/// Run the task once, under supervision: a panic, an `Err` or a timeout is logged and retried
+/// according to the task's options, and never takes the scheduling loop down with it. Every
+/// attempt is recorded in the task's history.
+async fn execute<T1, T2, F, FF>(control: Arc<TaskControl>, context: GotchaContext<T1, T2>, task: Arc<F>)
+where
+    T1: Clone + Send + Sync + 'static,
+    T2: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default,
+    F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+    FF: Future + Send + 'static,
+    FF::Output: Outcome,
+{
+    let name = &control.name;
+    let options = &control.options;
+    let mut retries = 0;
+    loop {
+        if control.cancelled.load(Ordering::SeqCst) {
+            return;
+        }
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let mut attempt = tokio::spawn(task(context.clone()));
+        let joined = match options.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, &mut attempt).await.map_err(|_| {
+                attempt.abort();
+                format!("timed out after {timeout:?}")
+            }),
+            None => Ok(attempt.await),
+        };
+        let (outcome, error) = match joined {
+            Ok(Ok(outcome)) => match outcome.into_result() {
+                Ok(()) => (RunOutcome::Succeeded, None),
+                Err(error) => (RunOutcome::Failed, Some(error)),
+            },
+            Ok(Err(join_error)) => (RunOutcome::Panicked, Some(panic_message(join_error))),
+            Err(timed_out) => (RunOutcome::TimedOut, Some(timed_out)),
+        };
+        control.history().record(TaskRun {
+            task: name.clone(),
+            started_at,
+            duration_ms: started.elapsed().as_millis().try_into().unwrap_or(u64::MAX),
+            attempt: retries + 1,
+            outcome,
+            error: error.clone(),
+        });
+        let Some(error) = error else {
+            return;
+        };
+        match &options.retry {
+            Some(retry) if retries < retry.max_retries => {
+                retries += 1;
+                let backoff = retry.backoff(retries);
+                // Registered before the flag is checked, so a cancel in between still wakes it.
+                let stopped = control.stopped.notified();
+                tokio::pin!(stopped);
+                stopped.as_mut().enable();
+                if control.cancelled.load(Ordering::SeqCst) {
+                    return;
+                }
+                tracing::warn!("scheduled task {name:?} failed: {error}; retry {retries}/{} in {backoff:?}", retry.max_retries);
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = stopped => return,
+                }
+            }
+            _ => {
+                tracing::error!("scheduled task {name:?} failed: {error}");
+                return;
+            }
+        }
+    }
+}
+
+/// Start a run: a scheduled one (`due` is set) only once it is claimed from the task's lock, if
+/// the scheduler has one.
+async fn run<T1, T2, F, FF>(control: Arc<TaskControl>, context: GotchaContext<T1, T2>, task: Arc<F>, due: Option<DateTime<Utc>>)
+where
+    T1: Clone + Send + Sync + 'static,
+    T2: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default,
+    F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+    FF: Future + Send + 'static,
+    FF::Output: Outcome,
+{
+    if let (Some(due), Some(lock)) = (due, control.lock()) {
+        match lock.acquire(&control.name, due).await {
+            Ok(true) => {}
+            Ok(false) => {
+                info!("task {:?} due at {due} was claimed by another replica; skipping", control.name);
+                return;
+            }
+            Err(error) => {
+                tracing::warn!("could not claim task {:?} due at {due}: {error}; skipping", control.name);
+                return;
+            }
+        }
+    }
+    execute(control, context, task).await
+}
+
+/// A random delay in `0..=max`. Jitter only has to differ between replicas, not be unpredictable,
+/// so a randomly seeded hasher does instead of an RNG dependency.
+fn jitter(max: Duration) -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+    let random = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    max.mul_f64(random as f64 / u64::MAX as f64)
+}
+
+/// When `trigger` fires next after the run planned for `last` and due at its time (`None` before
+/// the first run), and the due time replicas claim that run by. With `aligned`, set while a lock
+/// is installed, interval runs fall on the wall clock's interval boundaries; see [`lock`].
+fn next_deadline(trigger: &Trigger, last: Option<(Instant, DateTime<Utc>)>, aligned: bool) -> Option<(Instant, DateTime<Utc>)> {
+    let now = Instant::now();
+    match trigger {
+        Trigger::Cron(schedule, timezone) => {
+            let wall_now = Utc::now();
+            // A trigger computed in the past (clock skew, or a long previous run) would make
+            // `to_std()` fail — run immediately in that case rather than panicking.
+            let next = next_fire(schedule, *timezone, wall_now)?;
+            Some((now + (next - wall_now).to_std().unwrap_or(Duration::ZERO), next))
+        }
+        Trigger::Interval(interval) if aligned => {
+            let wall_now = Utc::now();
+            // Past the last run's boundary too, in case the timer woke a little before the clock.
+            let after = last.map_or(wall_now, |(_, due)| due.max(wall_now));
+            // Runs missed while paused or busy are skipped rather than fired in a burst.
+            let next = next_boundary(after, *interval)?;
+            Some((now + (next - wall_now).to_std().unwrap_or(Duration::ZERO), next))
+        }
+        Trigger::Interval(interval) => {
+            let mut deadline = last.map_or(now, |(deadline, _)| deadline) + *interval;
+            // Runs missed while paused or busy are skipped rather than fired in a burst.
+            while deadline <= now {
+                deadline += *interval;
+            }
+            // Only claimed if a lock was installed after this run was planned.
+            Some((deadline, wall_clock(deadline)))
+        }
+    }
+}
+
+/// The first multiple of `interval` since the Unix epoch after `time`.
+fn next_boundary(time: DateTime<Utc>, interval: Duration) -> Option<DateTime<Utc>> {
+    let step = i64::try_from(interval.as_millis()).unwrap_or(i64::MAX).max(1);
+    let millis = time.timestamp_millis();
+    DateTime::from_timestamp_millis((millis - millis.rem_euclid(step)).checked_add(step)?)
+}
+
+/// The first time after `after` that `schedule` matches on `timezone`'s wall clock.
+///
+/// The schedule is matched against local times, which a daylight saving change can make skip or
+/// repeat. A skipped time fires as soon as the clocks resume (02:30 becomes 03:00 when clocks
+/// spring forward at 02:00), and a repeated one fires once, the first time it comes round.
+fn next_fire(schedule: &Schedule, timezone: Tz, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    // `Utc` here only carries the naive local time: the `cron` crate drops local times that are
+    // skipped or repeated in the zone it iterates in, so it iterates in one that has neither.
+    let local = Utc.from_utc_datetime(&after.with_timezone(&timezone).naive_local());
+    schedule
+        .after(&local)
+        .filter_map(|candidate| resolve(timezone, candidate.naive_utc()))
+        .find(|candidate| *candidate > after)
+}
+
+/// The instant the local time `local` denotes in `timezone`: the earlier one if it happens twice,
+/// and the first minute after the gap if the clocks skip it.
+fn resolve(timezone: Tz, mut local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    // No zone skips more than a day.
+    for _ in 0..24 * 60 {
+        match timezone.from_local_datetime(&local) {
+            LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => return Some(time.with_timezone(&Utc)),
+            LocalResult::None => local += chrono::Duration::minutes(1),
+        }
+    }
+    None
+}
+
+/// The wall-clock time of `instant`.
+fn wall_clock(instant: Instant) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(instant.saturating_duration_since(Instant::now())).unwrap_or_default()
+}
+
+/// How many runs may wait behind the running one under [`Overlap::Queue`].
+const QUEUE_CAPACITY: usize = 8;
+
+/// The loop behind every task: wait for the next trigger, a manual trigger or a control change,
+/// and start runs according to the overlap policy until the task is cancelled.
+async fn drive<T1, T2, F, FF>(context: GotchaContext<T1, T2>, control: Arc<TaskControl>, task: F)
+where
+    T1: Clone + Send + Sync + 'static,
+    T2: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default,
+    F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+    FF: Future + Send + 'static,
+    FF::Output: Outcome,
+{
+    let task = Arc::new(task);
+    // One permit: held by the running execution under `Skip` and `Queue`.
+    let gate = Arc::new(Semaphore::new(1));
+    // One permit per run allowed to wait for `gate` under `Queue`.
+    let queue = Arc::new(Semaphore::new(QUEUE_CAPACITY));
+    let mut runs = JoinSet::new();
+    let start = |runs: &mut JoinSet<()>, due: Option<DateTime<Utc>>| {
+        let run = run(control.clone(), context.clone(), task.clone(), due);
+        match control.options.overlap {
+            Overlap::Skip => match gate.clone().try_acquire_owned() {
+                Ok(permit) => {
+                    runs.spawn(async move {
+                        run.await;
+                        drop(permit);
+                    });
+                }
+                Err(_) => info!("task {:?} is still running; skipping this run", control.name),
+            },
+            Overlap::Queue => match queue.clone().try_acquire_owned() {
+                Ok(place) => {
+                    let (gate, control) = (gate.clone(), control.clone());
+                    runs.spawn(async move {
+                        let _permit = gate.acquire_owned().await.expect("the task gate is never closed");
+                        drop(place);
+                        if !control.cancelled.load(Ordering::SeqCst) {
+                            run.await;
+                        }
+                    });
+                }
+                Err(_) => info!("task {:?} already has {QUEUE_CAPACITY} runs queued; skipping this run", control.name),
+            },
+            Overlap::Concurrent => {
+                runs.spawn(run);
+            }
+        }
+    };
+
+    let mut last = None;
+    // The next scheduled run, when it fires once jitter is added, and the due time it is claimed
+    // by; kept until it fires or the task's controls change, so jitter is drawn once per run.
+    let mut planned: Option<(Instant, Instant, DateTime<Utc>)> = None;
+    while !control.cancelled.load(Ordering::SeqCst) {
+        if control.triggered.swap(false, Ordering::SeqCst) {
+            start(&mut runs, None);
+            continue;
+        }
+        if control.paused.load(Ordering::SeqCst) {
+            control.set_next_run(None);
+            planned = None;
+            tokio::select! {
+                _ = control.wake.notified() => {}
+                Some(_) = runs.join_next(), if !runs.is_empty() => {}
+            }
+            continue;
+        }
+        let (deadline, fire_at, due) = match planned {
+            Some(planned) => planned,
+            None => {
+                let Some((deadline, due)) = next_deadline(&control.trigger, last, control.lock().is_some()) else {
+                    info!("task {:?} has no upcoming trigger", control.name);
+                    break;
+                };
+                let fire_at = deadline + control.options.jitter.map(jitter).unwrap_or_default();
+                control.set_next_run(Some(wall_clock(fire_at)));
+                *planned.insert((deadline, fire_at, due))
+            }
+        };
+        tokio::select! {
+            _ = tokio::time::sleep_until(fire_at) => {
+                last = Some((deadline, due));
+                planned = None;
+                start(&mut runs, Some(due));
+            }
+            _ = control.wake.notified() => planned = None,
+            Some(_) = runs.join_next(), if !runs.is_empty() => {}
+        }
+    }
+    control.set_next_run(None);
+    while runs.join_next().await.is_some() {}
+}
+
+/// Drives a cron task in UTC, without a handle, history or lock.
+#[deprecated(note = "declare the task with `#[task(cron = \"..\")]`, or register it with `TaskScheduler::cron`")]
+pub async fn cron_proc_macro_wrapper<T1, T2, F, FF>(context: GotchaContext<T1, T2>, schedule: Schedule, name: String, task: F)
+where
+    T1: Clone + Send + Sync + 'static,
+    T2: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default,
+    F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+    FF: Future + Send + 'static,
+    FF::Output: Outcome,
+{
+    drive(
+        context,
+        TaskControl::new(
+            name,
+            Trigger::Cron(Box::new(schedule), Tz::UTC),
+            TaskOptions::default(),
+            default_history(),
+            LockSlot::default(),
+        ),
+        task,
+    )
+    .await
+}
+
+/// Drives an interval task, without a handle, history or lock.
+#[deprecated(note = "declare the task with `#[task(interval = \"..\")]`, or register it with `TaskScheduler::interval`")]
+pub async fn interval_proc_macro_wrapper<T1, T2, F, FF>(context: GotchaContext<T1, T2>, interval: Duration, name: String, task: F)
+where
+    T1: Clone + Send + Sync + 'static,
+    T2: Clone + Send + Sync + 'static + Serialize + for<'de> Deserialize<'de> + Default,
+    F: Fn(GotchaContext<T1, T2>) -> FF + Send + Sync + 'static,
+    FF: Future + Send + 'static,
+    FF::Output: Outcome,
+{
+    assert!(!interval.is_zero(), "task {name:?} has a zero interval");
+    drive(
+        context,
+        TaskControl::new(
+            name,
+            Trigger::Interval(interval),
+            TaskOptions::default(),
+            default_history(),
+            LockSlot::default(),
+        ),
+        task,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    fn next(expression: &str, after: &str) -> DateTime<Utc> {
+        next_fire(&Schedule::from_str(expression).unwrap(), Berlin, utc(after)).unwrap()
+    }
+
+    #[test]
+    fn cron_follows_the_local_clock_across_dst() {
+        // 02:00 in Berlin is 01:00 UTC in winter and 00:00 UTC in summer.
+        assert_eq!(next("0 0 2 * * *", "2024-01-10T12:00:00Z"), utc("2024-01-11T01:00:00Z"));
+        assert_eq!(next("0 0 2 * * *", "2024-07-10T12:00:00Z"), utc("2024-07-11T00:00:00Z"));
+    }
+
+    #[test]
+    fn a_skipped_local_time_fires_when_the_clocks_resume() {
+        // On 2024-03-31 Berlin skips from 02:00 to 03:00 CEST (01:00 UTC).
+        assert_eq!(next("0 30 2 * * *", "2024-03-30T12:00:00Z"), utc("2024-03-31T01:00:00Z"));
+        assert_eq!(next("0 30 2 * * *", "2024-03-31T01:00:00Z"), utc("2024-04-01T00:30:00Z"));
+    }
+
+    #[test]
+    fn a_repeated_local_time_fires_once() {
+        // On 2024-10-27 Berlin goes from 03:00 CEST back to 02:00 CET, so 02:30 happens twice.
+        assert_eq!(next("0 30 2 * * *", "2024-10-26T12:00:00Z"), utc("2024-10-27T00:30:00Z"));
+        assert_eq!(next("0 30 2 * * *", "2024-10-27T00:30:00Z"), utc("2024-10-28T01:30:00Z"));
+    }
+
+    #[test]
+    fn interval_boundaries_are_counted_from_the_epoch() {
+        let minute = Duration::from_secs(60);
+        assert_eq!(next_boundary(utc("2024-01-01T02:00:00Z"), minute), Some(utc("2024-01-01T02:01:00Z")));
+        assert_eq!(next_boundary(utc("2024-01-01T02:00:59.999Z"), minute), Some(utc("2024-01-01T02:01:00Z")));
+        assert_eq!(next_boundary(utc("2024-01-01T02:00:00.001Z"), Duration::from_secs(3600)), Some(utc("2024-01-01T03:00:00Z")));
+    }
+
+    #[test]
+    fn utc_is_unchanged() {
+        let schedule = Schedule::from_str("0 0 2 * * *").unwrap();
+        assert_eq!(next_fire(&schedule, Tz::UTC, utc("2024-03-30T12:00:00Z")), Some(utc("2024-03-31T02:00:00Z")));
+    }
+}
@@ -0,0 +1,485 @@
+//! Serving several versions of an API side by side.
+//!
+//! [`GotchaRouter::versions`] registers the same routes once per declared version. A version is
+//! selected by path prefix (`/v1/users`), by a request header (`x-api-version: 1`) or by the
+//! `Accept` media type (`application/vnd.example.v1+json`). Each version gets its own spec and
+//! documentation UIs under its prefix — `/v1/openapi.json`, `/v1/redoc`, … — whichever strategy
+//! routes the requests.
+//!
+//! `#[api(since = "2", until = "3")]` narrows a handler to the versions in `[since, until)`,
+//! compared by their declaration order; this holds with or without the `openapi` feature. A
+//! handler with an `until` is deprecated in every version it appears in, as is everything in a
+//! version that has a [`sunset`](ApiVersion::sunset) date — and responses from a sunsetting
+//! version carry a `Sunset` header (RFC 8594) with that date.
+//!
+//! Versioned routes behave like any others of the router they are declared on: its later
+//! [`layer`](GotchaRouter::layer)s wrap them, and [`nest`](GotchaRouter::nest) moves them, and
+//! their docs, under its path.
+//!
+//! Bounds, deprecation and the `Sunset` header apply to routes added with
+//! [`get`](GotchaRouter::get), [`post`](GotchaRouter::post) and the other method helpers, which
+//! see the handler. A [`MethodRouter`](axum::routing::MethodRouter) added with
+//! [`route`](GotchaRouter::route) hides its handlers, so it is served as is in every version.
+//!
+//! ```rust,no_run
+//! use gotcha::versioning::{ApiVersion, Versioning};
+//! use gotcha::GotchaRouter;
+//!
+//! async fn list_users() -> &'static str {
+//!     "[]"
+//! }
+//!
+//! let router: GotchaRouter<()> = GotchaRouter::default().versions(
+//!     Versioning::path_prefix()
+//!         .version(ApiVersion::new("1").sunset("Wed, 31 Dec 2025 23:59:59 GMT"))
+//!         .version("2"),
+//!     |router| router.get("/users", list_users),
+//! );
+//! ```
+
+use std::any::type_name;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::extract::Request;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::Route;
+use axum::Router;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{GotchaRouter, Responder};
+
+/// One version of the API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiVersion {
+    /// The version's name, as it appears in the path prefix, header or media type: `"1"`.
+    pub name: String,
+    /// When this version stops being served, as an HTTP-date. Sent as the `Sunset` header.
+    pub sunset: Option<String>,
+}
+
+impl ApiVersion {
+    /// A version called `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            sunset: None,
+        }
+    }
+
+    /// Announce the date this version goes away, as an HTTP-date
+    /// (`"Wed, 31 Dec 2025 23:59:59 GMT"`). Deprecates everything in it.
+    ///
+    /// # Panics
+    ///
+    /// If `http_date` is not an HTTP-date, so a typo stops the application from starting rather
+    /// than silently dropping the `Sunset` header.
+    pub fn sunset(mut self, http_date: impl Into<String>) -> Self {
+        let http_date = http_date.into();
+        assert!(
+            is_http_date(&http_date),
+            "the sunset of API version {:?} is not an HTTP-date like \"Wed, 31 Dec 2025 23:59:59 GMT\": {http_date:?}",
+            self.name
+        );
+        self.sunset = Some(http_date);
+        self
+    }
+
+    /// The path prefix of this version: `/v{name}`. Routes live under it with
+    /// [`Versioning::path_prefix`], and its docs do with every strategy.
+    pub fn prefix(&self) -> String {
+        format!("/v{}", self.name)
+    }
+}
+
+/// Whether `date` can be sent as a `Sunset` header: an RFC 2822 date, as HTTP-dates are.
+pub(crate) fn is_http_date(date: &str) -> bool {
+    chrono::DateTime::parse_from_rfc2822(date).is_ok() && axum::http::HeaderValue::from_str(date).is_ok()
+}
+
+impl From<&str> for ApiVersion {
+    fn from(name: &str) -> Self {
+        ApiVersion::new(name)
+    }
+}
+
+/// How a request picks the version that serves it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionStrategy {
+    /// By path: `/v1/users`.
+    PathPrefix,
+    /// By the named request header: `x-api-version: 1`.
+    Header(String),
+    /// By the vendor media type in `Accept`: `application/vnd.{vendor}.v1+json`.
+    MediaType(String),
+}
+
+/// The versions an API is served in, and how requests choose between them.
+#[derive(Debug, Clone)]
+pub struct Versioning {
+    pub(crate) strategy: VersionStrategy,
+    pub(crate) versions: Vec<ApiVersion>,
+    pub(crate) default: Option<String>,
+}
+
+impl Versioning {
+    fn with_strategy(strategy: VersionStrategy) -> Self {
+        Self {
+            strategy,
+            versions: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Versions selected by path prefix: `/v1/users`, `/v2/users`.
+    pub fn path_prefix() -> Self {
+        Self::with_strategy(VersionStrategy::PathPrefix)
+    }
+
+    /// Versions selected by the value of request header `name`.
+    pub fn header(name: impl Into<String>) -> Self {
+        Self::with_strategy(VersionStrategy::Header(name.into()))
+    }
+
+    /// Versions selected by an `Accept` of `application/vnd.{vendor}.v{version}+json`.
+    pub fn media_type(vendor: impl Into<String>) -> Self {
+        Self::with_strategy(VersionStrategy::MediaType(vendor.into()))
+    }
+
+    /// Declare a version. Versions are ordered by declaration, oldest first; `since` and `until`
+    /// compare against that order.
+    pub fn version(mut self, version: impl Into<ApiVersion>) -> Self {
+        self.versions.push(version.into());
+        self
+    }
+
+    /// The version serving requests that do not ask for one, with the header and media type
+    /// strategies. Defaults to the newest.
+    pub fn default_version(mut self, name: impl Into<String>) -> Self {
+        self.default = Some(name.into());
+        self
+    }
+
+    /// The version a request asks for, if it asks for one.
+    pub(crate) fn requested<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        match &self.strategy {
+            VersionStrategy::PathPrefix => None,
+            VersionStrategy::Header(name) => headers.get(name.as_str()).and_then(|value| value.to_str().ok()).map(str::trim),
+            VersionStrategy::MediaType(vendor) => {
+                let accept = headers.get(axum::http::header::ACCEPT)?.to_str().ok()?;
+                let marker = format!("vnd.{vendor}.v");
+                let start = accept.find(&marker)? + marker.len();
+                let rest = &accept[start..];
+                let end = rest.find(['+', ';', ',', ' ']).unwrap_or(rest.len());
+                Some(&rest[..end])
+            }
+        }
+    }
+
+    fn default_index(&self) -> usize {
+        self.default
+            .as_ref()
+            .and_then(|name| self.versions.iter().position(|version| &version.name == name))
+            .unwrap_or(self.versions.len().saturating_sub(1))
+    }
+
+    fn scope(&self, version: &ApiVersion) -> VersionScope {
+        VersionScope {
+            version: version.clone(),
+            order: self.versions.iter().map(|version| version.name.clone()).collect(),
+        }
+    }
+}
+
+/// The API versions a handler exists in, registered by `#[api(since, until)]`.
+#[doc(hidden)]
+pub struct VersionBounds {
+    /// Fully qualified name of the handler function, as `std::any::type_name` gives it.
+    pub type_name: &'static str,
+    /// The first version the handler exists in.
+    pub since: Option<&'static str>,
+    /// The version the handler is removed in.
+    pub until: Option<&'static str>,
+}
+
+inventory::collect!(VersionBounds);
+
+impl VersionBounds {
+    /// The bounds of handler `H`, if it declares any.
+    pub(crate) fn of<H>() -> Option<&'static Self> {
+        Self::named(type_name::<H>())
+    }
+
+    /// The bounds of the handler called `type_name`, if it declares any.
+    pub(crate) fn named(type_name: &str) -> Option<&'static Self> {
+        inventory::iter::<VersionBounds>.into_iter().find(|bounds| bounds.type_name == type_name)
+    }
+}
+
+/// The version a router is being built for, while the [`GotchaRouter::versions`] closure runs.
+#[derive(Debug, Clone)]
+pub(crate) struct VersionScope {
+    pub(crate) version: ApiVersion,
+    order: Arc<[String]>,
+}
+
+impl VersionScope {
+    fn position(&self, name: &str) -> Option<usize> {
+        let position = self.order.iter().position(|each| each == name);
+        if position.is_none() {
+            tracing::warn!("`{name}` is not a declared API version; the bound is ignored");
+        }
+        position
+    }
+
+    /// Whether an operation available in `[since, until)` exists in this version.
+    pub(crate) fn includes(&self, since: Option<&str>, until: Option<&str>) -> bool {
+        let Some(current) = self.position(&self.version.name) else {
+            return true;
+        };
+        let after_since = since.and_then(|since| self.position(since)).is_none_or(|since| since <= current);
+        let before_until = until.and_then(|until| self.position(until)).is_none_or(|until| current < until);
+        after_since && before_until
+    }
+
+    /// Whether an operation that goes away in `until` is deprecated in this version.
+    pub(crate) fn deprecates(&self, until: Option<&str>) -> bool {
+        until.is_some() || self.version.sunset.is_some()
+    }
+
+    /// Mark the `deprecated` operations of `spec` as such, and document the `Sunset` header their
+    /// responses carry when this version has a sunset date.
+    #[cfg(feature = "openapi")]
+    pub(crate) fn annotate(&self, spec: &mut oas::OpenAPIV3, deprecated: &[(String, axum::http::Method)]) {
+        use axum::http::Method;
+        use oas::{Header, Referenceable, Schema};
+
+        for (path, method) in deprecated {
+            let Some(item) = spec.paths.get_mut(path) else {
+                continue;
+            };
+            let operation = match *method {
+                Method::GET => item.get.as_mut(),
+                Method::POST => item.post.as_mut(),
+                Method::PUT => item.put.as_mut(),
+                Method::DELETE => item.delete.as_mut(),
+                Method::HEAD => item.head.as_mut(),
+                Method::OPTIONS => item.options.as_mut(),
+                Method::PATCH => item.patch.as_mut(),
+                Method::TRACE => item.trace.as_mut(),
+                _ => None,
+            };
+            let Some(operation) = operation else {
+                continue;
+            };
+            operation.deprecated = Some(true);
+            let Some(sunset) = &self.version.sunset else {
+                continue;
+            };
+            for response in operation.responses.data.values_mut() {
+                if let Referenceable::Data(response) = response {
+                    response.headers.get_or_insert_with(Default::default).insert(
+                        "Sunset".to_string(),
+                        Referenceable::Data(Header {
+                            description: Some(format!("This API version is retired at {sunset}.")),
+                            required: Some(true),
+                            deprecated: None,
+                            allow_empty_value: None,
+                            style: None,
+                            explode: None,
+                            allow_reserved: None,
+                            schema: Some(Referenceable::Data(Schema {
+                                _type: Some("string".to_string()),
+                                format: None,
+                                nullable: None,
+                                description: None,
+                                extras: Default::default(),
+                            })),
+                            example: None,
+                            examples: None,
+                            content: None,
+                        }),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The routers [`GotchaRouter::versions`] built, one per version, waiting to be assembled.
+pub(crate) struct VersionedRoutes<State> {
+    pub(crate) versioning: Versioning,
+    /// Where the routes are nested, or empty at the root.
+    pub(crate) path: String,
+    pub(crate) routers: Vec<(ApiVersion, GotchaRouter<State>)>,
+}
+
+impl<State: Clone + Send + Sync + 'static> VersionedRoutes<State> {
+    /// Build each version's router with `routes`.
+    pub(crate) fn build<F>(versioning: Versioning, routes: F) -> Self
+    where
+        F: Fn(GotchaRouter<State>) -> GotchaRouter<State>,
+    {
+        let routers = versioning
+            .versions
+            .iter()
+            .map(|version| {
+                let router = GotchaRouter {
+                    version_scope: Some(versioning.scope(version)),
+                    ..GotchaRouter::default()
+                };
+                (version.clone(), routes(router))
+            })
+            .collect();
+        Self {
+            versioning,
+            path: String::new(),
+            routers,
+        }
+    }
+
+    /// Move the routes under `path`, as [`GotchaRouter::nest`] does.
+    pub(crate) fn nested(mut self, path: &str) -> Self {
+        self.path = format!("{}{}", path.trim_end_matches('/'), self.path);
+        self
+    }
+
+    /// Wrap every version's routes in `layer`, as [`GotchaRouter::layer`] does.
+    pub(crate) fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: Responder + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.routers = self.routers.into_iter().map(|(version, router)| (version, router.layer(layer.clone()))).collect();
+        self
+    }
+
+    /// Add every set of versions to `app`, and their docs to `docs`.
+    ///
+    /// # Panics
+    ///
+    /// When two sets chosen by header or media type live at the same path, as axum panics on
+    /// two fallbacks.
+    pub(crate) fn mount(all: Vec<Self>, mut app: Router, mut docs: Router, parent: &GotchaRouter<State>, state: State) -> (Router, Router) {
+        let mut dispatchers: Vec<VersionDispatch> = Vec::new();
+        for versioned in all {
+            let mut routers = Vec::new();
+            for (version, router) in versioned.routers {
+                let router = router.inherit(parent, &version, &versioned.versioning.strategy, &versioned.path);
+                let (routes, version_docs) = router.assemble(state.clone());
+                docs = docs.merge(version_docs);
+                match versioned.versioning.strategy {
+                    VersionStrategy::PathPrefix => app = app.nest(&format!("{}{}", versioned.path, version.prefix()), routes),
+                    VersionStrategy::Header(_) | VersionStrategy::MediaType(_) if versioned.path.is_empty() => routers.push((version.name, routes)),
+                    VersionStrategy::Header(_) | VersionStrategy::MediaType(_) => routers.push((version.name, Router::new().nest(&versioned.path, routes))),
+                }
+            }
+            if routers.is_empty() {
+                continue;
+            }
+            if dispatchers.iter().any(|dispatcher| dispatcher.path == versioned.path) {
+                panic!("two sets of versions chosen by header or media type are mounted at `{}`", if versioned.path.is_empty() { "/" } else { &versioned.path });
+            }
+            let default = versioned.versioning.default_index();
+            dispatchers.push(VersionDispatch {
+                path: versioned.path,
+                versioning: Arc::new(versioned.versioning),
+                routers: routers.into(),
+                default,
+            });
+        }
+        if !dispatchers.is_empty() {
+            // The deepest path first, so nested sets win over the ones around them.
+            dispatchers.sort_by_key(|dispatcher| std::cmp::Reverse(dispatcher.path.len()));
+            app = app.fallback_service(VersionDispatchers(dispatchers.into()));
+        }
+        (app, docs)
+    }
+}
+
+/// Routes a request to the router of the version its header or media type asks for.
+#[derive(Clone)]
+struct VersionDispatch {
+    /// Where the versioned routes are nested, or empty at the root.
+    path: String,
+    versioning: Arc<Versioning>,
+    routers: Arc<[(String, Router)]>,
+    default: usize,
+}
+
+impl VersionDispatch {
+    /// Whether a request for `path` is for these versions.
+    fn serves(&self, path: &str) -> bool {
+        self.path.is_empty() || path.strip_prefix(self.path.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+}
+
+/// The [`VersionDispatch`]es of a router, deepest path first.
+#[derive(Clone)]
+struct VersionDispatchers(Arc<[VersionDispatch]>);
+
+impl Service<Request> for VersionDispatchers {
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let Some(dispatch) = self.0.iter().find(|dispatch| dispatch.serves(request.uri().path())) else {
+            return Box::pin(async { Ok(StatusCode::NOT_FOUND.into_response()) });
+        };
+        let index = match dispatch.versioning.requested(request.headers()) {
+            None => Some(dispatch.default),
+            Some(requested) => dispatch.routers.iter().position(|(name, _)| name == requested),
+        };
+        match index {
+            Some(index) => {
+                let mut router = dispatch.routers[index].1.clone();
+                Box::pin(router.call(request))
+            }
+            None => Box::pin(async { Ok((StatusCode::BAD_REQUEST, "unsupported API version").into_response()) }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(current: &str) -> VersionScope {
+        Versioning::path_prefix()
+            .version("1")
+            .version("2")
+            .version("3")
+            .scope(&ApiVersion::new(current))
+    }
+
+    #[test]
+    fn since_and_until_bound_a_half_open_range() {
+        assert!(!scope("1").includes(Some("2"), Some("3")));
+        assert!(scope("2").includes(Some("2"), Some("3")));
+        assert!(!scope("3").includes(Some("2"), Some("3")));
+        assert!(scope("3").includes(None, None));
+    }
+
+    #[test]
+    fn media_type_names_the_version() {
+        let versioning = Versioning::media_type("example").version("1").version("2");
+        let mut headers = HeaderMap::new();
+        headers.insert(axum::http::header::ACCEPT, "application/vnd.example.v1+json".parse().unwrap());
+        assert_eq!(versioning.requested(&headers), Some("1"));
+        assert_eq!(versioning.requested(&HeaderMap::new()), None);
+        assert_eq!(versioning.default_index(), 1, "the newest version is the default");
+    }
+}
@@ -0,0 +1,5 @@
+window.ui = SwaggerUIBundle({
+    url: "{{spec_url}}",
+    dom_id: "#swagger-ui",
+    deepLinking: true,
+});
@@ -0,0 +1,291 @@
+//! Actors handle their messages one at a time from a bounded mailbox, and are recreated when they
+//! panic.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use gotcha::actor::{Actor, ActorContext, ActorError, ActorOptions, Actors, Addr, Handles};
+use gotcha::axum::body::{to_bytes, Body};
+use gotcha::axum::http::{Request, StatusCode};
+use gotcha::axum::routing::get;
+use gotcha::axum::Router;
+use gotcha::{ConfigWrapper, GotchaContext};
+use tower::ServiceExt;
+
+#[derive(Clone)]
+struct AppState {
+    step: u64,
+}
+
+fn context() -> GotchaContext<AppState, ()> {
+    GotchaContext::new(ConfigWrapper::default(), AppState { step: 2 })
+}
+
+fn actors() -> Actors<AppState, ()> {
+    Actors::new(context())
+}
+
+struct Counter {
+    count: u64,
+}
+
+impl Actor for Counter {
+    type State = AppState;
+    type Config = ();
+
+    fn create(_context: &ActorContext<Self>) -> Self {
+        Self { count: 0 }
+    }
+}
+
+struct Increment;
+
+#[async_trait]
+impl Handles<Increment> for Counter {
+    type Reply = u64;
+
+    async fn handle(&mut self, _message: Increment, context: &ActorContext<Self>) -> u64 {
+        // Yields halfway, so interleaved messages would lose updates.
+        let count = self.count;
+        tokio::task::yield_now().await;
+        self.count = count + context.state.step;
+        self.count
+    }
+}
+
+struct Crash;
+
+#[async_trait]
+impl Handles<Crash> for Counter {
+    type Reply = ();
+
+    async fn handle(&mut self, _message: Crash, _context: &ActorContext<Self>) {
+        panic!("crash");
+    }
+}
+
+#[tokio::test]
+async fn messages_are_handled_one_at_a_time() {
+    let counter = actors().start::<Counter>();
+    let sends: Vec<_> = (0..50)
+        .map(|_| {
+            let counter = counter.clone();
+            tokio::spawn(async move { counter.send(Increment).await.unwrap() })
+        })
+        .collect();
+    for send in sends {
+        send.await.unwrap();
+    }
+    assert_eq!(counter.send(Increment).await, Ok(102));
+}
+
+#[tokio::test]
+async fn panicking_actors_are_recreated_until_they_run_out_of_restarts() {
+    let counter = actors().start_with::<Counter>(ActorOptions {
+        max_restarts: 2,
+        ..ActorOptions::default()
+    });
+    assert_eq!(counter.send(Increment).await, Ok(2));
+
+    assert!(matches!(counter.send(Crash).await, Err(ActorError::NoReply(_))));
+    assert_eq!(counter.send(Increment).await, Ok(2));
+    assert!(matches!(counter.send(Crash).await, Err(ActorError::NoReply(_))));
+    assert!(counter.is_alive());
+
+    assert!(matches!(counter.send(Crash).await, Err(ActorError::NoReply(_))));
+    // The reply is dropped as the panic unwinds, just before the supervisor gives up.
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(matches!(counter.send(Increment).await, Err(ActorError::Stopped(_))));
+    assert!(!counter.is_alive());
+}
+
+static FRAGILE_CREATES: AtomicUsize = AtomicUsize::new(0);
+
+/// Panics the first time it is created.
+struct Fragile;
+
+impl Actor for Fragile {
+    type State = AppState;
+    type Config = ();
+
+    fn create(_context: &ActorContext<Self>) -> Self {
+        if FRAGILE_CREATES.fetch_add(1, Ordering::SeqCst) == 0 {
+            panic!("not yet");
+        }
+        Self
+    }
+}
+
+struct Broken;
+
+impl Actor for Broken {
+    type State = AppState;
+    type Config = ();
+
+    fn create(_context: &ActorContext<Self>) -> Self {
+        panic!("broken");
+    }
+}
+
+struct Ping;
+
+#[async_trait]
+impl Handles<Ping> for Fragile {
+    type Reply = &'static str;
+
+    async fn handle(&mut self, _message: Ping, _context: &ActorContext<Self>) -> &'static str {
+        "pong"
+    }
+}
+
+#[async_trait]
+impl Handles<Ping> for Broken {
+    type Reply = &'static str;
+
+    async fn handle(&mut self, _message: Ping, _context: &ActorContext<Self>) -> &'static str {
+        "pong"
+    }
+}
+
+#[tokio::test]
+async fn actors_that_panic_while_being_created_are_restarted() {
+    let fragile = actors().start::<Fragile>();
+    assert_eq!(fragile.send(Ping).await, Ok("pong"));
+    assert_eq!(FRAGILE_CREATES.load(Ordering::SeqCst), 2);
+
+    let broken = actors().start_with::<Broken>(ActorOptions {
+        max_restarts: 2,
+        ..ActorOptions::default()
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(matches!(broken.send(Ping).await, Err(ActorError::Stopped(_))));
+    assert!(!broken.is_alive());
+}
+
+static WATCHED_DROPPED: AtomicBool = AtomicBool::new(false);
+
+struct Watched;
+
+impl Actor for Watched {
+    type State = AppState;
+    type Config = ();
+
+    fn create(_context: &ActorContext<Self>) -> Self {
+        Self
+    }
+}
+
+impl Drop for Watched {
+    fn drop(&mut self) {
+        WATCHED_DROPPED.store(true, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl Handles<Ping> for Watched {
+    type Reply = &'static str;
+
+    async fn handle(&mut self, _message: Ping, _context: &ActorContext<Self>) -> &'static str {
+        "pong"
+    }
+}
+
+#[tokio::test]
+async fn actors_stop_once_their_context_and_addresses_are_gone() {
+    let context = context();
+    let watched = Actors::new(context.clone()).start::<Watched>();
+    assert_eq!(watched.send(Ping).await, Ok("pong"));
+
+    drop(watched);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    // The context still holds an address.
+    assert!(!WATCHED_DROPPED.load(Ordering::SeqCst));
+
+    drop(context);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(WATCHED_DROPPED.load(Ordering::SeqCst));
+}
+
+struct Slow;
+
+impl Actor for Slow {
+    type State = AppState;
+    type Config = ();
+
+    fn create(_context: &ActorContext<Self>) -> Self {
+        Self
+    }
+}
+
+struct Nap(Duration);
+
+#[async_trait]
+impl Handles<Nap> for Slow {
+    type Reply = ();
+
+    async fn handle(&mut self, Nap(duration): Nap, _context: &ActorContext<Self>) {
+        tokio::time::sleep(duration).await;
+    }
+}
+
+#[tokio::test]
+async fn full_mailboxes_push_back_on_senders() {
+    let slow = actors().start_with::<Slow>(ActorOptions {
+        mailbox: 1,
+        ..ActorOptions::default()
+    });
+    // One message being handled, one waiting: the mailbox is full.
+    slow.tell(Nap(Duration::from_millis(200))).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    slow.tell(Nap(Duration::ZERO)).await.unwrap();
+
+    assert!(matches!(slow.try_send(Nap(Duration::ZERO)).await, Err(ActorError::Full(_))));
+    assert_eq!(slow.send(Nap(Duration::ZERO)).await, Ok(()));
+}
+
+struct Greeter;
+
+impl Actor for Greeter {
+    type State = AppState;
+    type Config = ();
+
+    fn create(_context: &ActorContext<Self>) -> Self {
+        Self
+    }
+}
+
+struct Greet(&'static str);
+
+#[async_trait]
+impl Handles<Greet> for Greeter {
+    type Reply = String;
+
+    async fn handle(&mut self, Greet(name): Greet, _context: &ActorContext<Self>) -> String {
+        format!("Hello, {name}!")
+    }
+}
+
+async fn greet(greeter: Addr<Greeter>) -> Result<String, ActorError> {
+    greeter.send(Greet("world")).await
+}
+
+#[tokio::test]
+async fn handlers_extract_addresses() {
+    let context = context();
+    let app = Router::new().route("/", get(greet)).with_state(context.clone());
+    let request = || Request::builder().uri("/").body(Body::empty()).unwrap();
+
+    let response = app.clone().oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    Actors::new(context).start::<Greeter>();
+    let response = app.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(&to_bytes(response.into_body(), usize::MAX).await.unwrap()[..], b"Hello, world!");
+
+    // Another application has started no greeter.
+    let other = Router::new().route("/", get(greet)).with_state(self::context());
+    let response = other.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
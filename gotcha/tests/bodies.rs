@@ -0,0 +1,141 @@
+//! Request bodies beyond JSON: forms, raw text and bytes, and `Negotiated<T>` content negotiation.
+#![cfg(feature = "openapi")]
+
+use gotcha::axum::body::{Body, Bytes};
+use gotcha::axum::http::{Request, StatusCode};
+use gotcha::axum::Router;
+use gotcha::negotiate::{Format, Negotiated};
+use gotcha::prelude::*;
+use tower::ServiceExt;
+
+#[derive(Debug, Serialize, Deserialize, Schematic)]
+struct Greeting {
+    name: String,
+}
+
+/// Sign up from an HTML form
+#[api(id = "sign_up")]
+async fn sign_up(Form(greeting): Form<Greeting>) -> String {
+    greeting.name
+}
+
+/// Echo text
+#[api(id = "echo_text")]
+async fn echo_text(body: String) -> String {
+    body
+}
+
+/// Count bytes
+#[api(id = "count_bytes")]
+async fn count_bytes(body: Bytes) -> String {
+    body.len().to_string()
+}
+
+/// Greet in any format
+#[api(id = "greet")]
+async fn greet(greeting: Negotiated<Greeting>) -> Negotiated<Greeting> {
+    greeting.respond(Greeting {
+        name: format!("hello {}", greeting.name),
+    })
+}
+
+/// Show a greeting in any format
+#[api(id = "show")]
+async fn show(format: Format) -> Negotiated<Greeting> {
+    format.respond(Greeting { name: "Ada".to_string() })
+}
+
+struct App;
+
+impl GotchaApp for App {
+    type State = ();
+    type Config = EmptyConfig;
+
+    fn routes(&self, router: GotchaRouter<GotchaContext<Self::State, Self::Config>>) -> GotchaRouter<GotchaContext<Self::State, Self::Config>> {
+        router
+            .post("/form", sign_up)
+            .post("/text", echo_text)
+            .post("/bytes", count_bytes)
+            .post("/greet", greet)
+            .get("/greet", show)
+    }
+
+    async fn state(&self, _config: &ConfigWrapper<Self::Config>) -> GotchaResult<Self::State> {
+        Ok(())
+    }
+}
+
+async fn app() -> Router {
+    let context = GotchaContext::new(ConfigWrapper::default(), ());
+    App.build_router(context).await.unwrap()
+}
+
+async fn send(app: &Router, method: &str, uri: &str, headers: &[(&str, &str)], body: Vec<u8>) -> (StatusCode, Option<String>, Vec<u8>) {
+    let mut request = Request::builder().method(method).uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = app.clone().oneshot(request.body(Body::from(body)).unwrap()).await.unwrap();
+    let status = response.status();
+    let content_type = response.headers().get("content-type").map(|value| value.to_str().unwrap().to_string());
+    let body = gotcha::axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, content_type, body.to_vec())
+}
+
+#[tokio::test]
+async fn negotiated_bodies_follow_content_type_and_accept() {
+    let app = app().await;
+    let json = br#"{"name":"Ada"}"#.to_vec();
+
+    let (status, content_type, body) = send(&app, "POST", "/greet", &[("content-type", "application/json")], json.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+    assert_eq!(body, br#"{"name":"hello Ada"}"#);
+
+    let headers = [("content-type", "application/json"), ("accept", "text/html")];
+    assert_eq!(send(&app, "POST", "/greet", &headers, json.clone()).await.0, StatusCode::NOT_ACCEPTABLE);
+    assert_eq!(
+        send(&app, "POST", "/greet", &[("content-type", "text/plain")], json).await.0,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+    let garbage = b"{".to_vec();
+    assert_eq!(
+        send(&app, "POST", "/greet", &[("content-type", "application/json")], garbage).await.0,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[cfg(all(feature = "msgpack", feature = "cbor"))]
+#[tokio::test]
+async fn binary_formats_round_trip() {
+    let app = app().await;
+    let request = Format::MessagePack.encode(&Greeting { name: "Ada".to_string() }).unwrap();
+
+    let headers = [("content-type", "application/msgpack"), ("accept", "application/cbor")];
+    let (status, content_type, body) = send(&app, "POST", "/greet", &headers, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/cbor"));
+    assert_eq!(Format::Cbor.decode::<Greeting>(&body).unwrap().name, "hello Ada");
+
+    let (_, content_type, _) = send(&app, "GET", "/greet", &[("accept", "application/msgpack")], vec![]).await;
+    assert_eq!(content_type.as_deref(), Some("application/msgpack"));
+}
+
+#[tokio::test]
+async fn every_body_kind_is_documented() {
+    let app = app().await;
+    let (_, _, body) = send(&app, "GET", "/openapi.json", &[], vec![]).await;
+    let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let content = |path: &str, method: &str| spec["paths"][path][method]["requestBody"]["content"].clone();
+
+    assert!(content("/form", "post")["application/x-www-form-urlencoded"]["schema"].is_object());
+    assert_eq!(content("/text", "post")["text/plain"]["schema"]["type"], "string");
+    assert_eq!(content("/bytes", "post")["application/octet-stream"]["schema"]["format"], "binary");
+
+    let negotiated = content("/greet", "post");
+    let responses = &spec["paths"]["/greet"]["get"]["responses"]["200"]["content"];
+    for format in Format::ALL {
+        assert!(negotiated[format.media_type()]["schema"].is_object(), "{format} request");
+        assert!(responses[format.media_type()]["schema"].is_object(), "{format} response");
+    }
+}
@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default)]
 struct TestState {
+    #[allow(dead_code)]
     counter: u32,
 }
 
@@ -0,0 +1,106 @@
+//! Tagged enums map each discriminator value to its variant's own component.
+#![cfg(feature = "openapi")]
+
+use axum::response::{IntoResponse, Response};
+use gotcha::prelude::*;
+use serde_json::json;
+
+/// A payment method.
+#[derive(Schematic, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Payment {
+    Card { number: String },
+    BankTransfer { iban: String },
+    Cash,
+}
+
+/// A change to an order.
+#[derive(Schematic, Serialize, Deserialize)]
+#[serde(tag = "op", content = "value")]
+pub enum Change {
+    Rename { name: String },
+    Quantity(u32),
+    Clear,
+}
+
+/// Why a request failed.
+#[derive(Schematic, Serialize, Deserialize)]
+#[serde(tag = "code")]
+pub enum ApiError {
+    NotFound { resource: String },
+    Conflict { reason: String },
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        unimplemented!()
+    }
+}
+
+/// Pay
+#[api(id = "pay")]
+async fn pay(_payment: Json<Payment>) -> Json<Change> {
+    unimplemented!()
+}
+
+/// Lookup
+#[api(id = "lookup")]
+async fn lookup() -> Result<Json<String>, ApiError> {
+    unimplemented!()
+}
+
+#[test]
+fn internally_tagged_variants_are_mapped_components() {
+    let spec = GotchaRouter::<()>::default().post("/pay", pay).openapi_spec();
+    let schemas = &spec["components"]["schemas"];
+
+    assert_eq!(
+        schemas["Payment"]["discriminator"],
+        json!({
+            "propertyName": "kind",
+            "mapping": {
+                "card": "#/components/schemas/PaymentCard",
+                "bank_transfer": "#/components/schemas/PaymentBankTransfer",
+                "cash": "#/components/schemas/PaymentCash",
+            }
+        })
+    );
+    assert_eq!(schemas["Payment"]["oneOf"][1], json!({ "$ref": "#/components/schemas/PaymentBankTransfer" }));
+    assert_eq!(schemas["PaymentCard"]["title"], "card");
+    assert_eq!(schemas["PaymentCard"]["required"], json!(["kind", "number"]));
+    assert_eq!(schemas["PaymentCash"]["properties"]["kind"]["enum"], json!(["cash"]));
+}
+
+#[test]
+fn adjacently_tagged_variants_are_mapped_components() {
+    let spec = GotchaRouter::<()>::default().post("/pay", pay).openapi_spec();
+    let schemas = &spec["components"]["schemas"];
+
+    assert_eq!(schemas["Change"]["discriminator"]["propertyName"], "op");
+    assert_eq!(schemas["Change"]["discriminator"]["mapping"]["Quantity"], "#/components/schemas/ChangeQuantity");
+    assert_eq!(schemas["ChangeRename"]["properties"]["value"]["required"], json!(["name"]));
+    assert_eq!(schemas["ChangeClear"]["required"], json!(["op"]));
+}
+
+#[test]
+fn error_enums_are_mapped_too() {
+    let spec = GotchaRouter::<()>::default().get("/lookup", lookup).openapi_spec();
+
+    assert_eq!(
+        spec["paths"]["/lookup"]["get"]["responses"]["default"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/ApiError"
+    );
+    assert_eq!(
+        spec["components"]["schemas"]["ApiError"]["discriminator"]["mapping"]["NotFound"],
+        "#/components/schemas/ApiErrorNotFound"
+    );
+    assert_eq!(spec["components"]["schemas"]["ApiErrorConflict"]["required"], json!(["code", "reason"]));
+}
+
+#[test]
+fn inline_schemas_keep_the_branches() {
+    let schema = Payment::generate_schema().schema.to_value();
+
+    assert_eq!(schema["discriminator"], json!({ "propertyName": "kind" }));
+    assert_eq!(schema["oneOf"][0]["title"], "card");
+}
@@ -0,0 +1,125 @@
+//! Events published on a `Messager` reach every `#[subscribe]`r of their type, in order, and every
+//! open `EventStream`.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use gotcha::event::EventStream;
+use gotcha::{subscribe, ConfigWrapper, GotchaContext, Messager, Responder};
+use serde::Serialize;
+
+#[derive(Clone, Default)]
+struct AppState {
+    seen: Arc<Mutex<Vec<u32>>>,
+}
+
+fn messager<S: Clone + Send + Sync + 'static>(state: S) -> Messager<S, ()> {
+    Messager::new(GotchaContext::new(ConfigWrapper::default(), state))
+}
+
+#[derive(Clone)]
+struct Tick(u32);
+
+#[subscribe]
+async fn record(Tick(tick): Tick, context: GotchaContext<AppState, ()>) {
+    // Earlier events take longer, so they would finish last if they were not handled in order.
+    tokio::time::sleep(Duration::from_millis(u64::from(10 - tick))).await;
+    context.state.seen.lock().unwrap().push(tick);
+}
+
+#[subscribe(name = "refuse")]
+async fn refuse(_tick: Tick, _messager: Messager<AppState, ()>) -> Result<(), String> {
+    Err("not today".to_string())
+}
+
+#[subscribe]
+async fn explode(_tick: Tick) {
+    panic!("boom");
+}
+
+#[tokio::test]
+async fn subscribers_receive_events_in_order_despite_failing_neighbours() {
+    let state = AppState::default();
+    let messager = messager(state.clone());
+    for tick in 0..10 {
+        assert_eq!(messager.publish(Tick(tick)), 3);
+    }
+
+    for _ in 0..100 {
+        if state.seen.lock().unwrap().len() == 10 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(*state.seen.lock().unwrap(), (0..10).collect::<Vec<_>>());
+}
+
+#[derive(Clone)]
+struct Signup;
+
+#[subscribe]
+async fn welcome(_signup: Signup, _context: GotchaContext<AppState, ()>) {}
+
+#[tokio::test]
+async fn subscribers_only_receive_events_published_with_their_context() {
+    assert_eq!(messager(()).publish(Signup), 0);
+    assert_eq!(messager(AppState::default()).publish(Signup), 1);
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+struct Price {
+    cents: u64,
+}
+
+#[tokio::test]
+async fn streams_receive_the_events_they_select() {
+    let messager = messager(());
+    let mut prices = EventStream::<Price>::new(messager.context()).filter(|price| price.cents > 100);
+    messager.publish(Price { cents: 50 });
+    messager.publish(Price { cents: 150 });
+    assert_eq!(prices.recv().await, Some(Price { cents: 150 }));
+}
+
+#[derive(Clone, Serialize)]
+struct Quote {
+    symbol: &'static str,
+}
+
+#[tokio::test]
+async fn sse_responses_carry_events_as_json() {
+    let messager = messager(());
+    let response = EventStream::<Quote>::new(messager.context()).into_sse().into_response();
+    messager.publish(Quote { symbol: "GOT" });
+
+    let mut body = response.into_body().into_data_stream();
+    let frame = body.next().await.unwrap().unwrap();
+    assert_eq!(std::str::from_utf8(&frame).unwrap(), "event: Quote\ndata: {\"symbol\":\"GOT\"}\n\n");
+}
+
+#[tokio::test]
+async fn streams_only_receive_events_of_their_own_application() {
+    let (ours, theirs) = (messager(()), messager(()));
+    let mut prices = EventStream::<Price>::new(ours.context());
+    theirs.publish(Price { cents: 1 });
+    ours.clone().publish(Price { cents: 2 });
+    assert_eq!(prices.recv().await, Some(Price { cents: 2 }));
+}
+
+#[derive(Clone)]
+struct Flood;
+
+#[subscribe]
+async fn stall(_flood: Flood) {
+    std::future::pending::<()>().await;
+}
+
+#[tokio::test]
+async fn events_beyond_a_slow_subscribers_queue_are_dropped() {
+    let flooded = messager(());
+    // The worker does not run until the test yields, so all of these wait in its queue.
+    let delivered: usize = (0..300).map(|_| flooded.publish(Flood)).sum();
+    assert_eq!(delivered, 256);
+
+    assert_eq!(messager(()).publish(Flood), 1, "another application has a queue of its own");
+}
@@ -0,0 +1,110 @@
+//! Examples: type-level `#[schematic(example = ..)]` and named `#[api(example(..))]` bodies.
+#![cfg(feature = "openapi")]
+
+use gotcha::prelude::*;
+
+/// A registered user.
+#[derive(Schematic, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[schematic(example = Self::sample())]
+pub struct User {
+    display_name: String,
+    age: u8,
+}
+
+impl User {
+    fn sample() -> Self {
+        User {
+            display_name: "Ada".to_string(),
+            age: 36,
+        }
+    }
+}
+
+#[derive(Schematic, Serialize, Deserialize)]
+#[schematic(example = json!({ "code": 404, "message": "no such user" }), rename = "Problem")]
+pub struct ApiError {
+    code: u16,
+    message: String,
+}
+
+impl Responder for ApiError {
+    fn into_response(self) -> Response {
+        (StatusCode::NOT_FOUND, Json(self)).into_response()
+    }
+}
+
+#[derive(Schematic, Serialize, Deserialize)]
+#[schematic(example = "u-42")]
+pub struct UserId(String);
+
+/// Create a user
+#[api(
+    id = "create_user",
+    example(name = "minimal", summary = "Only the required fields", value = User { display_name: "Grace".into(), age: 45 }),
+    example(name = "raw", value = json!({ "displayName": "Linus", "age": 28 })),
+    example(name = "created", value = User::sample(), response),
+    example(name = "missing", value = json!({ "code": 404, "message": "gone" }), response = "default")
+)]
+async fn create_user(Json(user): Json<User>) -> Result<Json<User>, ApiError> {
+    Ok(Json(user))
+}
+
+/// Look up an id
+#[api(id = "lookup")]
+async fn lookup() -> Json<UserId> {
+    Json(UserId("u-1".to_string()))
+}
+
+/// Broken example
+#[api(id = "broken", example(name = "nothing", value = 1))]
+async fn broken() -> Json<User> {
+    Json(User::sample())
+}
+
+fn spec() -> serde_json::Value {
+    GotchaRouter::<()>::default().post("/users", create_user).get("/ids", lookup).openapi_spec()
+}
+
+#[test]
+fn type_examples_are_serialized_by_serde() {
+    let spec = spec();
+    let user = json!({ "displayName": "Ada", "age": 36 });
+
+    assert_eq!(spec["components"]["schemas"]["User"]["example"], user);
+    assert_eq!(spec["components"]["schemas"]["Problem"]["example"]["code"], 404);
+    let operation = &spec["paths"]["/users"]["post"];
+    assert_eq!(operation["requestBody"]["content"]["application/json"]["example"], user);
+    assert_eq!(operation["responses"]["200"]["content"]["application/json"]["example"], user);
+    assert_eq!(
+        operation["responses"]["default"]["content"]["application/json"]["example"]["message"],
+        "no such user"
+    );
+    assert_eq!(
+        spec["paths"]["/ids"]["get"]["responses"]["200"]["content"]["application/json"]["example"],
+        "u-42"
+    );
+}
+
+#[test]
+fn named_examples_go_on_their_bodies() {
+    let spec = spec();
+    let operation = &spec["paths"]["/users"]["post"];
+
+    let request = &operation["requestBody"]["content"]["application/json"]["examples"];
+    assert_eq!(request["minimal"]["summary"], "Only the required fields");
+    assert_eq!(request["minimal"]["value"], json!({ "displayName": "Grace", "age": 45 }));
+    assert_eq!(request["raw"]["value"]["displayName"], "Linus");
+    assert!(request["created"].is_null(), "response examples stay off the request");
+
+    let ok = &operation["responses"]["200"]["content"]["application/json"]["examples"];
+    assert_eq!(ok["created"]["value"]["displayName"], "Ada");
+    let error = &operation["responses"]["default"]["content"]["application/json"]["examples"];
+    assert_eq!(error["missing"]["value"]["message"], "gone");
+}
+
+#[test]
+#[should_panic(expected = "example `nothing` of operation `broken` is for a request body the operation does not have")]
+fn an_example_without_its_body_fails_assembly() {
+    GotchaRouter::<()>::default().get("/broken", broken).openapi_spec();
+}
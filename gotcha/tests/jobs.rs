@@ -0,0 +1,286 @@
+//! Jobs enqueued on a `JobQueue` are stored, run by a bounded pool of workers, retried and
+//! dead-lettered.
+#![cfg(feature = "job")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use gotcha::job::{FileStore, JobError, JobOptions, JobQueue, JobRecord, JobStore, MemoryStore};
+use gotcha::retry::RetryPolicy;
+use gotcha::{ConfigWrapper, GotchaContext, Message, Messager};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Default)]
+struct AppState {
+    handled: Arc<AtomicUsize>,
+    running: Arc<AtomicUsize>,
+    most_running: Arc<AtomicUsize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Work {
+    millis: u64,
+}
+
+#[async_trait]
+impl Message<AppState, ()> for Work {
+    type Output = ();
+
+    async fn handle(self, messager: Messager<AppState, ()>) {
+        let state = messager.state();
+        let running = state.running.fetch_add(1, Ordering::SeqCst) + 1;
+        state.most_running.fetch_max(running, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(self.millis)).await;
+        state.running.fetch_sub(1, Ordering::SeqCst);
+        state.handled.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Fail;
+
+#[async_trait]
+impl Message<AppState, ()> for Fail {
+    type Output = Result<(), String>;
+
+    async fn handle(self, messager: Messager<AppState, ()>) -> Self::Output {
+        messager.state().handled.fetch_add(1, Ordering::SeqCst);
+        Err("mailbox full".to_string())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Unregistered;
+
+#[async_trait]
+impl Message<AppState, ()> for Unregistered {
+    type Output = ();
+
+    async fn handle(self, _messager: Messager<AppState, ()>) {}
+}
+
+fn context(state: AppState) -> GotchaContext<AppState, ()> {
+    GotchaContext::new(ConfigWrapper::default(), state)
+}
+
+fn queue(store: impl JobStore, options: JobOptions) -> JobQueue<AppState, ()> {
+    JobQueue::with_options(store, options).register::<Work>("work").register::<Fail>("fail")
+}
+
+fn fast(concurrency: usize, max_retries: u32) -> JobOptions {
+    JobOptions {
+        concurrency,
+        retry: RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_millis(10),
+            ..RetryPolicy::default()
+        },
+        poll_interval: Duration::from_millis(20),
+        ..JobOptions::default()
+    }
+}
+
+async fn wait_for(what: impl Fn() -> bool) {
+    for _ in 0..200 {
+        if what() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("timed out");
+}
+
+#[tokio::test]
+async fn workers_run_jobs_up_to_the_concurrency_limit() {
+    let state = AppState::default();
+    let jobs = queue(MemoryStore::new(), fast(2, 0));
+    jobs.start(context(state.clone())).await.unwrap();
+    for _ in 0..6 {
+        jobs.enqueue(Work { millis: 30 }).await.unwrap();
+    }
+
+    wait_for(|| state.handled.load(Ordering::SeqCst) == 6).await;
+    assert_eq!(state.most_running.load(Ordering::SeqCst), 2);
+    assert_eq!(jobs.pending().await.unwrap(), 0);
+    jobs.shutdown().await;
+}
+
+#[tokio::test]
+async fn failed_jobs_are_retried_then_dead_lettered() {
+    let state = AppState::default();
+    let jobs = queue(MemoryStore::new(), fast(1, 2));
+    jobs.start(context(state.clone())).await.unwrap();
+    let id = jobs.enqueue(Fail).await.unwrap();
+
+    wait_for(|| state.handled.load(Ordering::SeqCst) == 3).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(state.handled.load(Ordering::SeqCst), 3);
+
+    let dead = jobs.dead_letters().await.unwrap();
+    assert_eq!(dead.len(), 1);
+    assert_eq!((dead[0].id, dead[0].kind.as_str(), dead[0].attempts), (id, "fail", 3));
+    assert_eq!(dead[0].last_error.as_deref(), Some("mailbox full"));
+    assert_eq!(jobs.pending().await.unwrap(), 0);
+    jobs.shutdown().await;
+}
+
+#[tokio::test]
+async fn delayed_jobs_wait_for_their_time() {
+    let state = AppState::default();
+    let jobs = queue(MemoryStore::new(), fast(1, 0));
+    jobs.start(context(state.clone())).await.unwrap();
+    jobs.enqueue_in(Work { millis: 0 }, Duration::from_millis(200)).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(state.handled.load(Ordering::SeqCst), 0);
+    assert_eq!(jobs.pending().await.unwrap(), 1);
+    wait_for(|| state.handled.load(Ordering::SeqCst) == 1).await;
+    jobs.shutdown().await;
+}
+
+#[tokio::test]
+async fn full_queues_and_unregistered_messages_are_refused() {
+    let jobs = queue(
+        MemoryStore::new(),
+        JobOptions {
+            capacity: Some(1),
+            ..JobOptions::default()
+        },
+    );
+    jobs.enqueue(Work { millis: 0 }).await.unwrap();
+    assert!(matches!(jobs.enqueue(Work { millis: 0 }).await, Err(JobError::Full { capacity: 1 })));
+    assert!(matches!(jobs.enqueue(Unregistered).await, Err(JobError::Unregistered(name)) if name.ends_with("Unregistered")));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_enqueues_stop_at_the_capacity() {
+    let dir = std::env::temp_dir().join(format!("gotcha-jobs-capacity-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let jobs = queue(
+        FileStore::new(dir.join("jobs.json")),
+        JobOptions {
+            capacity: Some(5),
+            ..JobOptions::default()
+        },
+    );
+
+    let enqueues: Vec<_> = (0..20)
+        .map(|_| {
+            let jobs = jobs.clone();
+            tokio::spawn(async move { jobs.enqueue(Work { millis: 0 }).await })
+        })
+        .collect();
+    let mut enqueued = 0;
+    for enqueue in enqueues {
+        match enqueue.await.unwrap() {
+            Ok(_) => enqueued += 1,
+            Err(error) => assert!(matches!(error, JobError::Full { capacity: 5 })),
+        }
+    }
+    assert_eq!(enqueued, 5);
+    assert_eq!(jobs.pending().await.unwrap(), 5);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn file_stores_keep_jobs_across_restarts() {
+    let dir = std::env::temp_dir().join(format!("gotcha-jobs-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.join("jobs.json");
+
+    // Enqueued by a process that never started its workers.
+    queue(FileStore::new(&path), fast(1, 0)).enqueue(Work { millis: 0 }).await.unwrap();
+    // Claimed by a process that stopped while running it.
+    let store = FileStore::new(&path);
+    store
+        .push(
+            JobRecord {
+                id: Uuid::new_v4(),
+                kind: "work".to_string(),
+                payload: serde_json::json!({ "millis": 0 }),
+                enqueued_at: Utc::now(),
+                run_at: Utc::now(),
+                attempts: 0,
+                last_error: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(store.claim(Utc::now()).await.unwrap().is_some());
+    assert_eq!(store.pending().await.unwrap(), 1);
+
+    let state = AppState::default();
+    let jobs = queue(FileStore::new(&path), fast(1, 0));
+    jobs.start(context(state.clone())).await.unwrap();
+    wait_for(|| state.handled.load(Ordering::SeqCst) == 2).await;
+    jobs.shutdown().await;
+    assert_eq!(store.pending().await.unwrap(), 0);
+    assert!(store.next_run_at().await.unwrap().is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn file_stores_beside_each_other_keep_their_own_files() {
+    let dir = std::env::temp_dir().join(format!("gotcha-jobs-beside-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let json = queue(FileStore::new(dir.join("jobs.json")), JobOptions::default());
+    let yaml = queue(FileStore::new(dir.join("jobs.yaml")), JobOptions::default());
+
+    let enqueues: Vec<_> = (0..20)
+        .map(|n| {
+            let jobs = if n % 2 == 0 { json.clone() } else { yaml.clone() };
+            tokio::spawn(async move { jobs.enqueue(Work { millis: 0 }).await })
+        })
+        .collect();
+    for enqueue in enqueues {
+        enqueue.await.unwrap().unwrap();
+    }
+    assert_eq!(json.pending().await.unwrap(), 10);
+    assert_eq!(yaml.pending().await.unwrap(), 10);
+    assert!(dir.join("jobs.json.lock").exists());
+    assert!(dir.join("jobs.yaml.lock").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn delays_past_the_end_of_time_are_refused_and_backoffs_clamped() {
+    let jobs = queue(MemoryStore::new(), fast(1, 0));
+    assert!(matches!(jobs.enqueue_in(Work { millis: 0 }, Duration::MAX).await, Err(JobError::Delay(Duration::MAX))));
+    assert_eq!(jobs.pending().await.unwrap(), 0);
+
+    let dir = std::env::temp_dir().join(format!("gotcha-jobs-backoff-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.join("jobs.json");
+    let state = AppState::default();
+    let jobs = queue(
+        FileStore::new(&path),
+        JobOptions {
+            retry: RetryPolicy {
+                max_retries: 1,
+                initial_backoff: Duration::MAX,
+                max_backoff: Duration::MAX,
+                ..RetryPolicy::default()
+            },
+            ..fast(1, 1)
+        },
+    );
+    jobs.start(context(state.clone())).await.unwrap();
+    jobs.enqueue(Fail).await.unwrap();
+
+    wait_for(|| state.handled.load(Ordering::SeqCst) == 1).await;
+    let store = FileStore::new(&path);
+    for _ in 0..200 {
+        if store.next_run_at().await.unwrap().is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(store.next_run_at().await.unwrap(), Some(DateTime::<Utc>::MAX_UTC));
+    jobs.shutdown().await;
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,70 @@
+//! Documenting types the derive cannot see: `SchemaBuilder`, `#[schematic(with)]` and
+//! `schematic_remote!`.
+#![cfg(feature = "openapi")]
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use gotcha::prelude::*;
+use gotcha::{schematic_remote, EnhancedSchema, SchemaBuilder};
+
+/// `PathBuf` serializes as a plain string.
+fn path_schema() -> EnhancedSchema {
+    SchemaBuilder::string()
+        .format("path")
+        .description("A file path")
+        .example("/var/log/app.log")
+        .build()
+}
+
+schematic_remote! {
+    /// A point in time, as serde writes it.
+    pub fn system_time for SystemTime as SystemTime {
+        secs_since_epoch: u64,
+        #[schematic(description = "Always below one billion")]
+        nanos_since_epoch: u32,
+    }
+}
+
+#[derive(Schematic, Serialize, Deserialize)]
+pub struct Upload {
+    #[schematic(with = "path_schema")]
+    path: PathBuf,
+    #[schematic(with = "system_time")]
+    modified: SystemTime,
+    /// Every earlier version.
+    #[schematic(with = "history")]
+    history: Vec<SystemTime>,
+}
+
+fn history() -> EnhancedSchema {
+    SchemaBuilder::array(system_time()).max_items(10).build()
+}
+
+/// Latest upload
+#[api(id = "latest")]
+async fn latest() -> Json<Upload> {
+    unimplemented!()
+}
+
+#[test]
+fn fields_use_their_schema_functions() {
+    let spec = GotchaRouter::<()>::default().get("/latest", latest).openapi_spec();
+    let schemas = &spec["components"]["schemas"];
+
+    let upload = &schemas["Upload"];
+    assert_eq!(
+        upload["properties"]["path"],
+        json!({ "type": "string", "format": "path", "description": "A file path", "example": "/var/log/app.log" })
+    );
+    assert_eq!(upload["properties"]["modified"]["$ref"], "#/components/schemas/SystemTime");
+    assert_eq!(upload["properties"]["history"]["items"]["$ref"], "#/components/schemas/SystemTime");
+    assert_eq!(upload["properties"]["history"]["maxItems"], 10);
+    assert_eq!(upload["properties"]["history"]["description"], "Every earlier version.");
+    assert_eq!(upload["required"], json!(["path", "modified", "history"]));
+
+    let time = &schemas["SystemTime"];
+    assert_eq!(time["description"], "A point in time, as serde writes it.");
+    assert_eq!(time["properties"]["nanos_since_epoch"]["description"], "Always below one billion");
+    assert_eq!(time["required"], json!(["secs_since_epoch", "nanos_since_epoch"]));
+}
@@ -0,0 +1,183 @@
+//! `MessageLayer`s wrap every message a `Messager` sends or spawns.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use gotcha::message::{Dispatch, MessageLayer, MessageLayers, MetricsLayer, Next, Rejected, TracingLayer};
+use gotcha::{ConfigWrapper, GotchaContext, Message, Messager};
+
+/// Records what it wraps, as `"<name> <message> <mode> <outcome>"`.
+#[derive(Clone)]
+struct Record {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl MessageLayer for Record {
+    async fn handle(&self, dispatch: Dispatch, next: Next<'_>) -> Result<(), String> {
+        self.log.lock().unwrap().push(format!("{} enter", self.name));
+        let result = next.run().await;
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{} {} {:?} {:?}", self.name, dispatch.message, dispatch.mode, result));
+        result
+    }
+}
+
+struct Divide(u32, u32);
+
+#[async_trait]
+impl Message<(), ()> for Divide {
+    type Output = Result<u32, String>;
+
+    async fn handle(self, _messager: Messager<(), ()>) -> Self::Output {
+        match self.1 {
+            0 => Err("division by zero".to_string()),
+            divisor => Ok(self.0 / divisor),
+        }
+    }
+
+    fn failure(output: &Self::Output) -> Option<String> {
+        output.as_ref().err().cloned()
+    }
+}
+
+struct Explode;
+
+#[async_trait]
+impl Message<(), ()> for Explode {
+    type Output = ();
+
+    async fn handle(self, _messager: Messager<(), ()>) {
+        panic!("boom");
+    }
+}
+
+fn context<S: Clone + Send + Sync + 'static>(state: S) -> GotchaContext<S, ()> {
+    GotchaContext::new(ConfigWrapper::default(), state)
+}
+
+#[tokio::test]
+async fn layers_wrap_sent_and_spawned_messages_outermost_first() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let layers = MessageLayers::new()
+        .layer(Record {
+            name: "outer",
+            log: log.clone(),
+        })
+        .layer(TracingLayer)
+        .layer(Record {
+            name: "inner",
+            log: log.clone(),
+        });
+    let messager = Messager::with_layers(context(()), layers);
+
+    assert_eq!(messager.send(Divide(6, 3)).await, Ok(2));
+    assert_eq!(messager.send(Divide(1, 0)).await, Err("division by zero".to_string()));
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "outer enter",
+            "inner enter",
+            "inner Divide Send Ok(())",
+            "outer Divide Send Ok(())",
+            "outer enter",
+            "inner enter",
+            "inner Divide Send Err(\"division by zero\")",
+            "outer Divide Send Err(\"division by zero\")",
+        ]
+    );
+
+    log.lock().unwrap().clear();
+    messager.spawn(Explode);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(*log.lock().unwrap(), ["outer enter", "inner enter"]);
+}
+
+#[tokio::test]
+async fn metrics_count_failures_and_panics() {
+    let metrics = MetricsLayer::new();
+    let messager = Messager::with_layers(context(()), MessageLayers::new().layer(metrics.clone()));
+
+    messager.send(Divide(4, 2)).await.unwrap();
+    messager.send(Divide(4, 0)).await.unwrap_err();
+    let sent = tokio::spawn({
+        let messager = messager.clone();
+        async move { messager.send(Explode).await }
+    });
+    assert!(sent.await.unwrap_err().is_panic());
+
+    let snapshot = metrics.snapshot();
+    let divide = snapshot["Divide"];
+    assert_eq!((divide.count, divide.failures, divide.panics), (2, 1, 0));
+    let explode = snapshot["Explode"];
+    assert_eq!((explode.count, explode.failures, explode.panics), (1, 1, 1));
+}
+
+struct Ping;
+
+#[async_trait]
+impl Message<(), ()> for Ping {
+    type Output = ();
+
+    async fn handle(self, _messager: Messager<(), ()>) {}
+}
+
+#[tokio::test]
+async fn a_contexts_layers_apply_to_its_messagers_only() {
+    let metrics = MetricsLayer::new();
+    let app = context(()).with_message_layers(MessageLayers::new().layer(metrics.clone()));
+
+    Messager::new(app.clone()).send(Ping).await;
+    Messager::new(context(())).send(Ping).await;
+    assert_eq!(metrics.snapshot()["Ping"].count, 1, "another application has no layers");
+}
+
+tokio::task_local! {
+    static USER: &'static str;
+}
+
+/// Rejects messages sent without a signed-in user, and passes the user on to the handler.
+struct RequireUser;
+
+#[async_trait]
+impl MessageLayer for RequireUser {
+    async fn handle(&self, _dispatch: Dispatch, next: Next<'_>) -> Result<(), String> {
+        match USER.try_with(|user| *user) {
+            Ok(user) => USER.scope(user, next.run()).await,
+            Err(_) => Err("no signed-in user".to_string()),
+        }
+    }
+}
+
+struct Whoami;
+
+#[async_trait]
+impl Message<(), ()> for Whoami {
+    type Output = String;
+
+    async fn handle(self, _messager: Messager<(), ()>) -> String {
+        USER.with(|user| user.to_string())
+    }
+}
+
+#[tokio::test]
+async fn layers_that_skip_the_message_reject_it() {
+    let messager = Messager::new(context(()).with_message_layers(MessageLayers::new().layer(RequireUser)));
+
+    assert_eq!(USER.scope("ada", messager.send(Whoami)).await, "ada");
+    assert_eq!(USER.scope("ada", messager.try_send(Whoami)).await, Ok("ada".to_string()));
+    assert_eq!(
+        messager.try_send(Whoami).await,
+        Err(Rejected {
+            message: "Whoami",
+            reason: "no signed-in user".to_string()
+        })
+    );
+
+    let sent = tokio::spawn(async move { messager.send(Whoami).await });
+    assert!(sent.await.unwrap_err().is_panic(), "send panics on a rejection");
+}
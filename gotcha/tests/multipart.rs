@@ -0,0 +1,162 @@
+//! `TypedMultipart<T>`: parts read into a struct, per-part limits and content types, and the
+//! documented request body.
+#![cfg(feature = "openapi")]
+
+use gotcha::axum::body::Body;
+use gotcha::axum::http::{Request, StatusCode};
+use gotcha::axum::Router;
+use gotcha::multipart::{MultipartForm, TypedMultipart, UploadedFile};
+use gotcha::prelude::*;
+use tower::ServiceExt;
+
+/// A profile update with an avatar.
+#[derive(MultipartForm, Schematic)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+struct Upload {
+    display_name: String,
+    age: Option<u8>,
+    tags: Vec<String>,
+    #[multipart(limit = 8, content_type = "image/png", content_type = "image/jpeg")]
+    avatar: UploadedFile,
+    #[multipart(temp_file)]
+    attachment: Option<UploadedFile>,
+}
+
+/// Upload a profile
+#[api(id = "upload")]
+async fn upload(TypedMultipart(form): TypedMultipart<Upload>) -> String {
+    let attachment = match &form.attachment {
+        Some(file) => {
+            let path = file.path().expect("streamed to a temporary file");
+            format!("{} bytes on disk", std::fs::metadata(path).unwrap().len())
+        }
+        None => "none".to_string(),
+    };
+    format!(
+        "{} {:?} {:?} {}:{} {}",
+        form.display_name,
+        form.age,
+        form.tags,
+        form.avatar.file_name().unwrap_or_default(),
+        String::from_utf8_lossy(form.avatar.bytes().unwrap()),
+        attachment
+    )
+}
+
+struct App;
+
+impl GotchaApp for App {
+    type State = ();
+    type Config = EmptyConfig;
+
+    fn routes(&self, router: GotchaRouter<GotchaContext<Self::State, Self::Config>>) -> GotchaRouter<GotchaContext<Self::State, Self::Config>> {
+        router.post("/upload", upload)
+    }
+
+    async fn state(&self, _config: &ConfigWrapper<Self::Config>) -> GotchaResult<Self::State> {
+        Ok(())
+    }
+}
+
+async fn app() -> Router {
+    let context = GotchaContext::new(ConfigWrapper::default(), ());
+    App.build_router(context).await.unwrap()
+}
+
+const BOUNDARY: &str = "X-BOUNDARY";
+
+/// A part: its name, file name, content type and contents.
+type Part<'a> = (&'a str, Option<&'a str>, Option<&'a str>, &'a str);
+
+async fn post(app: &Router, parts: &[Part<'_>]) -> (StatusCode, String) {
+    let mut body = String::new();
+    for (name, file_name, content_type, contents) in parts {
+        body.push_str(&format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\""));
+        if let Some(file_name) = file_name {
+            body.push_str(&format!("; filename=\"{file_name}\""));
+        }
+        body.push_str("\r\n");
+        if let Some(content_type) = content_type {
+            body.push_str(&format!("Content-Type: {content_type}\r\n"));
+        }
+        body.push_str(&format!("\r\n{contents}\r\n"));
+    }
+    body.push_str(&format!("--{BOUNDARY}--\r\n"));
+    let request = Request::builder()
+        .method("POST")
+        .uri("/upload")
+        .header("content-type", format!("multipart/form-data; boundary={BOUNDARY}"))
+        .body(Body::from(body))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = gotcha::axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+const AVATAR: Part<'static> = ("avatar", Some("me.png"), Some("image/png"), "PNG");
+
+#[tokio::test]
+async fn parts_fill_the_struct() {
+    let app = app().await;
+    let (status, body) = post(
+        &app,
+        &[
+            ("displayName", None, None, "Ada"),
+            ("tags", None, None, "a"),
+            AVATAR,
+            ("tags", None, None, "b"),
+            ("unknown", None, None, "skipped"),
+            ("attachment", Some("notes.txt"), None, "on disk"),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, r#"Ada None ["a", "b"] me.png:PNG 7 bytes on disk"#);
+}
+
+#[tokio::test]
+async fn bad_parts_are_rejected() {
+    let app = app().await;
+    let name = ("displayName", None, None, "Ada");
+
+    assert_eq!(post(&app, &[AVATAR]).await.0, StatusCode::UNPROCESSABLE_ENTITY, "displayName is missing");
+    assert_eq!(
+        post(&app, &[name, ("age", None, None, "old"), AVATAR]).await.0,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+
+    let too_large = ("avatar", Some("me.png"), Some("image/png"), "far too large");
+    let (status, body) = post(&app, &[name, too_large]).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert!(body.contains(r#""field":"avatar""#), "{body}");
+
+    let gif = ("avatar", Some("me.gif"), Some("image/gif"), "GIF");
+    assert_eq!(post(&app, &[name, gif]).await.0, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn request_body_lists_every_part() {
+    let app = app().await;
+    let request = Request::builder().uri("/openapi.json").body(Body::empty()).unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let body = gotcha::axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let content = &spec["paths"]["/upload"]["post"]["requestBody"]["content"]["multipart/form-data"];
+    let schema = match content["schema"]["$ref"].as_str() {
+        Some(reference) => &spec["components"]["schemas"][reference.trim_start_matches("#/components/schemas/")],
+        None => &content["schema"],
+    };
+    let properties = &schema["properties"];
+    assert_eq!(properties["displayName"]["type"], "string");
+    assert_eq!(properties["avatar"]["format"], "binary");
+    assert_eq!(properties["attachment"]["format"], "binary");
+    assert_eq!(properties["tags"]["type"], "array");
+
+    let encoding = &content["encoding"];
+    assert_eq!(encoding["avatar"]["contentType"], "image/png, image/jpeg");
+    assert_eq!(encoding["attachment"]["contentType"], "application/octet-stream");
+    assert!(encoding["displayName"].is_null());
+}
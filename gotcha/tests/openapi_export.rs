@@ -0,0 +1,131 @@
+//! Assembling the spec without serving it, and keeping an exported copy on disk up to date.
+#![cfg(feature = "openapi")]
+
+use gotcha::openapi::write_spec;
+use gotcha::prelude::*;
+
+#[derive(Schematic, Serialize, Deserialize)]
+pub struct Pet {
+    name: String,
+}
+
+/// Fetch a pet
+#[api(id = "get_pet", group = "pets")]
+async fn get_pet() -> Json<Pet> {
+    unimplemented!()
+}
+
+fn routes<S: Clone + Send + Sync + 'static>(router: GotchaRouter<S>) -> GotchaRouter<S> {
+    router.get("/pets/{id}", get_pet)
+}
+
+struct App;
+
+impl GotchaApp for App {
+    type State = ();
+    type Config = EmptyConfig;
+
+    fn routes(&self, router: GotchaRouter<GotchaContext<Self::State, Self::Config>>) -> GotchaRouter<GotchaContext<Self::State, Self::Config>> {
+        routes(router)
+    }
+
+    async fn state(&self, _config: &ConfigWrapper<Self::Config>) -> GotchaResult<Self::State> {
+        Ok(())
+    }
+}
+
+/// An app whose configuration retitles the spec.
+struct Configured;
+
+impl GotchaApp for Configured {
+    type State = ();
+    type Config = EmptyConfig;
+
+    async fn config(&self) -> GotchaResult<ConfigWrapper<Self::Config>> {
+        let mut config = ConfigWrapper::<EmptyConfig>::default();
+        config.server.openapi = Some(gotcha::openapi::ApiInfo::new("Configured API", "2.0.0"));
+        Ok(config)
+    }
+
+    fn routes(&self, router: GotchaRouter<GotchaContext<Self::State, Self::Config>>) -> GotchaRouter<GotchaContext<Self::State, Self::Config>> {
+        routes(router)
+    }
+
+    async fn state(&self, _config: &ConfigWrapper<Self::Config>) -> GotchaResult<Self::State> {
+        Ok(())
+    }
+}
+
+/// A fresh directory per test, so parallel tests never share a file.
+fn scratch_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("gotcha-openapi-export-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn router_app_and_builder_assemble_the_same_spec() {
+    let from_router = routes(GotchaRouter::<()>::default()).openapi_spec();
+    let from_app = App.openapi_spec().await.unwrap();
+    let from_builder = Gotcha::new().routes(routes).openapi_spec();
+
+    assert!(from_router["paths"]["/pets/{id}"]["get"].is_object(), "route is documented: {from_router}");
+    assert_eq!(from_router, from_app);
+    assert_eq!(from_router, from_builder);
+}
+
+#[tokio::test]
+async fn write_spec_reports_and_fixes_a_stale_file() {
+    let spec = App.openapi_spec().await.unwrap();
+    let path = scratch_dir("stale").join("openapi.yaml");
+
+    assert!(write_spec(&path, &spec).unwrap(), "a missing file is written");
+    assert!(!write_spec(&path, &spec).unwrap(), "an up-to-date file is left alone");
+
+    std::fs::write(&path, "openapi: 3.0.0\n").unwrap();
+    assert!(write_spec(&path, &spec).unwrap(), "a stale file is reported");
+    let reread: serde_json::Value = serde_yaml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(reread, spec, "and regenerated");
+}
+
+#[tokio::test]
+async fn write_spec_picks_the_format_from_the_extension() {
+    let spec = App.openapi_spec().await.unwrap();
+    let path = scratch_dir("json").join("openapi.json");
+
+    write_spec(&path, &spec).unwrap();
+    let reread: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(reread, spec);
+}
+
+#[tokio::test]
+async fn exported_specs_apply_the_configuration_like_the_served_one() {
+    use gotcha::axum::body::{to_bytes, Body};
+    use gotcha::axum::http::Request;
+    use tower::ServiceExt;
+
+    let spec = Configured.openapi_spec().await.unwrap();
+    assert_eq!(spec["info"]["title"], "Configured API");
+    assert_eq!(spec["info"]["version"], "2.0.0");
+
+    let config = Configured.config().await.unwrap();
+    let router = Configured.build_router(GotchaContext::new(config.clone(), ())).await.unwrap();
+    let response = router.oneshot(Request::builder().uri("/openapi.json").body(Body::empty()).unwrap()).await.unwrap();
+    let served: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(served, spec);
+
+    let from_builder = Gotcha::new().config(config).routes(routes).openapi_spec();
+    assert_eq!(from_builder, spec);
+}
+
+#[tokio::test]
+async fn the_info_block_defaults_to_the_handlers_package() {
+    let spec = App.openapi_spec().await.unwrap();
+    assert_eq!(spec["info"]["title"], env!("CARGO_PKG_NAME"));
+    assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(spec["info"]["description"], env!("CARGO_PKG_DESCRIPTION"));
+    assert_eq!(spec["info"]["license"]["name"], env!("CARGO_PKG_LICENSE"));
+
+    let without_handlers = GotchaRouter::<()>::default().openapi_spec();
+    assert_eq!(without_handlers["info"]["title"], "Gotcha", "nothing records a package without an #[api] handler");
+}
@@ -0,0 +1,97 @@
+//! The same routes rendered as OpenAPI 3.0 and 3.1, compared against committed snapshots.
+//!
+//! The types are the enum and flatten shapes the other fixtures cover — tagged, adjacently tagged,
+//! untagged and external enums, and a struct flattening one — since those are where the two
+//! versions differ most (`nullable`, single-value `enum`, `$ref` with siblings).
+#![cfg(feature = "openapi")]
+
+use std::collections::HashMap;
+
+use assert_json_diff::assert_json_eq;
+use gotcha::axum::http::Method;
+use gotcha::openapi::{generate_openapi, version, OpenApiVersion, Operable};
+use gotcha::{api, Json, Schematic};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Schematic)]
+#[serde(tag = "type")]
+pub enum Status {
+    Active { since: String },
+    Inactive { reason: Option<String> },
+}
+
+#[derive(Serialize, Deserialize, Schematic)]
+#[serde(tag = "kind", content = "data")]
+pub enum Command {
+    Start { delay: u32 },
+    Stop,
+}
+
+#[derive(Serialize, Deserialize, Schematic)]
+#[serde(untagged)]
+pub enum Event {
+    Message { text: String },
+    Number(i32),
+}
+
+#[derive(Serialize, Deserialize, Schematic)]
+pub enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+#[derive(Serialize, Deserialize, Schematic)]
+pub struct Account {
+    #[schematic(example = "ada")]
+    name: String,
+    /// The account's current standing.
+    #[serde(flatten)]
+    status: Status,
+    nickname: Option<String>,
+    last_event: Option<Event>,
+}
+
+#[derive(Serialize, Deserialize, Schematic)]
+pub struct Dashboard {
+    account: Account,
+    pending: Vec<Command>,
+    shape: Shape,
+}
+
+#[api(id = "get_dashboard")]
+async fn get_dashboard(_body: Json<Command>) -> Json<Dashboard> {
+    unimplemented!()
+}
+
+fn operable<H, T>(_handler: H) -> &'static Operable
+where
+    H: gotcha::axum::handler::Handler<T, ()>,
+    T: 'static,
+{
+    gotcha::router::extract_operable::<H, T, ()>().expect("handler is registered")
+}
+
+fn render(version: OpenApiVersion) -> serde_json::Value {
+    let mut operables = HashMap::new();
+    operables.insert(("/dashboard".to_string(), Method::POST), operable(get_dashboard));
+    version::render(&generate_openapi(operables), version)
+}
+
+#[test]
+fn openapi_3_0_matches_snapshot() {
+    let expected: serde_json::Value = serde_json::from_str(include_str!("snapshots/openapi_3_0.json")).unwrap();
+    assert_json_eq!(render(OpenApiVersion::V3_0), expected);
+}
+
+#[test]
+fn openapi_3_1_matches_snapshot() {
+    let expected: serde_json::Value = serde_json::from_str(include_str!("snapshots/openapi_3_1.json")).unwrap();
+    assert_json_eq!(render(OpenApiVersion::V3_1), expected);
+}
+
+#[test]
+fn openapi_3_1_has_no_3_0_only_keywords() {
+    let document = render(OpenApiVersion::V3_1).to_string();
+    assert!(!document.contains("\"nullable\""), "3.1 has no `nullable`: {document}");
+    assert!(!document.contains("\"example\""), "3.1 schemas use `examples`: {document}");
+}
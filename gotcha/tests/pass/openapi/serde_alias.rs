@@ -0,0 +1,24 @@
+//! `#[serde(alias = "...")]` is only an extra name accepted on input: the schema keeps
+//! documenting the field under its serialized name.
+
+use assert_json_diff::assert_json_eq;
+use gotcha::Schematic;
+use serde::Deserialize;
+
+#[derive(Schematic, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Login {
+    #[serde(alias = "user", alias = "login")]
+    user_name: String,
+}
+
+fn main() {
+    let schema = serde_json::to_value(Login::generate_schema().schema).unwrap();
+    let props = schema["properties"].as_object().unwrap();
+
+    assert_eq!(props.keys().collect::<Vec<_>>(), ["userName"]);
+    assert_eq!(schema["required"], serde_json::json!(["userName"]));
+
+    let expected: serde_json::Value = serde_json::from_str(include_str!("serde_alias.json")).unwrap();
+    assert_json_eq!(schema, expected);
+}
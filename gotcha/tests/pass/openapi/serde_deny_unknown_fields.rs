@@ -0,0 +1,28 @@
+//! `#[serde(deny_unknown_fields)]` rejects undeclared properties, documented as
+//! `additionalProperties: false`.
+
+use assert_json_diff::assert_json_eq;
+use gotcha::Schematic;
+use serde::Deserialize;
+
+#[derive(Schematic, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Strict {
+    name: String,
+}
+
+#[derive(Schematic, Deserialize)]
+struct Lenient {
+    name: String,
+}
+
+fn main() {
+    let strict = serde_json::to_value(Strict::generate_schema().schema).unwrap();
+    assert_eq!(strict["additionalProperties"], false);
+
+    let lenient = serde_json::to_value(Lenient::generate_schema().schema).unwrap();
+    assert!(lenient.get("additionalProperties").is_none());
+
+    let expected: serde_json::Value = serde_json::from_str(include_str!("serde_deny_unknown_fields.json")).unwrap();
+    assert_json_eq!(strict, expected);
+}
@@ -0,0 +1,82 @@
+//! `#[serde(from / try_from = "...")]` and `#[serde(into = "...")]` document the type each side
+//! actually goes through: requests are read through `from`, responses written through `into`.
+//! A side without a conversion keeps the type's own shape, and so does a component shared by
+//! both sides, which only request and response components documented separately avoid.
+
+use assert_json_diff::assert_json_eq;
+use gotcha::Schematic;
+use gotcha_core::registry::{self, Direction};
+use serde::{Deserialize, Serialize};
+
+/// Sent and received as a plain string.
+#[derive(Schematic, Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+struct Slug {
+    value: String,
+}
+
+impl TryFrom<String> for Slug {
+    type Error = &'static str;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(Slug { value })
+    }
+}
+
+impl From<Slug> for String {
+    fn from(slug: Slug) -> String {
+        slug.value
+    }
+}
+
+/// Accepts a bare count, answers with the full object.
+#[derive(Schematic, Serialize, Deserialize)]
+#[serde(from = "u32")]
+struct Quota {
+    limit: u32,
+    used: u32,
+}
+
+impl From<u32> for Quota {
+    fn from(limit: u32) -> Self {
+        Quota { limit, used: 0 }
+    }
+}
+
+/// Holds both, so it is documented once per side.
+#[derive(Schematic, Serialize, Deserialize)]
+struct Account {
+    slug: Slug,
+    quota: Quota,
+}
+
+fn schema_of(direction: Option<Direction>, build: fn() -> gotcha_core::EnhancedSchema) -> serde_json::Value {
+    let schema = match direction {
+        Some(direction) => registry::try_collect_by_direction(|| registry::directed(direction, build)).unwrap().0,
+        None => build(),
+    };
+    serde_json::to_value(schema.schema).unwrap()
+}
+
+fn main() {
+    for direction in [None, Some(Direction::Request), Some(Direction::Response)] {
+        assert_eq!(schema_of(direction, Slug::generate_schema), serde_json::json!({ "type": "string" }));
+    }
+
+    assert_eq!(schema_of(Some(Direction::Request), Quota::generate_schema)["type"], "integer");
+    let (response, schemas) = registry::try_collect_by_direction(|| registry::directed(Direction::Response, Quota::generate_schema)).unwrap();
+    assert_eq!(response.schema.extras["$ref"], "#/components/schemas/Quota");
+    assert_eq!(schemas["Quota"].extras["required"], serde_json::json!(["limit", "used"]));
+
+    // Shared by both sides, the component cannot follow either conversion.
+    let (request, _) = registry::collect(|| registry::directed(Direction::Request, Quota::generate_schema));
+    assert_eq!(request.schema.extras["$ref"], "#/components/schemas/Quota");
+
+    let (_, schemas) = registry::try_collect_by_direction(|| {
+        registry::directed(Direction::Request, Account::generate_schema);
+        registry::directed(Direction::Response, Account::generate_schema);
+    })
+    .unwrap();
+    let components: serde_json::Map<_, _> = schemas.iter().map(|(name, schema)| (name.clone(), schema.to_value())).collect();
+    let expected: serde_json::Value = serde_json::from_str(include_str!("serde_from_into.json")).unwrap();
+    assert_json_eq!(serde_json::Value::Object(components), expected);
+}
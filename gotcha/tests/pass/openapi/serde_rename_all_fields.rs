@@ -0,0 +1,20 @@
+//! `#[serde(rename_all_fields = "...")]` renames the fields of every struct variant; a variant's
+//! own `rename_all` wins over it.
+
+use assert_json_diff::assert_json_eq;
+use gotcha::Schematic;
+use serde::Serialize;
+
+#[derive(Schematic, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case", rename_all_fields = "camelCase")]
+pub enum Event {
+    UserCreated { user_id: u64, display_name: String },
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    UserDeleted { user_id: u64 },
+}
+
+fn main() {
+    let schema = serde_json::to_value(Event::generate_schema().schema).unwrap();
+    let expected: serde_json::Value = serde_json::from_str(include_str!("serde_rename_all_fields.json")).unwrap();
+    assert_json_eq!(schema, expected);
+}
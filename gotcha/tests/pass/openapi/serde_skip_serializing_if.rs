@@ -0,0 +1,39 @@
+//! `#[serde(skip_serializing_if = "...")]` without `default` only leaves the field out of what is
+//! sent back: a request still has to carry it. With request and response components documented
+//! separately the field stays required on the request side; otherwise it is optional.
+
+use assert_json_diff::assert_json_eq;
+use gotcha::Schematic;
+use gotcha_core::registry::{self, Direction};
+use serde::{Deserialize, Serialize};
+
+#[derive(Schematic, Serialize, Deserialize)]
+struct Profile {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nickname: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+fn required(schema: &gotcha_core::oas::Schema) -> serde_json::Value {
+    schema.extras["required"].clone()
+}
+
+fn main() {
+    let inline = Profile::generate_schema().schema;
+    assert_eq!(required(&inline), serde_json::json!(["name"]));
+
+    let (_, schemas) = registry::try_collect_by_direction(|| {
+        registry::directed(Direction::Request, Profile::generate_schema);
+        registry::directed(Direction::Response, Profile::generate_schema);
+    })
+    .unwrap();
+    assert_eq!(schemas.keys().collect::<Vec<_>>(), ["ProfileRequest", "ProfileResponse"]);
+    assert_eq!(required(&schemas["ProfileRequest"]), serde_json::json!(["name", "tags"]));
+    assert_eq!(required(&schemas["ProfileResponse"]), serde_json::json!(["name"]));
+
+    let components: serde_json::Map<_, _> = schemas.iter().map(|(name, schema)| (name.clone(), schema.to_value())).collect();
+    let expected: serde_json::Value = serde_json::from_str(include_str!("serde_skip_serializing_if.json")).unwrap();
+    assert_json_eq!(serde_json::Value::Object(components), expected);
+}
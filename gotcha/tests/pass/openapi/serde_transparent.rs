@@ -0,0 +1,36 @@
+//! `#[serde(transparent)]` structs serialize as their single field, so their schema is that
+//! field's — skipped fields alongside it do not count.
+
+use std::marker::PhantomData;
+
+use assert_json_diff::assert_json_eq;
+use gotcha::Schematic;
+use serde::Serialize;
+
+#[derive(Schematic, Serialize)]
+#[serde(transparent)]
+struct Email {
+    address: String,
+}
+
+#[derive(Schematic, Serialize)]
+#[serde(transparent)]
+struct Tagged<T> {
+    values: Vec<u32>,
+    #[serde(skip)]
+    marker: PhantomData<T>,
+}
+
+fn main() {
+    let email = serde_json::to_value(Email::generate_schema().schema).unwrap();
+    assert_eq!(email["type"], "string");
+    assert!(email.get("properties").is_none());
+
+    let tagged = serde_json::to_value(Tagged::<String>::generate_schema().schema).unwrap();
+    assert_eq!(tagged["type"], "array");
+    assert_eq!(tagged["items"]["type"], "integer");
+    assert!(tagged.get("properties").is_none());
+
+    let expected: serde_json::Value = serde_json::from_str(include_str!("serde_transparent.json")).unwrap();
+    assert_json_eq!(email, expected);
+}
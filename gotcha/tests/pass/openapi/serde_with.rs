@@ -0,0 +1,49 @@
+//! A field serialized `#[serde(with / serialize_with / deserialize_with = "...")]` has a wire
+//! format the derive cannot see, so it is documented as any value — and its Rust type does not
+//! need to be `Schematic`.
+
+use assert_json_diff::assert_json_eq;
+use gotcha::Schematic;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Not `Schematic`: only ever sent through `as_seconds`.
+struct Elapsed(std::time::Duration);
+
+mod as_seconds {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Elapsed, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(value.0.as_secs())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Elapsed, D::Error> {
+        u64::deserialize(deserializer).map(|secs| Elapsed(std::time::Duration::from_secs(secs)))
+    }
+}
+
+fn upper<S: Serializer>(value: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_uppercase())
+}
+
+#[derive(Schematic, Serialize, Deserialize)]
+struct Run {
+    /// Wall-clock time
+    #[serde(with = "as_seconds")]
+    elapsed: Elapsed,
+    #[serde(serialize_with = "upper")]
+    status: String,
+    attempts: u32,
+}
+
+fn main() {
+    let schema = serde_json::to_value(Run::generate_schema().schema).unwrap();
+    let props = &schema["properties"];
+
+    assert_eq!(props["elapsed"], serde_json::json!({ "description": "Wall-clock time" }));
+    assert_eq!(props["status"], serde_json::json!({}));
+    assert_eq!(props["attempts"]["type"], "integer");
+    assert_eq!(schema["required"], serde_json::json!(["elapsed", "status", "attempts"]));
+
+    let expected: serde_json::Value = serde_json::from_str(include_str!("serde_with.json")).unwrap();
+    assert_json_eq!(schema, expected);
+}
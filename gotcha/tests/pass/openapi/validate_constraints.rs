@@ -0,0 +1,73 @@
+//! More `#[validate(...)]` rules mirrored into the schema, and the `#[schematic(...)]` constraints
+//! validator has no rule for:
+//! `regex(path = ..)` → `pattern`, read from the regex when the schema is built;
+//! `contains` / `does_not_contain` → an escaped `pattern` / `not: { pattern }`, and a regex
+//! alongside `contains` → one `pattern` each under `allOf`;
+//! bounds given as constants; `custom` / `must_match` / `nested` stay runtime-only, and a `nested`
+//! type documents its own constraints; `#[schematic(unique_items, multiple_of, enum_values)]`.
+
+use std::sync::LazyLock;
+
+use gotcha::{Schematic, Validate};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+static SLUG: LazyLock<Regex> = LazyLock::new(|| Regex::new("^[a-z0-9-]+$").unwrap());
+const MAX_SEATS: u32 = 500;
+
+fn not_reserved(_: &str) -> Result<(), validator::ValidationError> {
+    Ok(())
+}
+
+#[derive(Schematic, Serialize, Deserialize, Validate)]
+struct Owner {
+    #[validate(email)]
+    email: String,
+}
+
+#[derive(Schematic, Serialize, Deserialize, Validate)]
+struct CreateTeam {
+    #[validate(regex(path = *SLUG), custom(function = "not_reserved"))]
+    slug: String,
+    #[validate(contains(pattern = "c++"), does_not_contain(pattern = "$"))]
+    motto: String,
+    #[validate(contains(pattern = "team"), regex(path = *SLUG))]
+    handle: String,
+    #[validate(range(min = 1, max = MAX_SEATS))]
+    seats: u32,
+    password: String,
+    #[validate(must_match(other = "password"))]
+    password_again: String,
+    #[validate(nested)]
+    owner: Owner,
+    #[schematic(unique_items)]
+    #[validate(length(max = 10))]
+    tags: Vec<String>,
+    #[schematic(multiple_of = 5)]
+    budget: u32,
+    #[schematic(enum_values = ["free", "pro", String::from("enterprise")], description = "Billing plan")]
+    plan: String,
+}
+
+fn main() {
+    let team = CreateTeam::generate_schema().schema.to_value();
+    let props = &team["properties"];
+
+    assert_eq!(props["slug"]["pattern"], "^[a-z0-9-]+$");
+    assert_eq!(props["motto"]["pattern"], "c\\+\\+");
+    assert_eq!(props["motto"]["not"], serde_json::json!({ "pattern": "\\$" }));
+    assert!(props["handle"].get("pattern").is_none());
+    assert_eq!(props["handle"]["allOf"], serde_json::json!([{ "pattern": "team" }, { "pattern": "^[a-z0-9-]+$" }]));
+    assert_eq!(props["seats"]["minimum"], 1.0);
+    assert_eq!(props["seats"]["maximum"], 500.0);
+    assert!(props["password_again"].get("pattern").is_none());
+    assert_eq!(props["tags"]["uniqueItems"], true);
+    assert_eq!(props["tags"]["maxItems"], 10);
+    assert_eq!(props["budget"]["multipleOf"], 5);
+    assert_eq!(props["plan"]["enum"], serde_json::json!(["free", "pro", "enterprise"]));
+    assert_eq!(props["plan"]["description"], "Billing plan");
+
+    let owner = Owner::generate_schema().schema.to_value();
+    assert_eq!(owner["properties"]["email"]["format"], "email");
+    assert_eq!(props["owner"]["properties"]["email"]["format"], "email");
+}
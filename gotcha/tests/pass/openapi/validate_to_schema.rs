@@ -2,7 +2,7 @@
 //! so a rule written once for runtime validation also documents the field:
 //! `range` (incl. exclusive bounds) → minimum/maximum (+ exclusiveMinimum/Maximum),
 //! `length` (min/max/equal) → minLength/maxLength (minItems/maxItems for collections),
-//! `email`/`url` → format. Unmodelled validators (`regex`, `custom`, …) are silently skipped.
+//! `email`/`url` → format. See `validate_constraints.rs` for `regex` and the rest.
 
 use gotcha::{Schematic, Validate};
 use serde::{Deserialize, Serialize};
@@ -0,0 +1,80 @@
+//! `#[schematic(read_only)]` / `#[schematic(write_only)]`, and per-direction components.
+#![cfg(feature = "openapi")]
+
+use gotcha::prelude::*;
+
+#[derive(Schematic, Serialize, Deserialize)]
+pub struct User {
+    #[schematic(read_only)]
+    id: u64,
+    name: String,
+    #[schematic(write_only)]
+    password: String,
+}
+
+#[derive(Schematic, Serialize, Deserialize)]
+pub struct Team {
+    owner: User,
+    tags: Vec<Tag>,
+}
+
+#[derive(Schematic, Serialize, Deserialize)]
+pub struct Tag {
+    label: String,
+}
+
+/// Create a team
+#[api(id = "create_team")]
+async fn create_team(Json(team): Json<Team>) -> Json<Team> {
+    Json(team)
+}
+
+/// List tags
+#[api(id = "tags")]
+async fn tags(Json(tags): Json<Vec<Tag>>) -> Json<Vec<Tag>> {
+    Json(tags)
+}
+
+fn routes(router: GotchaRouter) -> GotchaRouter {
+    router.post("/teams", create_team).post("/tags", tags)
+}
+
+fn properties(spec: &serde_json::Value, component: &str) -> Vec<String> {
+    spec["components"]["schemas"][component]["properties"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn fields_are_flagged() {
+    let spec = routes(GotchaRouter::default()).openapi_spec();
+
+    let user = &spec["components"]["schemas"]["User"]["properties"];
+    assert_eq!(user["id"]["readOnly"], true);
+    assert_eq!(user["password"]["writeOnly"], true);
+    assert!(user["name"].get("readOnly").is_none());
+}
+
+#[test]
+fn split_schemas_documents_each_side() {
+    let spec = routes(GotchaRouter::default()).split_schemas(true).openapi_spec();
+
+    let components = spec["components"]["schemas"].as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+    assert_eq!(components, ["Tag", "TeamRequest", "TeamResponse", "UserRequest", "UserResponse"]);
+    assert_eq!(properties(&spec, "UserRequest"), ["name", "password"]);
+    assert_eq!(properties(&spec, "UserResponse"), ["id", "name"]);
+    assert_eq!(spec["components"]["schemas"]["UserRequest"]["required"], json!(["name", "password"]));
+
+    let operation = &spec["paths"]["/teams"]["post"];
+    let body = &operation["requestBody"]["content"]["application/json"]["schema"]["$ref"];
+    let response = &operation["responses"]["200"]["content"]["application/json"]["schema"]["$ref"];
+    assert_eq!(body, "#/components/schemas/TeamRequest");
+    assert_eq!(response, "#/components/schemas/TeamResponse");
+    assert_eq!(
+        spec["components"]["schemas"]["TeamResponse"]["properties"]["owner"]["$ref"],
+        "#/components/schemas/UserResponse"
+    );
+}
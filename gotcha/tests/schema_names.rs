@@ -0,0 +1,123 @@
+//! Component names: generic instantiations, `#[schematic(rename)]`, and two types claiming one name.
+#![cfg(feature = "openapi")]
+
+use gotcha::prelude::*;
+
+#[derive(Schematic, Serialize, Deserialize)]
+pub struct User {
+    name: String,
+}
+
+#[derive(Schematic, Serialize, Deserialize)]
+pub struct Order {
+    total: u32,
+}
+
+/// One page of results.
+#[derive(Schematic, Serialize, Deserialize)]
+pub struct Page<T> {
+    items: Vec<T>,
+    next: Option<String>,
+}
+
+#[derive(Schematic, Serialize, Deserialize)]
+#[schematic(rename = "Money")]
+pub struct Amount {
+    cents: i64,
+}
+
+mod billing {
+    use gotcha::prelude::*;
+
+    /// Not the same type as the top-level `User`.
+    #[derive(Schematic, Serialize, Deserialize)]
+    pub struct User {
+        pub account: String,
+    }
+}
+
+mod legacy {
+    use gotcha::prelude::*;
+
+    /// Renamed out of the way of the top-level `User`.
+    #[derive(Schematic, Serialize, Deserialize)]
+    #[schematic(rename = "LegacyUser")]
+    pub struct User {
+        pub login: String,
+    }
+}
+
+/// List users
+#[api(id = "users")]
+async fn users() -> Json<Page<User>> {
+    unimplemented!()
+}
+
+/// List orders
+#[api(id = "orders")]
+async fn orders() -> Json<Page<Order>> {
+    unimplemented!()
+}
+
+/// Nested pages
+#[api(id = "pages")]
+async fn pages() -> Json<Page<Vec<User>>> {
+    unimplemented!()
+}
+
+/// Balance
+#[api(id = "balance")]
+async fn balance() -> Json<Amount> {
+    unimplemented!()
+}
+
+/// Legacy account
+#[api(id = "legacy")]
+async fn legacy_user() -> Json<legacy::User> {
+    unimplemented!()
+}
+
+/// Billing account
+#[api(id = "billing")]
+async fn billing_user() -> Json<billing::User> {
+    unimplemented!()
+}
+
+fn schemas(spec: &serde_json::Value) -> Vec<String> {
+    spec["components"]["schemas"].as_object().unwrap().keys().cloned().collect()
+}
+
+#[test]
+fn generic_instantiations_get_their_own_components() {
+    let spec = GotchaRouter::<()>::default()
+        .get("/users", users)
+        .get("/orders", orders)
+        .get("/pages", pages)
+        .openapi_spec();
+
+    assert_eq!(schemas(&spec), ["Order", "Page_Order", "Page_User", "Page_Vec_User", "User"]);
+    let response = |path: &str| spec["paths"][path]["get"]["responses"]["200"]["content"]["application/json"]["schema"]["$ref"].clone();
+    assert_eq!(response("/users"), "#/components/schemas/Page_User");
+    assert_eq!(response("/orders"), "#/components/schemas/Page_Order");
+    assert_eq!(
+        spec["components"]["schemas"]["Page_Order"]["properties"]["items"]["items"]["$ref"],
+        "#/components/schemas/Order"
+    );
+}
+
+#[test]
+fn rename_overrides_the_component_name() {
+    let spec = GotchaRouter::<()>::default()
+        .get("/balance", balance)
+        .get("/users", users)
+        .get("/legacy", legacy_user)
+        .openapi_spec();
+
+    assert_eq!(schemas(&spec), ["LegacyUser", "Money", "Page_User", "User"]);
+}
+
+#[test]
+#[should_panic(expected = "schema component `User` is claimed by both")]
+fn two_types_with_one_name_fail_assembly() {
+    GotchaRouter::<()>::default().get("/users", users).get("/billing", billing_user).openapi_spec();
+}
@@ -0,0 +1,93 @@
+//! The task admin routes: listing tasks, reading their history and triggering them.
+#![cfg(feature = "task")]
+
+use std::time::Duration;
+
+use gotcha::axum::body::{to_bytes, Body};
+use gotcha::axum::http::{Method, Request, StatusCode};
+use gotcha::axum::{Extension, Router};
+use gotcha::{ConfigWrapper, GotchaApp, GotchaContext, GotchaResult, GotchaRouter, TaskScheduler};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+fn context() -> GotchaContext<(), ()> {
+    GotchaContext::new(ConfigWrapper::default(), ())
+}
+
+struct App;
+
+impl GotchaApp for App {
+    type State = ();
+    type Config = ();
+
+    fn routes(&self, router: GotchaRouter<GotchaContext<Self::State, Self::Config>>) -> GotchaRouter<GotchaContext<Self::State, Self::Config>> {
+        router.task_admin("/admin/tasks")
+    }
+
+    async fn state(&self, _config: &ConfigWrapper<Self::Config>) -> GotchaResult<Self::State> {
+        Ok(())
+    }
+}
+
+async fn app(scheduler: &TaskScheduler<(), ()>) -> Router {
+    App.build_router(context()).await.unwrap().layer(Extension(scheduler.registry()))
+}
+
+async fn call(app: &Router, method: Method, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test(start_paused = true)]
+async fn lists_triggers_and_shows_history() {
+    let scheduler = TaskScheduler::new(context());
+    scheduler.interval("cleanup", Duration::from_secs(3600), |_ctx| async {}).unwrap();
+    scheduler.interval("report", Duration::from_secs(3600), |_ctx| async {}).unwrap().pause();
+    let app = app(&scheduler).await;
+
+    let (status, tasks) = call(&app, Method::GET, "/admin/tasks").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tasks[0]["name"], "cleanup");
+    assert_eq!(tasks[1]["paused"], true);
+    assert_eq!(tasks[1]["last_run"], Value::Null);
+
+    let (status, task) = call(&app, Method::POST, "/admin/tasks/cleanup/trigger").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["name"], "cleanup");
+    tokio::time::sleep(Duration::from_millis(1)).await;
+
+    let (status, history) = call(&app, Method::GET, "/admin/tasks/cleanup/history").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history.as_array().unwrap().len(), 1);
+    assert_eq!(history[0]["outcome"], "succeeded");
+    assert_eq!(history[0]["attempt"], 1);
+}
+
+#[tokio::test]
+async fn unknown_tasks_are_not_found() {
+    let scheduler = TaskScheduler::new(context());
+    let app = app(&scheduler).await;
+
+    let (status, body) = call(&app, Method::POST, "/admin/tasks/missing/trigger").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, json!({ "task": "missing" }));
+}
+
+#[cfg(feature = "openapi")]
+#[tokio::test]
+async fn the_routes_are_documented() {
+    let spec = App.openapi_spec().await.unwrap();
+
+    let operation = |path: &str, method: &str| spec["paths"][path][method]["operationId"].clone();
+    assert_eq!(operation("/admin/tasks", "get"), "list_tasks");
+    assert_eq!(operation("/admin/tasks/{name}/history", "get"), "task_history");
+    assert_eq!(operation("/admin/tasks/{name}/trigger", "post"), "trigger_task");
+    assert_eq!(spec["paths"]["/admin/tasks/{name}/trigger"]["post"]["tags"], json!(["tasks"]));
+    assert_eq!(spec["paths"]["/admin/tasks/{name}/history"]["get"]["parameters"][0]["name"], "name");
+    let schemas = &spec["components"]["schemas"];
+    assert_eq!(schemas["TaskSummary"]["properties"]["last_run"]["$ref"], "#/components/schemas/TaskRun");
+    assert_eq!(schemas["RunOutcome"]["enum"], json!(["succeeded", "failed", "panicked", "timed_out"]));
+}
@@ -0,0 +1,57 @@
+//! Tasks declared with `#[task]` are registered with their arguments extracted from the context.
+#![cfg(feature = "task")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Timelike;
+use gotcha::task::{TaskError, Tz};
+use gotcha::{task, ConfigWrapper, GotchaContext, Messager, State, TaskScheduler};
+
+#[derive(Clone, Default)]
+struct AppState {
+    ticks: Arc<AtomicUsize>,
+}
+
+#[task(interval = "10s")]
+async fn tick(State(state): State<AppState>) {
+    state.ticks.fetch_add(1, Ordering::SeqCst);
+}
+
+#[task(cron = "0 0 2 * * *", timezone = "Europe/Berlin", name = "nightly")]
+async fn report(_messager: Messager<AppState, ()>, _config: ConfigWrapper<()>) -> Result<(), String> {
+    Err("no recipients".to_string())
+}
+
+fn scheduler<S: Clone + Send + Sync + 'static>(state: S) -> TaskScheduler<S, ()> {
+    TaskScheduler::new(GotchaContext::new(ConfigWrapper::default(), state))
+}
+
+#[tokio::test(start_paused = true)]
+async fn declared_tasks_are_registered_and_run() {
+    let state = AppState::default();
+    let scheduler = scheduler(state.clone());
+    let handles = scheduler.register_declared().unwrap();
+
+    let names: Vec<_> = handles.iter().map(|handle| handle.name().to_string()).collect();
+    assert_eq!(names, ["nightly", "tick"]);
+
+    tokio::time::sleep(Duration::from_secs(25)).await;
+    assert_eq!(state.ticks.load(Ordering::SeqCst), 2);
+
+    let berlin: Tz = "Europe/Berlin".parse().unwrap();
+    let nightly = scheduler.get("nightly").unwrap();
+    let next = nightly.next_run().expect("scheduled").with_timezone(&berlin);
+    assert_eq!((next.hour(), next.minute()), (2, 0));
+
+    nightly.trigger();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(nightly.last_run().unwrap().error.as_deref(), Some("no recipients"));
+}
+
+#[tokio::test]
+async fn tasks_whose_arguments_are_missing_are_errors() {
+    let declared = scheduler(()).register_declared();
+    assert!(matches!(declared, Err(TaskError::Argument { argument, .. }) if argument.contains("Messager")));
+}
@@ -0,0 +1,500 @@
+//! Scheduling tasks and controlling them through their handles, on tokio's paused clock.
+#![cfg(feature = "task")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Timelike;
+use gotcha::config::Config;
+use gotcha::task::{FileLock, MemoryLock, Overlap, RetryPolicy, RunOutcome, TaskError, TaskHistory, TaskLock, TaskOptions, TaskRun, Tz};
+use gotcha::{ConfigWrapper, GotchaContext, TaskScheduler};
+
+fn scheduler() -> TaskScheduler<(), ()> {
+    TaskScheduler::new(GotchaContext::new(ConfigWrapper::default(), ()))
+}
+
+/// A task body that counts its runs.
+fn counting(runs: &Arc<AtomicUsize>) -> impl Fn(GotchaContext<(), ()>) -> std::future::Ready<()> + Send + 'static {
+    let runs = runs.clone();
+    move |_ctx| {
+        runs.fetch_add(1, Ordering::SeqCst);
+        std::future::ready(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn interval_tasks_run_once_per_interval() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    scheduler().interval("tick", Duration::from_secs(10), counting(&runs)).unwrap();
+
+    tokio::time::sleep(Duration::from_secs(35)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn paused_tasks_only_run_when_triggered() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let handle = scheduler().interval("tick", Duration::from_secs(10), counting(&runs)).unwrap();
+
+    handle.pause();
+    tokio::time::sleep(Duration::from_secs(25)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+    assert!(handle.is_paused());
+    assert_eq!(handle.next_run(), None);
+
+    handle.trigger();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+    handle.resume();
+    tokio::time::sleep(Duration::from_secs(12)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelled_tasks_stop() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let handle = scheduler().interval("tick", Duration::from_secs(10), counting(&runs)).unwrap();
+
+    tokio::time::sleep(Duration::from_secs(15)).await;
+    handle.cancel();
+    tokio::time::sleep(Duration::from_secs(30)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert!(handle.is_cancelled());
+}
+
+#[tokio::test(start_paused = true)]
+async fn next_run_is_reported() {
+    let scheduler = scheduler();
+    let tick = scheduler.interval("tick", Duration::from_secs(60), |_ctx| async {}).unwrap();
+    let nightly = scheduler.cron("nightly", "0 0 0 * * *".to_string(), |_ctx| async {}).unwrap();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+
+    let until = |next: Option<chrono::DateTime<chrono::Utc>>| (next.expect("scheduled") - chrono::Utc::now()).num_seconds();
+    assert!((58..=60).contains(&until(tick.next_run())));
+    assert!((0..=86_400).contains(&until(nightly.next_run())));
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_scheduler_lists_and_shuts_down_its_tasks() {
+    let scheduler = scheduler();
+    scheduler.interval("a", Duration::from_secs(1), |_ctx| async {}).unwrap();
+    scheduler.clone().interval("b", Duration::from_secs(1), |_ctx| async {}).unwrap();
+
+    let names: Vec<_> = scheduler.tasks().iter().map(|task| task.name().to_string()).collect();
+    assert_eq!(names, ["a", "b"]);
+    let b = scheduler.get("b").unwrap();
+
+    scheduler.shutdown().await;
+    assert!(b.is_cancelled());
+    assert!(scheduler.tasks().is_empty());
+}
+
+#[tokio::test]
+async fn invalid_registrations_are_errors() {
+    let scheduler = scheduler();
+
+    let invalid = scheduler.cron("bad", "every day".to_string(), |_ctx| async {});
+    assert!(matches!(invalid, Err(TaskError::InvalidSchedule { ref name, .. }) if name == "bad"));
+    let zero = scheduler.interval("zero", Duration::ZERO, |_ctx| async {});
+    assert!(matches!(zero, Err(TaskError::ZeroInterval { .. })));
+
+    scheduler.interval("tick", Duration::from_secs(1), |_ctx| async {}).unwrap();
+    let duplicate = scheduler.interval("tick", Duration::from_secs(1), |_ctx| async {});
+    assert!(matches!(duplicate, Err(TaskError::Duplicate(ref name)) if name == "tick"));
+}
+
+/// A task body that counts its starts and takes `duration` to finish.
+fn slow(starts: &Arc<AtomicUsize>, duration: Duration) -> impl Fn(GotchaContext<(), ()>) -> tokio::time::Sleep + Send + Sync + 'static {
+    let starts = starts.clone();
+    move |_ctx| {
+        starts.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(duration)
+    }
+}
+
+fn options(overlap: Overlap) -> TaskOptions {
+    TaskOptions {
+        overlap,
+        ..TaskOptions::default()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn overlapping_runs_are_skipped_by_default() {
+    let starts = Arc::new(AtomicUsize::new(0));
+    scheduler()
+        .interval("slow", Duration::from_secs(10), slow(&starts, Duration::from_secs(25)))
+        .unwrap();
+
+    // Due at 10, 20, 30, 40 and 50. The runs started at 10 and 40 last until 35 and 65, so the
+    // others are skipped.
+    tokio::time::sleep(Duration::from_secs(55)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn queued_runs_wait_for_the_previous_one() {
+    let starts = Arc::new(AtomicUsize::new(0));
+    let scheduler = scheduler();
+    scheduler
+        .interval_with_options("slow", Duration::from_secs(10), options(Overlap::Queue), slow(&starts, Duration::from_secs(25)))
+        .unwrap();
+
+    // Due at 10 and 20; the second starts when the first ends at 35.
+    tokio::time::sleep(Duration::from_secs(24)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 1);
+    tokio::time::sleep(Duration::from_secs(12)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 2);
+    scheduler.get("slow").unwrap().cancel();
+}
+
+#[tokio::test(start_paused = true)]
+async fn queued_runs_are_capped() {
+    let starts = Arc::new(AtomicUsize::new(0));
+    let handle = scheduler()
+        .interval_with_options("slow", Duration::from_secs(1), options(Overlap::Queue), slow(&starts, Duration::from_secs(100)))
+        .unwrap();
+
+    // Due every second until paused at 50; the run started at 1 is still going, so only eight of
+    // the rest wait for it and the others are skipped.
+    tokio::time::sleep(Duration::from_millis(50_500)).await;
+    handle.pause();
+    tokio::time::sleep(Duration::from_secs(2_000)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 9);
+    handle.cancel();
+}
+
+#[tokio::test(start_paused = true)]
+async fn concurrent_runs_start_on_time() {
+    let starts = Arc::new(AtomicUsize::new(0));
+    scheduler()
+        .interval_with_options(
+            "slow",
+            Duration::from_secs(10),
+            options(Overlap::Concurrent),
+            slow(&starts, Duration::from_secs(25)),
+        )
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_secs(35)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn failed_runs_are_retried_with_backoff() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let retry = RetryPolicy {
+        max_retries: 3,
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(60),
+        multiplier: 2.0,
+    };
+    let handle = scheduler()
+        .interval_with_options(
+            "flaky",
+            Duration::from_secs(100),
+            TaskOptions {
+                retry: Some(retry),
+                ..TaskOptions::default()
+            },
+            move |_ctx| {
+                let attempt = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt < 2 {
+                        Err(format!("attempt {attempt} failed"))
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+        )
+        .unwrap();
+    handle.trigger();
+
+    // Attempts at 0, 1 (after 1s) and 3 (after 2s more); the third succeeds.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    tokio::time::sleep(Duration::from_secs(50)).await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_stops_a_retry_waiting_out_its_backoff() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let scheduler = scheduler();
+    let handle = scheduler
+        .interval_with_options(
+            "failing",
+            Duration::from_secs(1_000),
+            TaskOptions {
+                retry: Some(RetryPolicy {
+                    max_retries: 5,
+                    initial_backoff: Duration::from_secs(60),
+                    max_backoff: Duration::from_secs(60),
+                    multiplier: 1.0,
+                }),
+                ..TaskOptions::default()
+            },
+            move |_ctx| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { Err::<(), _>("always fails") }
+            },
+        )
+        .unwrap();
+    handle.trigger();
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    tokio::time::timeout(Duration::from_secs(1), scheduler.shutdown())
+        .await
+        .expect("the backoff is cut short");
+    tokio::time::sleep(Duration::from_secs(600)).await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn runs_over_the_timeout_are_aborted_and_retried() {
+    let starts = Arc::new(AtomicUsize::new(0));
+    let finished = Arc::new(AtomicUsize::new(0));
+    let (counter, done) = (starts.clone(), finished.clone());
+    let options = TaskOptions {
+        timeout: Some(Duration::from_secs(5)),
+        retry: Some(RetryPolicy::new(1)),
+        ..TaskOptions::default()
+    };
+    let handle = scheduler()
+        .interval_with_options("hang", Duration::from_secs(100), options, move |_ctx| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let done = done.clone();
+            async move {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                done.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .unwrap();
+    handle.trigger();
+
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(starts.load(Ordering::SeqCst), 2);
+    assert_eq!(finished.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn jitter_delays_scheduled_runs_within_its_bound() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let options = TaskOptions {
+        jitter: Some(Duration::from_secs(5)),
+        ..TaskOptions::default()
+    };
+    scheduler()
+        .interval_with_options("spread", Duration::from_secs(10), options, counting(&runs))
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(9_999)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+    tokio::time::sleep(Duration::from_millis(5_002)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn backoff_grows_up_to_its_cap() {
+    let retry = RetryPolicy {
+        max_retries: 10,
+        initial_backoff: Duration::from_secs(1),
+        max_backoff: Duration::from_secs(10),
+        multiplier: 3.0,
+    };
+    let waits: Vec<_> = (1..=4).map(|retry_number| retry.backoff(retry_number).as_secs()).collect();
+    assert_eq!(waits, [1, 3, 9, 10]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn every_attempt_is_recorded() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let options = TaskOptions {
+        retry: Some(RetryPolicy::new(2)),
+        ..TaskOptions::default()
+    };
+    let handle = scheduler()
+        .interval_with_options("flaky", Duration::from_secs(100), options, move |_ctx| {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match attempt {
+                    0 => panic!("first attempt panics"),
+                    1 => Err("second attempt fails"),
+                    _ => Ok(()),
+                }
+            }
+        })
+        .unwrap();
+    handle.trigger();
+    tokio::time::sleep(Duration::from_secs(10)).await;
+
+    let history: Vec<_> = handle.history().into_iter().map(|run| (run.attempt, run.outcome, run.error)).collect();
+    assert_eq!(
+        history,
+        [
+            (3, RunOutcome::Succeeded, None),
+            (2, RunOutcome::Failed, Some("second attempt fails".to_string())),
+            (1, RunOutcome::Panicked, Some("first attempt panics".to_string())),
+        ]
+    );
+    assert_eq!(handle.last_run().unwrap().task, "flaky");
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_custom_history_receives_the_runs() {
+    struct Recorder(Arc<std::sync::Mutex<Vec<TaskRun>>>);
+    impl TaskHistory for Recorder {
+        fn record(&self, run: TaskRun) {
+            self.0.lock().unwrap().push(run);
+        }
+        fn runs(&self, task: &str) -> Vec<TaskRun> {
+            self.0.lock().unwrap().iter().filter(|run| run.task == task).cloned().collect()
+        }
+    }
+
+    let runs = Arc::new(std::sync::Mutex::new(vec![]));
+    let scheduler = scheduler();
+    let handle = scheduler.interval("tick", Duration::from_secs(10), |_ctx| async {}).unwrap();
+    scheduler.set_history(Recorder(runs.clone()));
+
+    tokio::time::sleep(Duration::from_secs(25)).await;
+    assert_eq!(runs.lock().unwrap().len(), 2);
+    assert_eq!(handle.history().len(), 2);
+}
+
+/// Load `toml` as the configuration file `name`.
+fn load(name: &str, toml: &str) -> Result<ConfigWrapper<()>, gotcha::config::ConfigError> {
+    let dir = std::env::temp_dir().join("gotcha-task-config");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{name}.toml"));
+    std::fs::write(&path, toml).unwrap();
+    Config::builder().file(&path).build()
+}
+
+fn configured(name: &str, toml: &str) -> TaskScheduler<(), ()> {
+    let config = load(name, toml).expect("loads");
+    TaskScheduler::new(GotchaContext::new(config, ()))
+}
+
+#[tokio::test(start_paused = true)]
+async fn config_sections_disable_and_reschedule_tasks() {
+    let scheduler = configured(
+        "overrides",
+        r#"
+[tasks.cleanup]
+enabled = false
+
+[tasks.heartbeat]
+interval = "5s"
+
+[tasks.report]
+cron = "0 0 2 * * *"
+timezone = "Europe/Berlin"
+"#,
+    );
+    let (cleanups, heartbeats) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let cleanup = scheduler.interval("cleanup", Duration::from_secs(10), counting(&cleanups)).unwrap();
+    scheduler.interval("heartbeat", Duration::from_secs(60), counting(&heartbeats)).unwrap();
+    let report = scheduler.interval("report", Duration::from_secs(10), |_ctx| async {}).unwrap();
+
+    tokio::time::sleep(Duration::from_secs(12)).await;
+    assert!(cleanup.is_paused());
+    assert_eq!(cleanups.load(Ordering::SeqCst), 0, "disabled tasks do not fire");
+    assert_eq!(heartbeats.load(Ordering::SeqCst), 2, "the configured interval wins");
+
+    let berlin: Tz = "Europe/Berlin".parse().unwrap();
+    let next = report.next_run().expect("scheduled").with_timezone(&berlin);
+    assert_eq!((next.hour(), next.minute()), (2, 0), "the configured cron runs on Berlin time");
+
+    cleanup.trigger();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert_eq!(cleanups.load(Ordering::SeqCst), 1, "disabled tasks can still be triggered");
+}
+
+#[tokio::test]
+async fn invalid_task_sections_are_errors() {
+    let scheduler = configured(
+        "conflicts",
+        "[tasks.both]\ncron = \"0 * * * * *\"\ninterval = \"1m\"\n\n[tasks.bad]\ncron = \"often\"\n",
+    );
+    let both = scheduler.interval("both", Duration::from_secs(1), |_ctx| async {});
+    assert!(matches!(both, Err(TaskError::ConflictingSchedule { .. })));
+    let bad = scheduler.interval("bad", Duration::from_secs(1), |_ctx| async {});
+    assert!(matches!(bad, Err(TaskError::InvalidSchedule { ref expression, .. }) if expression == "often"));
+
+    assert!(load("timezone", "[tasks.report]\ntimezone = \"Mars/Olympus\"\n").is_err());
+    assert!(load("interval", "[tasks.report]\ninterval = \"soon\"\n").is_err());
+}
+
+#[tokio::test]
+async fn locks_grant_each_trigger_once() {
+    let due: chrono::DateTime<chrono::Utc> = "2024-01-01T02:00:00Z".parse().unwrap();
+    let later = due + chrono::Duration::minutes(1);
+
+    let memory = MemoryLock::new();
+    assert!(memory.acquire("report", due).await.unwrap());
+    assert!(!memory.acquire("report", due).await.unwrap());
+    assert!(memory.acquire("cleanup", due).await.unwrap(), "tasks are claimed separately");
+    assert!(memory.acquire("report", later).await.unwrap());
+
+    // Two replicas, each with its own `FileLock` on the same directory.
+    let dir = std::env::temp_dir().join(format!("gotcha-task-lock-{}", std::process::id()));
+    let (first, second) = (FileLock::new(&dir), FileLock::new(&dir));
+    assert!(first.acquire("nightly report", due).await.unwrap());
+    assert!(!second.acquire("nightly report", due).await.unwrap());
+    assert!(second.acquire("nightly report", later).await.unwrap());
+    assert!(!first.acquire("nightly report", later).await.unwrap());
+    assert!(first.acquire("nightly_report", due).await.unwrap(), "names differing only in punctuation are claimed separately");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn replicas_sharing_a_lock_run_each_trigger_once() {
+    let lock = Arc::new(MemoryLock::new());
+    let runs = Arc::new(AtomicUsize::new(0));
+    let replicas: Vec<_> = (0..3).map(|_| scheduler()).collect();
+    for replica in &replicas {
+        replica.set_lock(lock.clone());
+        replica.cron("every-second", "* * * * * *".to_string(), counting(&runs)).unwrap();
+    }
+
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    for replica in &replicas {
+        replica.shutdown().await;
+    }
+    // Two or three seconds started, depending on when the test began within the first one.
+    assert!((2..=3).contains(&runs.load(Ordering::SeqCst)), "ran {runs:?} times");
+}
+
+#[tokio::test]
+async fn replicas_started_apart_run_each_interval_once() {
+    let lock = Arc::new(MemoryLock::new());
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut replicas = Vec::new();
+    for _ in 0..3 {
+        let replica = scheduler();
+        replica.set_lock(lock.clone());
+        replica.interval("every-half-second", Duration::from_millis(500), counting(&runs)).unwrap();
+        replicas.push(replica);
+        // Out of step with each other and with the boundaries they run on.
+        tokio::time::sleep(Duration::from_millis(170)).await;
+    }
+
+    tokio::time::sleep(Duration::from_millis(2000)).await;
+    for replica in &replicas {
+        replica.shutdown().await;
+    }
+    // About 2.5 seconds of half-second boundaries.
+    assert!((4..=6).contains(&runs.load(Ordering::SeqCst)), "ran {runs:?} times");
+}
@@ -268,6 +268,7 @@ fn test_flatten_option_struct() {
 pub struct WithSkip {
     visible: String,
     #[serde(skip)]
+    #[allow(dead_code)]
     hidden: String,
 }
 
@@ -114,6 +114,28 @@ fn test_untagged_with_rename_all() {
     assert!(resp_props.contains_key("responseBody"));
 }
 
+// === Untagged with rename_all_fields, and a variant's own rename_all ===
+#[derive(Debug, Clone, Serialize, Deserialize, Schematic)]
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum UntaggedWithRenameAllFields {
+    Request { user_name: String, request_id: i32 },
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    Response { status_code: i32 },
+}
+
+#[test]
+fn test_untagged_with_rename_all_fields() {
+    let json = UntaggedWithRenameAllFields::generate_schema().schema.to_value();
+    let one_of = json.get("oneOf").unwrap().as_array().unwrap();
+
+    let req_props = one_of[0].get("properties").unwrap().as_object().unwrap();
+    assert!(req_props.contains_key("userName"));
+    assert!(req_props.contains_key("requestId"));
+
+    let resp_props = one_of[1].get("properties").unwrap().as_object().unwrap();
+    assert!(resp_props.contains_key("STATUS_CODE"), "the variant's own rename_all wins");
+}
+
 // === Untagged with nested struct ===
 #[derive(Debug, Clone, Serialize, Deserialize, Schematic)]
 pub struct Address {
@@ -0,0 +1,131 @@
+//! One set of routes served as several API versions, with `#[api(since, until)]` deciding which
+//! handlers each version has.
+#![cfg(feature = "openapi")]
+
+use gotcha::axum::body::Body;
+use gotcha::axum::http::{Request, StatusCode};
+use gotcha::axum::Router;
+use gotcha::prelude::*;
+use gotcha::versioning::{ApiVersion, Versioning};
+use tower::ServiceExt;
+
+/// List users
+#[api(id = "list_users", group = "users")]
+async fn list_users() -> &'static str {
+    "users"
+}
+
+/// The old way to find a user, removed in v2
+#[api(id = "find_user", until = "2")]
+async fn find_user() -> &'static str {
+    "found"
+}
+
+/// Search users, new in v2
+#[api(id = "search_users", since = "2")]
+async fn search_users() -> &'static str {
+    "searched"
+}
+
+struct App(Versioning);
+
+impl GotchaApp for App {
+    type State = ();
+    type Config = EmptyConfig;
+
+    fn routes(&self, router: GotchaRouter<GotchaContext<Self::State, Self::Config>>) -> GotchaRouter<GotchaContext<Self::State, Self::Config>> {
+        let versioning = self
+            .0
+            .clone()
+            .version(ApiVersion::new("1").sunset("Wed, 31 Dec 2025 23:59:59 GMT"))
+            .version("2");
+        router.versions(versioning, |router| {
+            router
+                .get("/users", list_users)
+                .get("/users/find", find_user)
+                .get("/users/search", search_users)
+        })
+    }
+
+    async fn state(&self, _config: &ConfigWrapper<Self::Config>) -> GotchaResult<Self::State> {
+        Ok(())
+    }
+}
+
+async fn app(versioning: Versioning) -> Router {
+    let context = GotchaContext::new(ConfigWrapper::default(), ());
+    App(versioning).build_router(context).await.unwrap()
+}
+
+async fn get(app: &Router, uri: &str, header: Option<(&str, &str)>) -> (StatusCode, Option<String>, String) {
+    let mut request = Request::builder().uri(uri);
+    if let Some((name, value)) = header {
+        request = request.header(name, value);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let sunset = response.headers().get("sunset").map(|value| value.to_str().unwrap().to_string());
+    let body = gotcha::axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, sunset, String::from_utf8(body.to_vec()).unwrap())
+}
+
+async fn spec(app: &Router, uri: &str) -> serde_json::Value {
+    let (status, _, body) = get(app, uri, None).await;
+    assert_eq!(status, StatusCode::OK, "{uri}");
+    serde_json::from_str(&body).unwrap()
+}
+
+#[tokio::test]
+async fn path_prefix_versions_have_their_own_routes() {
+    let app = app(Versioning::path_prefix()).await;
+
+    assert_eq!(get(&app, "/v1/users/find", None).await.0, StatusCode::OK);
+    assert_eq!(get(&app, "/v1/users/search", None).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get(&app, "/v2/users/find", None).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get(&app, "/v2/users/search", None).await.0, StatusCode::OK);
+
+    let (_, sunset, _) = get(&app, "/v1/users", None).await;
+    assert_eq!(sunset.as_deref(), Some("Wed, 31 Dec 2025 23:59:59 GMT"), "a sunsetting version says so");
+    assert_eq!(get(&app, "/v2/users", None).await.1, None);
+}
+
+#[tokio::test]
+async fn each_version_has_its_own_spec() {
+    let app = app(Versioning::path_prefix()).await;
+
+    let v1 = spec(&app, "/v1/openapi.json").await;
+    assert_eq!(v1["info"]["version"], "1");
+    assert_eq!(v1["servers"][0]["url"], "/v1");
+    assert_eq!(v1["paths"]["/users/find"]["get"]["deprecated"], true);
+    assert_eq!(v1["paths"]["/users"]["get"]["responses"]["200"]["headers"]["Sunset"]["required"], true);
+    assert!(v1["paths"]["/users/search"].is_null());
+
+    let v2 = spec(&app, "/v2/openapi.json").await;
+    assert_eq!(v2["info"]["version"], "2");
+    assert!(v2["paths"]["/users/find"].is_null());
+    assert_eq!(v2["paths"]["/users/search"]["get"]["deprecated"], false);
+
+    assert_eq!(get(&app, "/v1/redoc", None).await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn header_picks_the_version() {
+    let app = app(Versioning::header("x-api-version")).await;
+
+    assert_eq!(get(&app, "/users/find", Some(("x-api-version", "1"))).await.0, StatusCode::OK);
+    assert_eq!(get(&app, "/users/search", Some(("x-api-version", "1"))).await.0, StatusCode::NOT_FOUND);
+    // No header: the newest version.
+    assert_eq!(get(&app, "/users/search", None).await.0, StatusCode::OK);
+    assert_eq!(get(&app, "/users", Some(("x-api-version", "7"))).await.0, StatusCode::BAD_REQUEST);
+    // The specs still live under the version prefixes.
+    assert!(spec(&app, "/v1/openapi.json").await["servers"].is_null());
+}
+
+#[tokio::test]
+async fn media_type_picks_the_version() {
+    let app = app(Versioning::media_type("example")).await;
+
+    let v1 = Some(("accept", "application/vnd.example.v1+json"));
+    assert_eq!(get(&app, "/users/find", v1).await.0, StatusCode::OK);
+    assert_eq!(get(&app, "/users/find", None).await.0, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,123 @@
+//! Versioned routes behave like the router's own: layers wrap them, nesting and merging keep them,
+//! and `#[api(since, until)]` bounds them with or without the `openapi` feature.
+
+use gotcha::axum::body::Body;
+use gotcha::axum::http::{HeaderValue, Request, StatusCode};
+use gotcha::axum::Router;
+use gotcha::prelude::*;
+use gotcha::versioning::{ApiVersion, Versioning};
+use tower::ServiceExt;
+
+type Context = GotchaContext<(), EmptyConfig>;
+
+#[api(id = "legacy", until = "2")]
+async fn legacy() -> &'static str {
+    "legacy"
+}
+
+#[api(id = "current", since = "2")]
+async fn current() -> &'static str {
+    "current"
+}
+
+struct App(fn(GotchaRouter<Context>) -> GotchaRouter<Context>);
+
+impl GotchaApp for App {
+    type State = ();
+    type Config = EmptyConfig;
+
+    fn routes(&self, router: GotchaRouter<Context>) -> GotchaRouter<Context> {
+        (self.0)(router)
+    }
+
+    async fn state(&self, _config: &ConfigWrapper<Self::Config>) -> GotchaResult<Self::State> {
+        Ok(())
+    }
+}
+
+async fn app(routes: fn(GotchaRouter<Context>) -> GotchaRouter<Context>) -> Router {
+    let context = GotchaContext::new(ConfigWrapper::default(), ());
+    App(routes).build_router(context).await.unwrap()
+}
+
+/// The status, the `x-layer` header and the body of a response.
+async fn get(app: &Router, uri: &str, version: Option<&str>) -> (StatusCode, Option<String>, String) {
+    let mut request = Request::builder().uri(uri);
+    if let Some(version) = version {
+        request = request.header("x-api-version", version);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    let status = response.status();
+    let layer = response.headers().get("x-layer").map(|value| value.to_str().unwrap().to_string());
+    let body = gotcha::axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, layer, String::from_utf8(body.to_vec()).unwrap())
+}
+
+fn tagged(router: GotchaRouter<Context>) -> GotchaRouter<Context> {
+    router.layer(gotcha::axum::middleware::map_response(|mut response: gotcha::axum::response::Response| async move {
+        response.headers_mut().insert("x-layer", HeaderValue::from_static("applied"));
+        response
+    }))
+}
+
+fn legacy_and_current(router: GotchaRouter<Context>) -> GotchaRouter<Context> {
+    router.get("/legacy", legacy).get("/current", current)
+}
+
+#[tokio::test]
+async fn since_and_until_bound_the_routes() {
+    let app = app(|router| router.versions(Versioning::path_prefix().version("1").version("2"), legacy_and_current)).await;
+
+    assert_eq!(get(&app, "/v1/legacy", None).await.0, StatusCode::OK);
+    assert_eq!(get(&app, "/v1/current", None).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get(&app, "/v2/legacy", None).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get(&app, "/v2/current", None).await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn layers_wrap_versioned_routes() {
+    let prefixed = app(|router| tagged(router.versions(Versioning::path_prefix().version("1").version("2"), legacy_and_current))).await;
+    assert_eq!(get(&prefixed, "/v1/legacy", None).await.1.as_deref(), Some("applied"));
+    assert_eq!(get(&prefixed, "/v2/current", None).await.1.as_deref(), Some("applied"));
+
+    let by_header = app(|router| tagged(router.versions(Versioning::header("x-api-version").version("1").version("2"), legacy_and_current))).await;
+    assert_eq!(get(&by_header, "/legacy", Some("1")).await.1.as_deref(), Some("applied"));
+    assert_eq!(get(&by_header, "/current", None).await.1.as_deref(), Some("applied"));
+}
+
+#[tokio::test]
+async fn nesting_moves_versioned_routes() {
+    let prefixed = app(|router| router.nest("/api", GotchaRouter::default().versions(Versioning::path_prefix().version("1").version("2"), legacy_and_current))).await;
+    assert_eq!(get(&prefixed, "/api/v1/legacy", None).await.2, "legacy");
+    assert_eq!(get(&prefixed, "/v1/legacy", None).await.0, StatusCode::NOT_FOUND);
+
+    let by_header = app(|router| {
+        router
+            .get("/health", || async { "ok" })
+            .nest("/api", GotchaRouter::default().versions(Versioning::header("x-api-version").version("1").version("2"), legacy_and_current))
+    })
+    .await;
+    assert_eq!(get(&by_header, "/api/legacy", Some("1")).await.2, "legacy");
+    assert_eq!(get(&by_header, "/api/current", None).await.2, "current");
+    assert_eq!(get(&by_header, "/legacy", Some("1")).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(get(&by_header, "/health", None).await.2, "ok");
+}
+
+#[tokio::test]
+async fn merging_keeps_both_sides_versions() {
+    let app = app(|router| {
+        let public = GotchaRouter::default().versions(Versioning::path_prefix().version("1").version("2"), legacy_and_current);
+        let admin = GotchaRouter::default().nest("/admin", GotchaRouter::default().versions(Versioning::path_prefix().version("1"), |router| router.get("/stats", || async { "stats" })));
+        router.merge(public).merge(admin)
+    })
+    .await;
+
+    assert_eq!(get(&app, "/v2/current", None).await.2, "current");
+    assert_eq!(get(&app, "/admin/v1/stats", None).await.2, "stats");
+}
+
+#[test]
+#[should_panic(expected = "is not an HTTP-date")]
+fn sunsets_must_be_http_dates() {
+    ApiVersion::new("1").sunset("end of 2025");
+}
@@ -0,0 +1,386 @@
+//! A typed builder for [`EnhancedSchema`], for documenting types the derive cannot see.
+//!
+//! A third-party type cannot implement [`Schematic`] in your crate (the orphan rule), and poking
+//! raw JSON into [`Schema::extras`] is easy to get wrong. Instead, build its schema with
+//! [`SchemaBuilder`] in a plain function and point the field at it with
+//! `#[schematic(with = "path::to::fn")]`:
+//!
+//! ```
+//! use gotcha_core::{EnhancedSchema, SchemaBuilder};
+//!
+//! /// `rust_money::Money` as serde writes it.
+//! fn money() -> EnhancedSchema {
+//!     SchemaBuilder::object()
+//!         .description("An amount in a currency")
+//!         .property("amount", SchemaBuilder::string().pattern(r"^-?\d+(\.\d+)?$"))
+//!         .property("currency", SchemaBuilder::string().min_length(3).max_length(3))
+//!         .build()
+//! }
+//!
+//! let schema = money().schema.to_value();
+//! assert_eq!(schema["required"], serde_json::json!(["amount", "currency"]));
+//! ```
+//!
+//! When the type's shape can be written as a struct, [`schematic_remote!`](crate::schematic_remote)
+//! derives such a function from a mirror definition instead.
+
+use oas::Schema;
+use serde_json::{Map, Value};
+
+use crate::{EnhancedSchema, Schematic};
+
+/// Builds an [`EnhancedSchema`] keyword by keyword.
+///
+/// Every schema starts out required; [`optional`](Self::optional) marks it otherwise, which
+/// decides whether [`property`](Self::property) lists it under `required`.
+pub struct SchemaBuilder {
+    schema: Schema,
+    required: bool,
+}
+
+impl SchemaBuilder {
+    fn typed(type_: Option<&str>) -> Self {
+        SchemaBuilder {
+            schema: Schema {
+                _type: type_.map(str::to_string),
+                format: None,
+                nullable: None,
+                description: None,
+                extras: Default::default(),
+            },
+            required: true,
+        }
+    }
+
+    /// Any JSON value: the empty schema `{}`.
+    pub fn any() -> Self {
+        Self::typed(None)
+    }
+
+    /// A string.
+    pub fn string() -> Self {
+        Self::typed(Some("string"))
+    }
+
+    /// A whole number.
+    pub fn integer() -> Self {
+        Self::typed(Some("integer"))
+    }
+
+    /// Any number.
+    pub fn number() -> Self {
+        Self::typed(Some("number"))
+    }
+
+    /// `true` or `false`.
+    pub fn boolean() -> Self {
+        Self::typed(Some("boolean"))
+    }
+
+    /// An object; add its fields with [`property`](Self::property).
+    pub fn object() -> Self {
+        Self::typed(Some("object"))
+    }
+
+    /// An array of `items`.
+    pub fn array(items: impl Into<EnhancedSchema>) -> Self {
+        Self::typed(Some("array")).extra("items", items.into().schema.to_value())
+    }
+
+    /// `T`'s own schema, to adjust further. Inside spec assembly a derived `T` is a `$ref`.
+    pub fn of<T: Schematic + ?Sized>() -> Self {
+        let EnhancedSchema { schema, required } = T::generate_schema();
+        SchemaBuilder { schema, required }
+    }
+
+    /// Exactly one of `schemas`.
+    pub fn one_of(schemas: impl IntoIterator<Item = impl Into<EnhancedSchema>>) -> Self {
+        Self::any().extra("oneOf", values(schemas))
+    }
+
+    /// At least one of `schemas`.
+    pub fn any_of(schemas: impl IntoIterator<Item = impl Into<EnhancedSchema>>) -> Self {
+        Self::any().extra("anyOf", values(schemas))
+    }
+
+    /// All of `schemas` at once.
+    pub fn all_of(schemas: impl IntoIterator<Item = impl Into<EnhancedSchema>>) -> Self {
+        Self::any().extra("allOf", values(schemas))
+    }
+
+    /// Adds the object property `name`, listed under `required` unless its schema is
+    /// [`optional`](Self::optional).
+    pub fn property(mut self, name: &str, schema: impl Into<EnhancedSchema>) -> Self {
+        let schema = schema.into();
+        if schema.required {
+            self.entry_array("required").push(Value::String(name.to_string()));
+        }
+        self.entry_object("properties").insert(name.to_string(), schema.schema.to_value());
+        self
+    }
+
+    /// Properties not listed with [`property`](Self::property) are allowed, and must match `schema`
+    /// (a map's values).
+    pub fn additional_properties(self, schema: impl Into<EnhancedSchema>) -> Self {
+        self.extra("additionalProperties", schema.into().schema.to_value())
+    }
+
+    /// Only the properties listed with [`property`](Self::property) are allowed.
+    pub fn deny_additional_properties(self) -> Self {
+        self.extra("additionalProperties", false)
+    }
+
+    /// Which `oneOf` / `anyOf` branch a value is, given by its `property`.
+    pub fn discriminator(mut self, property: &str) -> Self {
+        self.entry_object("discriminator")
+            .insert("propertyName".to_string(), Value::String(property.to_string()));
+        self
+    }
+
+    /// Maps a [`discriminator`](Self::discriminator) `value` to the component schema it selects.
+    pub fn mapping(mut self, value: &str, component: &str) -> Self {
+        let discriminator = self.entry_object("discriminator");
+        let Value::Object(mapping) = discriminator.entry("mapping").or_insert_with(|| Value::Object(Map::new())) else {
+            unreachable!("`mapping` is only ever set here, as an object")
+        };
+        mapping.insert(value.to_string(), Value::String(format!("#/components/schemas/{component}")));
+        self
+    }
+
+    /// A short title.
+    pub fn title(self, title: &str) -> Self {
+        self.extra("title", title)
+    }
+
+    /// What the value means.
+    pub fn description(mut self, description: &str) -> Self {
+        self.schema.description = Some(description.to_string());
+        self
+    }
+
+    /// The `format` of a string or number, such as `date-time` or `int64`.
+    pub fn format(mut self, format: &str) -> Self {
+        self.schema.format = Some(format.to_string());
+        self
+    }
+
+    /// An example value.
+    pub fn example(self, example: impl Into<Value>) -> Self {
+        self.extra("example", example)
+    }
+
+    /// The value assumed when none is given.
+    pub fn default(self, default: impl Into<Value>) -> Self {
+        self.extra("default", default)
+    }
+
+    /// `null` is accepted too.
+    pub fn nullable(mut self) -> Self {
+        self.schema.nullable = Some(true);
+        self
+    }
+
+    /// The value may be left out.
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    /// Set by the server; see `#[schematic(read_only)]`.
+    pub fn read_only(self) -> Self {
+        self.extra("readOnly", true)
+    }
+
+    /// Only ever sent by the client; see `#[schematic(write_only)]`.
+    pub fn write_only(self) -> Self {
+        self.extra("writeOnly", true)
+    }
+
+    /// The smallest number allowed.
+    pub fn minimum(self, minimum: f64) -> Self {
+        self.extra("minimum", minimum)
+    }
+
+    /// The largest number allowed.
+    pub fn maximum(self, maximum: f64) -> Self {
+        self.extra("maximum", maximum)
+    }
+
+    /// Numbers must be greater than `minimum`.
+    pub fn exclusive_minimum(self, minimum: f64) -> Self {
+        // OpenAPI 3.0 spells exclusivity as a flag next to the bound.
+        self.minimum(minimum).extra("exclusiveMinimum", true)
+    }
+
+    /// Numbers must be less than `maximum`.
+    pub fn exclusive_maximum(self, maximum: f64) -> Self {
+        self.maximum(maximum).extra("exclusiveMaximum", true)
+    }
+
+    /// Numbers must be a multiple of `factor`.
+    pub fn multiple_of(self, factor: f64) -> Self {
+        self.extra("multipleOf", factor)
+    }
+
+    /// The shortest string allowed.
+    pub fn min_length(self, length: u64) -> Self {
+        self.extra("minLength", length)
+    }
+
+    /// The longest string allowed.
+    pub fn max_length(self, length: u64) -> Self {
+        self.extra("maxLength", length)
+    }
+
+    /// A regular expression strings must match somewhere.
+    pub fn pattern(self, pattern: &str) -> Self {
+        self.extra("pattern", pattern)
+    }
+
+    /// The fewest array items allowed.
+    pub fn min_items(self, items: u64) -> Self {
+        self.extra("minItems", items)
+    }
+
+    /// The most array items allowed.
+    pub fn max_items(self, items: u64) -> Self {
+        self.extra("maxItems", items)
+    }
+
+    /// Array items must be distinct.
+    pub fn unique_items(self) -> Self {
+        self.extra("uniqueItems", true)
+    }
+
+    /// The only values allowed.
+    pub fn enum_values(self, values: impl IntoIterator<Item = impl Into<Value>>) -> Self {
+        self.extra("enum", values.into_iter().map(Into::into).collect::<Vec<_>>())
+    }
+
+    /// Sets any other keyword, replacing an earlier value.
+    pub fn extra(mut self, keyword: &str, value: impl Into<Value>) -> Self {
+        self.schema.extras.insert(keyword.to_string(), value.into());
+        self
+    }
+
+    /// The finished schema.
+    pub fn build(self) -> EnhancedSchema {
+        EnhancedSchema {
+            schema: self.schema,
+            required: self.required,
+        }
+    }
+
+    fn entry_object(&mut self, keyword: &str) -> &mut Map<String, Value> {
+        let entry = self.schema.extras.entry(keyword.to_string()).or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            *entry = Value::Object(Map::new());
+        }
+        entry.as_object_mut().expect("just made an object")
+    }
+
+    fn entry_array(&mut self, keyword: &str) -> &mut Vec<Value> {
+        let entry = self.schema.extras.entry(keyword.to_string()).or_insert_with(|| Value::Array(vec![]));
+        if !entry.is_array() {
+            *entry = Value::Array(vec![]);
+        }
+        entry.as_array_mut().expect("just made an array")
+    }
+}
+
+impl From<SchemaBuilder> for EnhancedSchema {
+    fn from(builder: SchemaBuilder) -> Self {
+        builder.build()
+    }
+}
+
+fn values(schemas: impl IntoIterator<Item = impl Into<EnhancedSchema>>) -> Vec<Value> {
+    schemas.into_iter().map(|schema| schema.into().schema.to_value()).collect()
+}
+
+/// Derives a schema function for a type from another crate, from a mirror of its serialized
+/// shape, for use with `#[schematic(with = "..")]`.
+///
+/// The mirror is written like a struct deriving [`Schematic`](derive@crate::Schematic) — field
+/// docs and `#[schematic(..)]` options included — and is registered as a component under its
+/// name. The remote type only has to exist; its fields are not checked against the mirror.
+///
+/// ```
+/// use gotcha_core::schematic_remote;
+///
+/// schematic_remote! {
+///     /// A point in time, as seconds and nanoseconds since the Unix epoch.
+///     pub fn system_time for std::time::SystemTime as SystemTime {
+///         secs_since_epoch: u64,
+///         nanos_since_epoch: u32,
+///     }
+/// }
+///
+/// let schema = system_time().schema.to_value();
+/// assert_eq!(schema["properties"]["secs_since_epoch"]["type"], "integer");
+/// ```
+#[macro_export]
+macro_rules! schematic_remote {
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $function:ident for $remote:ty as $name:ident { $($fields:tt)* }
+    ) => {
+        #[doc = concat!("The schema of `", stringify!($remote), "`.")]
+        $vis fn $function() -> $crate::EnhancedSchema {
+            use $crate::Schematic;
+
+            let _ = ::core::marker::PhantomData::<$remote>;
+            $(#[$attr])*
+            #[derive($crate::Schematic)]
+            #[allow(dead_code)]
+            struct $name { $($fields)* }
+            <$name as $crate::Schematic>::generate_schema()
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn properties_follow_requiredness() {
+        let schema = SchemaBuilder::object()
+            .property("id", SchemaBuilder::integer().format("int64").minimum(1.0))
+            .property("nickname", SchemaBuilder::string().nullable().optional())
+            .property("tags", SchemaBuilder::array(SchemaBuilder::string()).unique_items())
+            .deny_additional_properties()
+            .build();
+
+        assert_eq!(
+            schema.schema.to_value(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer", "format": "int64", "minimum": 1.0 },
+                    "nickname": { "type": "string", "nullable": true },
+                    "tags": { "type": "array", "items": { "type": "string" }, "uniqueItems": true },
+                },
+                "required": ["id", "tags"],
+                "additionalProperties": false,
+            })
+        );
+    }
+
+    #[test]
+    fn unions_carry_their_discriminator() {
+        let schema = SchemaBuilder::one_of([SchemaBuilder::of::<String>(), SchemaBuilder::of::<u32>()])
+            .discriminator("kind")
+            .mapping("text", "Text")
+            .build();
+
+        let schema = schema.schema.to_value();
+        assert_eq!(schema["oneOf"], json!([{ "type": "string" }, { "type": "integer" }]));
+        assert_eq!(
+            schema["discriminator"],
+            json!({ "propertyName": "kind", "mapping": { "text": "#/components/schemas/Text" } })
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Durations written as a number and a unit, such as `"30s"` or `"1h30m"`: the `interval` of
+//! `#[task]` and of the `[tasks]` configuration.
+//!
+//! [`parse_duration`] is a `const fn`, so `#[task(interval = "..")]` checks its interval while the
+//! crate compiles, with the same parser the configuration uses at run time.
+
+use std::fmt;
+use std::time::Duration;
+
+/// Why [`parse_duration`] rejected its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurationError(&'static str);
+
+impl DurationError {
+    const INVALID: Self = Self("expected a duration such as \"30s\", \"5m\" or \"1h30m\"");
+    const UNKNOWN_UNIT: Self = Self("unknown unit; use ms, s, m, h or d");
+    const TOO_LONG: Self = Self("the duration is too long");
+
+    /// What is wrong with the input.
+    pub const fn message(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for DurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for DurationError {}
+
+/// Parse a duration written as a number and a unit: `ms`, `s`, `m`, `h` or `d`. Several can be
+/// combined, as in `"1h30m"`.
+pub const fn parse_duration(input: &str) -> Result<Duration, DurationError> {
+    let bytes = input.as_bytes();
+    let (mut start, mut end) = (0, bytes.len());
+    while start < end && bytes[start].is_ascii_whitespace() {
+        start += 1;
+    }
+    while end > start && bytes[end - 1].is_ascii_whitespace() {
+        end -= 1;
+    }
+    if start == end {
+        return Err(DurationError::INVALID);
+    }
+    let mut total: u64 = 0;
+    let mut at = start;
+    while at < end {
+        let mut amount: u64 = 0;
+        let digits = at;
+        while at < end && bytes[at].is_ascii_digit() {
+            amount = match amount.checked_mul(10) {
+                Some(tens) => match tens.checked_add((bytes[at] - b'0') as u64) {
+                    Some(amount) => amount,
+                    None => return Err(DurationError::TOO_LONG),
+                },
+                None => return Err(DurationError::TOO_LONG),
+            };
+            at += 1;
+        }
+        if at == digits || at == end {
+            return Err(DurationError::INVALID);
+        }
+        let unit = at;
+        while at < end && !bytes[at].is_ascii_digit() {
+            at += 1;
+        }
+        let millis: u64 = match (at - unit, bytes[unit], bytes[at - 1]) {
+            (2, b'm', b's') => 1,
+            (1, b's', _) => 1_000,
+            (1, b'm', _) => 60_000,
+            (1, b'h', _) => 3_600_000,
+            (1, b'd', _) => 86_400_000,
+            _ => return Err(DurationError::UNKNOWN_UNIT),
+        };
+        total = match amount.checked_mul(millis) {
+            Some(part) => match total.checked_add(part) {
+                Some(total) => total,
+                None => return Err(DurationError::TOO_LONG),
+            },
+            None => return Err(DurationError::TOO_LONG),
+        };
+    }
+    Ok(Duration::from_millis(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration(" 2d "), Ok(Duration::from_secs(2 * 24 * 60 * 60)));
+        for invalid in ["", "30", "s", "-1s"] {
+            assert_eq!(parse_duration(invalid), Err(DurationError::INVALID), "{invalid:?} should be rejected");
+        }
+        for unknown in ["30x", "1.5h"] {
+            assert_eq!(parse_duration(unknown), Err(DurationError::UNKNOWN_UNIT), "{unknown:?} has no known unit");
+        }
+    }
+
+    #[test]
+    fn rejects_durations_that_overflow() {
+        assert_eq!(parse_duration("18446744073709551616ms"), Err(DurationError::TOO_LONG));
+        assert_eq!(parse_duration("18446744073709551615s"), Err(DurationError::TOO_LONG));
+        assert_eq!(parse_duration("18446744073709551615ms1ms"), Err(DurationError::TOO_LONG));
+    }
+
+    #[test]
+    fn parses_in_a_const_context() {
+        const INTERVAL: Result<Duration, DurationError> = parse_duration("5m");
+        assert_eq!(INTERVAL, Ok(Duration::from_secs(5 * 60)));
+    }
+}
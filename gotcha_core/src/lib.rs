@@ -19,7 +19,7 @@
 use std::collections::{HashMap, HashSet};
 
 use bigdecimal::BigDecimal;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone};
 /// Re-export of the [`Schematic`] derive macro. Kept alongside the trait of the same
 /// name (different namespaces) so `use gotcha_core::Schematic;` brings in both.
 pub use gotcha_macro::Schematic;
@@ -31,6 +31,11 @@ use oas::Schema;
 /// `::gotcha_core::serde_json::` without a direct `serde_json` dependency.
 pub use serde_json;
 
+pub mod builder;
+pub use builder::SchemaBuilder;
+
+pub mod duration;
+
 pub mod registry;
 
 pub mod responsible;
@@ -55,6 +60,12 @@ pub struct EnhancedSchema {
 pub trait Schematic {
     /// The name of the type.
     fn name() -> &'static str;
+    /// The key this type is registered under in `components/schemas`. Defaults to
+    /// [`name`](Self::name); containers spell out what they hold, so the derive can name
+    /// `Page<Vec<User>>` as `Page_Vec_User` rather than letting every `Page<_>` share one entry.
+    fn component_name() -> String {
+        Self::name().to_string()
+    }
     /// Whether the type is required.
     fn required() -> bool;
     /// Whether the type is nullable.
@@ -71,6 +82,11 @@ pub trait Schematic {
     fn format() -> Option<String> {
         None
     }
+    /// An example value, as serde writes it. Documented on the type's component schema and as the
+    /// `example` of request and response bodies of this type.
+    fn example() -> Option<serde_json::Value> {
+        None
+    }
 
     /// The type's fields, used to build object schemas and to flatten query parameters.
     fn fields() -> Vec<(&'static str, EnhancedSchema)> {
@@ -101,6 +117,11 @@ pub trait Schematic {
     fn flatten_schema() -> Option<serde_json::Value> {
         None
     }
+    /// The schemas of a tuple's elements, in order; `None` for every other type. `Path<(A, B)>`
+    /// documents one path parameter per element from these.
+    fn elements() -> Option<Vec<EnhancedSchema>> {
+        None
+    }
 }
 
 macro_rules! impl_primitive_type {
@@ -134,6 +155,9 @@ impl_primitive_type! { bool, "bool", "boolean"}
 impl_primitive_type! { f32, "f32", "number"}
 impl_primitive_type! { f64, "f64", "number"}
 
+// After `impl_primitive_type!`, which it uses.
+mod types;
+
 /// The unit type means "no value". As a *return* type that is an empty body, which
 /// [`Responsible`](crate::Responsible) documents as a response carrying no content. In the rare
 /// case it appears as a schema (`Json<()>`, which serializes as `null`) it produces an empty
@@ -271,6 +295,10 @@ impl<T: Schematic> Schematic for Option<T> {
         T::name()
     }
 
+    fn component_name() -> String {
+        format!("Option_{}", T::component_name())
+    }
+
     fn required() -> bool {
         false
     }
@@ -301,6 +329,10 @@ impl<T: Schematic> Schematic for &T {
         T::name()
     }
 
+    fn component_name() -> String {
+        T::component_name()
+    }
+
     fn required() -> bool {
         T::required()
     }
@@ -311,6 +343,12 @@ impl<T: Schematic> Schematic for &T {
     fn doc() -> Option<String> {
         T::doc()
     }
+    fn example() -> Option<serde_json::Value> {
+        T::example()
+    }
+    fn elements() -> Option<Vec<EnhancedSchema>> {
+        T::elements()
+    }
     fn generate_schema() -> EnhancedSchema {
         T::generate_schema()
     }
@@ -321,6 +359,10 @@ impl<T: Schematic> Schematic for Vec<T> {
         T::name()
     }
 
+    fn component_name() -> String {
+        format!("Vec_{}", T::component_name())
+    }
+
     fn required() -> bool {
         // A `Vec<T>` field is always present (possibly as an empty array) unless
         // it is wrapped in `Option`; its requiredness must not depend on whether
@@ -385,6 +427,10 @@ impl<T: Schematic> Schematic for HashSet<T> {
         T::name()
     }
 
+    fn component_name() -> String {
+        format!("Set_{}", T::component_name())
+    }
+
     fn required() -> bool {
         true
     }
@@ -414,6 +460,10 @@ impl<K: ToString, V: Schematic> Schematic for HashMap<K, V> {
         V::name()
     }
 
+    fn component_name() -> String {
+        format!("Map_{}", V::component_name())
+    }
+
     fn required() -> bool {
         true
     }
@@ -441,7 +491,8 @@ impl<K: ToString, V: Schematic> Schematic for HashMap<K, V> {
     }
 }
 
-impl Schematic for DateTime<Utc> {
+/// Every time zone serializes as an RFC 3339 timestamp, with its offset.
+impl<Tz: TimeZone> Schematic for DateTime<Tz> {
     fn name() -> &'static str {
         "datetime"
     }
@@ -461,6 +512,8 @@ impl Schematic for DateTime<Utc> {
 
 #[cfg(test)]
 mod tests {
+    use chrono::Utc;
+
     use super::*;
 
     #[test]
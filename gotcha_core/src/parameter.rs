@@ -2,13 +2,13 @@
 //! request body it contributes to an operation.
 //!
 //! Like [`crate::Responsible`], it lives in this crate (behind the `axum` feature) rather
-//! than in `gotcha` because the generic `Path<T>` impl overlaps the tuple impls
-//! `Path<(T1,)>` / `Path<(T1, T2)>`. The compiler can only prove those do not overlap — i.e.
-//! that `(T1,)` does not implement [`Schematic`] — in the crate where `Schematic` is defined.
+//! than in `gotcha`, next to the [`Schematic`] impls its `Path<T>` impl relies on: a tuple
+//! `Path<(A, B)>` is told apart from a struct or a scalar through [`Schematic::elements`].
 
 use std::collections::BTreeMap;
 
-use axum::extract::{Extension, Json, Path, Query, Request, State};
+use axum::body::Bytes;
+use axum::extract::{Extension, Form, Json, Path, Query, Request, State};
 use either::Either;
 use oas::{MediaType, Parameter, ParameterIn, Referenceable, RequestBody, Schema};
 
@@ -40,56 +40,32 @@ fn build_param(name: String, _in: ParameterIn, required: bool, schema: Schema, d
     }
 }
 
-impl<T1: Schematic> ParameterProvider for Path<(T1,)> {
-    fn generate(url: String) -> Either<Vec<Parameter>, RequestBody> {
-        // Since axum 0.8 a captured segment is written `{name}`, matching OpenAPI's own syntax.
-        let pattern = regex::Regex::new(r"\{([^}]+)\}").unwrap();
-        let param_names_in_path: Vec<String> = pattern.captures_iter(&url).map(|digits| digits.get(1).unwrap().as_str().to_string()).collect();
-
-        let t1_param = build_param(
-            param_names_in_path.first().cloned().expect("cannot get param in path"),
-            ParameterIn::Path,
-            T1::required(),
-            T1::generate_schema().schema,
-            T1::doc(),
-        );
-        Either::Left(vec![t1_param])
-    }
-}
-
-impl<T1: Schematic, T2: Schematic> ParameterProvider for Path<(T1, T2)> {
-    fn generate(url: String) -> Either<Vec<Parameter>, RequestBody> {
-        // Since axum 0.8 a captured segment is written `{name}`, matching OpenAPI's own syntax.
-        let pattern = regex::Regex::new(r"\{([^}]+)\}").unwrap();
-        let param_names_in_path: Vec<String> = pattern.captures_iter(&url).map(|digits| digits.get(1).unwrap().as_str().to_string()).collect();
-
-        let t1_param = build_param(
-            param_names_in_path.first().cloned().expect("cannot get param in path"),
-            ParameterIn::Path,
-            T1::required(),
-            T1::generate_schema().schema,
-            T1::doc(),
-        );
-        let t2_param = build_param(
-            param_names_in_path.get(1).cloned().expect("cannot get param in path"),
-            ParameterIn::Path,
-            T2::required(),
-            T2::generate_schema().schema,
-            T2::doc(),
-        );
-
-        Either::Left(vec![t1_param, t2_param])
-    }
+/// Since axum 0.8 a captured segment is written `{name}`, matching OpenAPI's own syntax.
+fn path_param_names(url: &str) -> Vec<String> {
+    let pattern = regex::Regex::new(r"\{([^}]+)\}").unwrap();
+    pattern.captures_iter(url).map(|digits| digits.get(1).unwrap().as_str().to_string()).collect()
 }
 
 impl<T: Schematic> ParameterProvider for Path<T> {
     fn generate(url: String) -> Either<Vec<Parameter>, RequestBody> {
         let mut ret = vec![];
+
+        // Case 1: a tuple — each element is the segment in the same position.
+        if let Some(elements) = T::elements() {
+            let names = path_param_names(&url);
+            assert!(names.len() >= elements.len(), "cannot get param in path");
+            for (name, element) in names.into_iter().zip(elements) {
+                let description = element.schema.description.clone();
+                ret.push(build_param(name, ParameterIn::Path, element.required, element.schema, description));
+            }
+            return Either::Left(ret);
+        }
+
         let mut schema = T::generate_schema();
 
         // Check if this is a struct with properties or a simple type
         if let Some(mut properties) = schema.schema.extras.remove("properties") {
-            // Case 1: Struct with properties - each property becomes a path parameter
+            // Case 2: Struct with properties - each property becomes a path parameter
             if let Some(properties) = properties.as_object_mut() {
                 properties.iter_mut().for_each(|(key, value)| {
                     let schema = serde_json::from_value(value.clone()).unwrap();
@@ -98,12 +74,8 @@ impl<T: Schematic> ParameterProvider for Path<T> {
                 })
             }
         } else {
-            // Case 2: Simple type like Uuid - extract parameter name from URL
-            // Since axum 0.8 a captured segment is written `{name}`, matching OpenAPI's own syntax.
-            let pattern = regex::Regex::new(r"\{([^}]+)\}").unwrap();
-            let param_names_in_path: Vec<String> = pattern.captures_iter(&url).map(|digits| digits.get(1).unwrap().as_str().to_string()).collect();
-
-            if let Some(param_name) = param_names_in_path.first() {
+            // Case 3: Simple type like Uuid - extract parameter name from URL
+            if let Some(param_name) = path_param_names(&url).first() {
                 let param = build_param(param_name.clone(), ParameterIn::Path, T::required(), T::generate_schema().schema, T::doc());
                 ret.push(param);
             }
@@ -121,7 +93,7 @@ impl<T: Schematic> ParameterProvider for Json<T> {
             "application/json".to_owned(),
             MediaType {
                 schema: Some(Referenceable::Data(schema.schema)),
-                example: None,
+                example: T::example(),
                 examples: None,
                 encoding: None,
             },
@@ -135,6 +107,60 @@ impl<T: Schematic> ParameterProvider for Json<T> {
     }
 }
 
+/// A request body of `media_type` with `schema`.
+fn request_body(
+    media_type: &str, schema: Schema, example: Option<serde_json::Value>, description: Option<String>, required: bool,
+) -> Either<Vec<Parameter>, RequestBody> {
+    Either::Right(RequestBody {
+        description,
+        required: Some(required),
+        content: BTreeMap::from([(
+            media_type.to_owned(),
+            MediaType {
+                schema: Some(Referenceable::Data(schema)),
+                example,
+                examples: None,
+                encoding: None,
+            },
+        )]),
+    })
+}
+
+/// axum reads `Form<T>` from the body for every method but `GET` and `HEAD`, where it reads the
+/// query string instead; it is documented as the body, which is what a form handler is for.
+impl<T: Schematic> ParameterProvider for Form<T> {
+    fn generate(_url: String) -> Either<Vec<Parameter>, RequestBody> {
+        request_body(
+            "application/x-www-form-urlencoded",
+            T::generate_schema().schema,
+            T::example(),
+            T::doc(),
+            T::required(),
+        )
+    }
+}
+
+/// The body as UTF-8 text.
+impl ParameterProvider for String {
+    fn generate(_url: String) -> Either<Vec<Parameter>, RequestBody> {
+        request_body("text/plain", String::generate_schema().schema, None, None, true)
+    }
+}
+
+/// The raw body, whatever it is.
+impl ParameterProvider for Bytes {
+    fn generate(_url: String) -> Either<Vec<Parameter>, RequestBody> {
+        let schema = Schema {
+            _type: Some("string".to_string()),
+            format: Some("binary".to_string()),
+            nullable: None,
+            description: None,
+            extras: Default::default(),
+        };
+        request_body("application/octet-stream", schema, None, None, true)
+    }
+}
+
 impl<T: Schematic> ParameterProvider for Query<T> {
     fn generate(_url: String) -> Either<Vec<Parameter>, RequestBody> {
         let fields = T::fields();
@@ -11,15 +11,26 @@
 //!   now the inner `Node` is already "in progress" and short-circuits to its `$ref`.
 //! - **Reused types are emitted once**, under `components/schemas`, and referenced elsewhere.
 //!
+//! Components are keyed by [`Schematic::component_name`](crate::Schematic::component_name), which
+//! spells out generic arguments (`Page<User>` is `Page_User`). Derived types register through
+//! [`component`], which also records the Rust type behind each name, so two different types
+//! claiming one name are reported by [`try_collect`] instead of one silently replacing the other.
+//!
+//! With [`try_collect_by_direction`], a type whose `readOnly` / `writeOnly` fields make its request
+//! and response shapes differ is emitted as two components, `UserRequest` and `UserResponse`,
+//! each without the fields that do not apply; see [`directed`].
+//!
 //! Outside a collection scope nothing changes: schemas are built inline exactly as before, so a
 //! direct `T::generate_schema()` call (tests, ad-hoc use) still returns a self-contained schema.
 
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
 
 use oas::Schema;
+use serde_json::Value;
 
-use crate::EnhancedSchema;
+use crate::{EnhancedSchema, Schematic};
 
 thread_local! {
     /// The collection scope currently running on this thread, if any.
@@ -32,8 +43,84 @@ struct Registry {
     schemas: BTreeMap<String, Schema>,
     /// Names whose schema is mid-construction; hitting one again means a recursive type.
     in_progress: HashSet<String>,
+    /// The Rust type each name was first registered for, when it is known.
+    owners: HashMap<String, &'static str>,
+    /// Names claimed by more than one type, in the order they were found.
+    collisions: Vec<NameCollision>,
+    /// Which side of an operation schemas are being built for, inside [`directed`].
+    direction: Option<Direction>,
+    /// Which components get per-direction variants.
+    split: Split,
+    /// What the discovery pass saw of each component, keyed by its undirected name.
+    variants: BTreeMap<String, Variants>,
+}
+
+/// The side of an operation a schema describes; see [`directed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// A request parameter or body: `readOnly` fields are not sent.
+    Request,
+    /// A response body: `writeOnly` fields are not returned.
+    Response,
+}
+
+impl Direction {
+    fn suffix(self) -> &'static str {
+        match self {
+            Direction::Request => "Request",
+            Direction::Response => "Response",
+        }
+    }
+
+    /// The property flag that hides a field on this side.
+    fn hidden_flag(self) -> &'static str {
+        match self {
+            Direction::Request => "readOnly",
+            Direction::Response => "writeOnly",
+        }
+    }
+}
+
+#[derive(Default)]
+enum Split {
+    /// One component per type.
+    #[default]
+    Off,
+    /// Give every directed component a variant, recording what each looks like.
+    Discover,
+    /// Give variants to these components only.
+    Only(HashSet<String>),
+}
+
+#[derive(Default)]
+struct Variants {
+    /// The per-direction names the component was registered under.
+    names: BTreeSet<String>,
+    /// Whether a `readOnly` / `writeOnly` property was dropped from one of them.
+    stripped: bool,
+}
+
+/// Two different Rust types that produce the same component name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameCollision {
+    /// The contested component name.
+    pub name: String,
+    /// The two types claiming it, sorted so the report does not depend on registration order.
+    pub types: [&'static str; 2],
+}
+
+impl fmt::Display for NameCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "schema component `{}` is claimed by both `{}` and `{}`; give one of them `#[schematic(rename = \"..\")]`",
+            self.name, self.types[0], self.types[1]
+        )
+    }
 }
 
+impl std::error::Error for NameCollision {}
+
 /// A schema that is nothing but a `$ref` to a registered component.
 fn reference_schema(name: &str) -> Schema {
     let mut extras = BTreeMap::new();
@@ -53,7 +140,71 @@ fn reference_schema(name: &str) -> Schema {
 /// Each call installs a *fresh* scope and restores the enclosing one on the way out, so
 /// independent assemblies — two apps in one process, say a data port and an admin port — each
 /// collect only their own schemas.
+///
+/// A name claimed by two types keeps the first one's schema; use [`try_collect`] to be told.
 pub fn collect<R>(f: impl FnOnce() -> R) -> (R, BTreeMap<String, Schema>) {
+    let (result, registry) = scoped(Split::Off, f);
+    (result, registry.schemas)
+}
+
+/// [`collect`], failing when two different types claimed the same component name.
+pub fn try_collect<R>(f: impl FnOnce() -> R) -> Result<(R, BTreeMap<String, Schema>), Vec<NameCollision>> {
+    finish(scoped(Split::Off, f))
+}
+
+/// [`try_collect`], emitting separate request and response components for a type whose two sides
+/// differ — because it has `readOnly` or `writeOnly` fields, or holds a type that does.
+///
+/// Variants are named `{name}Request` / `{name}Response`, and each drops the fields hidden on its
+/// side. Every other type keeps a single component. Which types differ is only known once
+/// everything has been built, so `f` runs twice: once to find out, once to build under the
+/// final names.
+pub fn try_collect_by_direction<R>(f: impl Fn() -> R) -> Result<(R, BTreeMap<String, Schema>), Vec<NameCollision>> {
+    let (_, discovery) = scoped(Split::Discover, &f);
+    let split = split_components(&discovery);
+    finish(scoped(Split::Only(split), f))
+}
+
+/// Run `f` building schemas for one side of an operation. Only [`try_collect_by_direction`] looks
+/// at the direction; everywhere else this just runs `f`.
+pub fn directed<R>(direction: Direction, f: impl FnOnce() -> R) -> R {
+    let previous = ACTIVE.with(|active| active.borrow_mut().as_mut().map(|registry| registry.direction.replace(direction)));
+    let result = f();
+    if let Some(previous) = previous {
+        ACTIVE.with(|active| {
+            if let Some(registry) = active.borrow_mut().as_mut() {
+                registry.direction = previous;
+            }
+        });
+    }
+    result
+}
+
+/// The side of an operation schemas are currently being built for, inside [`directed`] — but only
+/// under [`try_collect_by_direction`], where a component built for one side may differ from the
+/// other's. Anywhere else a component is shared by both sides, so anything that depends on the
+/// side has to hold for both. Derived types converted `#[serde(from = "..")]` /
+/// `#[serde(into = "..")]` use it to document the type each side actually goes through.
+pub fn component_direction() -> Option<Direction> {
+    ACTIVE.with(|active| {
+        let active = active.borrow();
+        let registry = active.as_ref()?;
+        match registry.split {
+            Split::Off => None,
+            Split::Discover | Split::Only(_) => registry.direction,
+        }
+    })
+}
+
+fn finish<R>((result, registry): (R, Registry)) -> Result<(R, BTreeMap<String, Schema>), Vec<NameCollision>> {
+    if registry.collisions.is_empty() {
+        Ok((result, registry.schemas))
+    } else {
+        Err(registry.collisions)
+    }
+}
+
+fn scoped<R>(split: Split, f: impl FnOnce() -> R) -> (R, Registry) {
     /// Puts the enclosing scope back on drop, so a panic inside `f` cannot strand this thread
     /// inside a collection scope (which would make later inline schemas come out as `$ref`).
     struct Restore(Option<Registry>);
@@ -63,28 +214,177 @@ pub fn collect<R>(f: impl FnOnce() -> R) -> (R, BTreeMap<String, Schema>) {
         }
     }
 
-    let _restore = Restore(ACTIVE.with(|active| active.borrow_mut().replace(Registry::default())));
+    let registry = Registry { split, ..Registry::default() };
+    let _restore = Restore(ACTIVE.with(|active| active.borrow_mut().replace(registry)));
     let result = f();
     let finished = ACTIVE.with(|active| active.borrow_mut().take());
-    (result, finished.map(|registry| registry.schemas).unwrap_or_default())
+    (result, finished.unwrap_or_default())
 }
 
-/// Entry point used by `#[derive(Schematic)]`.
+/// The components whose request and response variants differ, from a discovery pass.
 ///
+/// A component that dropped a field differs outright. One that did not is merged back into a
+/// single component when its variants are identical once every merged name is substituted in —
+/// which may un-merge another that referenced it, so this repeats until nothing changes.
+fn split_components(discovery: &Registry) -> HashSet<String> {
+    let base_of = discovery
+        .variants
+        .iter()
+        .flat_map(|(base, variants)| variants.names.iter().map(move |name| (name.clone(), base.clone())))
+        .collect::<HashMap<_, _>>();
+    let mut split = discovery
+        .variants
+        .iter()
+        .filter(|(_, variants)| variants.stripped)
+        .map(|(base, _)| base.clone())
+        .collect::<HashSet<_>>();
+    loop {
+        let merged = |name: &str| match base_of.get(name) {
+            Some(base) if !split.contains(base) => base.clone(),
+            _ => name.to_string(),
+        };
+        let differing = discovery
+            .variants
+            .iter()
+            .filter(|(base, _)| !split.contains(*base))
+            .filter(|(_, variants)| {
+                let mut rendered = variants.names.iter().filter_map(|name| discovery.schemas.get(name)).map(|schema| {
+                    let mut value = schema.to_value();
+                    rename_refs(&mut value, &merged);
+                    value
+                });
+                let first = rendered.next();
+                rendered.any(|other| Some(other) != first)
+            })
+            .map(|(base, _)| base.clone())
+            .collect::<Vec<_>>();
+        if differing.is_empty() {
+            return split;
+        }
+        split.extend(differing);
+    }
+}
+
+/// Rewrite every reference to a component inside `value` through `rename`: each `$ref`, and each
+/// target of a discriminator `mapping`.
+fn rename_refs(value: &mut Value, rename: &impl Fn(&str) -> String) {
+    let rename_target = |target: &mut String| {
+        if let Some(name) = target.strip_prefix("#/components/schemas/") {
+            *target = format!("#/components/schemas/{}", rename(name));
+        }
+    };
+    match value {
+        Value::Object(object) => {
+            for (key, nested) in object.iter_mut() {
+                match nested {
+                    Value::String(target) if key == "$ref" => rename_target(target),
+                    Value::Object(mapping) if key == "mapping" => mapping.values_mut().for_each(|target| match target {
+                        Value::String(target) => rename_target(target),
+                        nested => rename_refs(nested, rename),
+                    }),
+                    nested => rename_refs(nested, rename),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rename_refs(item, rename)),
+        _ => {}
+    }
+}
+
+/// Drop the properties hidden on `direction`'s side, returning whether there were any.
+fn strip_hidden(schema: &mut Schema, direction: Direction) -> bool {
+    let flag = direction.hidden_flag();
+    let Some(Value::Object(properties)) = schema.extras.get_mut("properties") else {
+        return false;
+    };
+    let hidden = properties
+        .iter()
+        .filter(|(_, property)| property.get(flag) == Some(&Value::Bool(true)))
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    for name in &hidden {
+        properties.remove(name);
+    }
+    if let Some(Value::Array(required)) = schema.extras.get_mut("required") {
+        required.retain(|name| !hidden.iter().any(|hidden| name == hidden));
+    }
+    !hidden.is_empty()
+}
+
+/// Entry point used by `#[derive(Schematic)]`: [`schema_or_ref`] for the type `T`, whose name is
+/// recorded so that another type registering under `name` is reported as a [`NameCollision`].
+/// `T`'s [`example`](Schematic::example), if any, is set on the built schema.
+pub fn component<T: Schematic + ?Sized>(name: &str, required: bool, build: impl FnOnce() -> EnhancedSchema) -> EnhancedSchema {
+    register(name, Some(std::any::type_name::<T>()), required, || {
+        let mut built = build();
+        if let Some(example) = T::example() {
+            built.schema.extras.entry("example".to_string()).or_insert(example);
+        }
+        built
+    })
+}
+
+/// Entry point used by `#[derive(Schematic)]` for one branch of a tagged enum `T`: registered under
+/// `name` as its own component, so the enum's `discriminator` can map the tag to it. Returns the
+/// `$ref`, or the branch itself outside a collection scope.
+pub fn variant<T: ?Sized>(name: &str, build: impl FnOnce() -> Value) -> Value {
+    let schema = register(name, Some(std::any::type_name::<T>()), true, || EnhancedSchema {
+        schema: serde_json::from_value(build()).expect("a variant schema is a JSON object"),
+        required: true,
+    });
+    schema.schema.to_value()
+}
+
 /// Outside a collection scope this simply returns `build()` — the historical inline behavior.
 /// Inside one, the built schema is registered under `name` and a `$ref` to it is returned; a name
 /// that is already registered (or mid-construction, i.e. recursive) skips rebuilding entirely.
 pub fn schema_or_ref(name: &str, required: bool, build: impl FnOnce() -> EnhancedSchema) -> EnhancedSchema {
+    register(name, None, required, build)
+}
+
+fn register(name: &str, owner: Option<&'static str>, required: bool, build: impl FnOnce() -> EnhancedSchema) -> EnhancedSchema {
     let collecting = ACTIVE.with(|active| active.borrow().is_some());
     if !collecting {
         return build();
     }
 
+    let base = name;
+    let direction = ACTIVE.with(|active| {
+        let active = active.borrow();
+        let registry = active.as_ref()?;
+        let direction = registry.direction?;
+        match &registry.split {
+            Split::Off => None,
+            Split::Discover => Some(direction),
+            Split::Only(split) => split.contains(base).then_some(direction),
+        }
+    });
+    let directed_name = direction.map(|direction| format!("{base}{}", direction.suffix()));
+    let name = directed_name.as_deref().unwrap_or(base);
+
     let known = ACTIVE.with(|active| {
-        active
-            .borrow()
-            .as_ref()
-            .is_some_and(|registry| registry.schemas.contains_key(name) || registry.in_progress.contains(name))
+        let mut active = active.borrow_mut();
+        let Some(registry) = active.as_mut() else {
+            return false;
+        };
+        let known = registry.schemas.contains_key(name) || registry.in_progress.contains(name);
+        if let Some(owner) = owner {
+            match registry.owners.get(name) {
+                Some(first) if *first != owner => {
+                    let mut types = [*first, owner];
+                    types.sort();
+                    let collision = NameCollision { name: name.to_string(), types };
+                    if !registry.collisions.contains(&collision) {
+                        registry.collisions.push(collision);
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    registry.owners.insert(name.to_string(), owner);
+                }
+            }
+        }
+        known
     });
 
     if !known {
@@ -94,11 +394,17 @@ pub fn schema_or_ref(name: &str, required: bool, build: impl FnOnce() -> Enhance
             }
         });
         // Called with no borrow held: `build` re-enters this function for nested field types.
-        let built = build();
+        let mut built = build();
+        let stripped = direction.is_some_and(|direction| strip_hidden(&mut built.schema, direction));
         ACTIVE.with(|active| {
             if let Some(registry) = active.borrow_mut().as_mut() {
                 registry.in_progress.remove(name);
                 registry.schemas.insert(name.to_string(), built.schema);
+                if matches!(registry.split, Split::Discover) && direction.is_some() {
+                    let variants = registry.variants.entry(base.to_string()).or_default();
+                    variants.names.insert(name.to_string());
+                    variants.stripped |= stripped;
+                }
             }
         });
     }
@@ -159,6 +465,79 @@ mod tests {
         assert_eq!(outer.keys().collect::<Vec<_>>(), ["Outer"]);
     }
 
+    #[test]
+    fn two_types_under_one_name_are_reported() {
+        let collisions = try_collect(|| {
+            component::<u8>("Model", true, object_schema);
+            component::<u16>("Model", true, object_schema);
+            // The same type again is just a reuse.
+            component::<u8>("Model", true, object_schema);
+        })
+        .unwrap_err();
+
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].name, "Model");
+        assert_eq!(collisions[0].types, ["u16", "u8"]);
+        assert!(try_collect(|| component::<u8>("Model", true, object_schema)).is_ok());
+    }
+
+    /// `{ id (read-only), password (write-only), name }`.
+    fn account_schema() -> EnhancedSchema {
+        let mut schema = object_schema();
+        schema.schema.extras.insert(
+            "properties".to_string(),
+            serde_json::json!({
+                "id": { "type": "integer", "readOnly": true },
+                "password": { "type": "string", "writeOnly": true },
+                "name": { "type": "string" },
+            }),
+        );
+        schema
+            .schema
+            .extras
+            .insert("required".to_string(), serde_json::json!(["id", "password", "name"]));
+        schema
+    }
+
+    /// Holds an account, so it differs per direction without hiding anything itself.
+    fn team_schema() -> EnhancedSchema {
+        let mut schema = object_schema();
+        let owner = schema_or_ref("Account", true, account_schema).schema.to_value();
+        schema.schema.extras.insert("properties".to_string(), serde_json::json!({ "owner": owner }));
+        schema
+    }
+
+    #[test]
+    fn directions_split_only_the_types_that_differ() {
+        let build = || {
+            for direction in [Direction::Request, Direction::Response] {
+                directed(direction, || {
+                    schema_or_ref("Team", true, team_schema);
+                    schema_or_ref("Tag", true, object_schema);
+                });
+            }
+        };
+        let (_, schemas) = try_collect_by_direction(build).unwrap();
+
+        assert_eq!(
+            schemas.keys().collect::<Vec<_>>(),
+            ["AccountRequest", "AccountResponse", "Tag", "TeamRequest", "TeamResponse"]
+        );
+        let request = schemas["AccountRequest"].to_value();
+        assert_eq!(request["properties"].as_object().unwrap().keys().collect::<Vec<_>>(), ["name", "password"]);
+        assert_eq!(request["required"], serde_json::json!(["password", "name"]));
+        let response = schemas["AccountResponse"].to_value();
+        assert_eq!(response["properties"].as_object().unwrap().keys().collect::<Vec<_>>(), ["id", "name"]);
+        assert_eq!(
+            schemas["TeamResponse"].to_value()["properties"]["owner"]["$ref"],
+            "#/components/schemas/AccountResponse"
+        );
+
+        // Without splitting, the flags are left for clients to interpret.
+        let (_, schemas) = try_collect(build).unwrap();
+        assert_eq!(schemas.keys().collect::<Vec<_>>(), ["Account", "Tag", "Team"]);
+    }
+
     #[test]
     fn a_panic_does_not_strand_the_scope() {
         let hook = std::panic::take_hook();
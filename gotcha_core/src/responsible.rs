@@ -19,7 +19,7 @@ pub trait Responsible {
     fn response() -> Responses;
 }
 
-/// Build a `200 application/json` response whose body schema is `T`'s schema.
+/// Build a `200 application/json` response whose body schema and example are `T`'s.
 fn json_response<T: Schematic>() -> Responses {
     let response_schema = T::generate_schema();
     let mut response = Responses {
@@ -35,7 +35,7 @@ fn json_response<T: Schematic>() -> Responses {
                 "application/json".to_string(),
                 MediaType {
                     schema: Some(Referenceable::Data(response_schema.schema)),
-                    example: None,
+                    example: T::example(),
                     examples: None,
                     encoding: None,
                 },
@@ -116,7 +116,7 @@ fn schema_error_response<E: Schematic>() -> Referenceable<Response> {
             "application/json".to_string(),
             MediaType {
                 schema: Some(Referenceable::Data(schema.schema)),
-                example: None,
+                example: E::example(),
                 examples: None,
                 encoding: None,
             },
@@ -0,0 +1,421 @@
+//! `Schematic` for the rest of the standard library's serializable types, and for the date, time
+//! and URL types of the crates behind the `url`, `time` and `jiff` features.
+//!
+//! Each schema follows the type's serde representation, which is not always a string: a
+//! [`Duration`] serializes as `{secs, nanos}`, and a tuple or fixed array as a JSON array of
+//! exactly its length.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use oas::Schema;
+use serde_json::{json, Value};
+
+use crate::{EnhancedSchema, Schematic};
+
+/// A schema of `type_` with the given extra keywords.
+fn schema_with(type_: &str, description: Option<String>, extras: Value) -> EnhancedSchema {
+    let Value::Object(extras) = extras else {
+        unreachable!("extras are always an object")
+    };
+    EnhancedSchema {
+        schema: Schema {
+            _type: Some(type_.to_string()),
+            format: None,
+            nullable: None,
+            description,
+            extras: extras.into_iter().collect(),
+        },
+        required: true,
+    }
+}
+
+/// The `items` schema for a list of element schemas: the element itself when they are all the
+/// same, otherwise `oneOf` the distinct ones. OpenAPI 3.0 has no positional `prefixItems`.
+fn items_of(elements: Vec<Value>) -> Value {
+    let mut distinct: Vec<Value> = Vec::new();
+    for element in elements {
+        if !distinct.contains(&element) {
+            distinct.push(element);
+        }
+    }
+    match distinct.len() {
+        1 => distinct.remove(0),
+        _ => json!({ "oneOf": distinct }),
+    }
+}
+
+/// A string with a `format`, such as a date or a URL.
+macro_rules! impl_formatted_string {
+    ($($t:ty => $name:expr, $format:expr;)*) => {$(
+        impl Schematic for $t {
+            fn name() -> &'static str {
+                $name
+            }
+            fn required() -> bool {
+                true
+            }
+            fn type_() -> &'static str {
+                "string"
+            }
+            fn format() -> Option<String> {
+                $format.map(str::to_string)
+            }
+        }
+    )*};
+}
+
+impl_formatted_string! {
+    Cow<'_, str> => "string", None::<&str>;
+    Ipv4Addr => "ipv4", Some("ipv4");
+    Ipv6Addr => "ipv6", Some("ipv6");
+    SocketAddr => "socket_addr", None::<&str>;
+    chrono::NaiveTime => "time", Some("time");
+}
+
+impl_primitive_type! { i128, "i128", "integer"}
+impl_primitive_type! { u128, "u128", "integer"}
+
+impl Schematic for char {
+    fn name() -> &'static str {
+        "char"
+    }
+    fn required() -> bool {
+        true
+    }
+    fn type_() -> &'static str {
+        "string"
+    }
+    fn generate_schema() -> EnhancedSchema {
+        schema_with("string", Self::doc(), json!({ "minLength": 1, "maxLength": 1 }))
+    }
+}
+
+/// Either address family; the string is one format or the other.
+impl Schematic for IpAddr {
+    fn name() -> &'static str {
+        "ip"
+    }
+    fn required() -> bool {
+        true
+    }
+    fn type_() -> &'static str {
+        "string"
+    }
+    fn generate_schema() -> EnhancedSchema {
+        schema_with("string", Self::doc(), json!({ "anyOf": [{ "format": "ipv4" }, { "format": "ipv6" }] }))
+    }
+}
+
+/// serde writes a `Duration` as its whole seconds and the remaining nanoseconds.
+impl Schematic for Duration {
+    fn name() -> &'static str {
+        "duration"
+    }
+    fn required() -> bool {
+        true
+    }
+    fn type_() -> &'static str {
+        "object"
+    }
+    fn generate_schema() -> EnhancedSchema {
+        schema_with(
+            "object",
+            Self::doc(),
+            json!({
+                "properties": {
+                    "secs": { "type": "integer", "minimum": 0 },
+                    "nanos": { "type": "integer", "minimum": 0, "maximum": 999_999_999 }
+                },
+                "required": ["secs", "nanos"]
+            }),
+        )
+    }
+}
+
+/// A non-zero integer: an unsigned one starts at 1, a signed one excludes 0.
+macro_rules! impl_non_zero {
+    ($($t:ty => $inner:ty, $unsigned:expr;)*) => {$(
+        impl Schematic for $t {
+            fn name() -> &'static str {
+                <$inner as Schematic>::name()
+            }
+            fn required() -> bool {
+                true
+            }
+            fn type_() -> &'static str {
+                "integer"
+            }
+            fn generate_schema() -> EnhancedSchema {
+                if $unsigned {
+                    schema_with("integer", Self::doc(), json!({ "minimum": 1 }))
+                } else {
+                    schema_with("integer", Self::doc(), json!({ "not": { "enum": [0] } }))
+                }
+            }
+        }
+    )*};
+}
+
+impl_non_zero! {
+    NonZeroU8 => u8, true;
+    NonZeroU16 => u16, true;
+    NonZeroU32 => u32, true;
+    NonZeroU64 => u64, true;
+    NonZeroU128 => u128, true;
+    NonZeroUsize => usize, true;
+    NonZeroI8 => i8, false;
+    NonZeroI16 => i16, false;
+    NonZeroI32 => i32, false;
+    NonZeroI64 => i64, false;
+    NonZeroI128 => i128, false;
+    NonZeroIsize => isize, false;
+}
+
+/// Smart pointers serialize as what they point to.
+macro_rules! impl_pointer {
+    ($($pointer:ident),*) => {$(
+        impl<T: Schematic + ?Sized> Schematic for $pointer<T> {
+            fn name() -> &'static str {
+                T::name()
+            }
+            fn component_name() -> String {
+                T::component_name()
+            }
+            fn required() -> bool {
+                T::required()
+            }
+            fn nullable() -> Option<bool> {
+                T::nullable()
+            }
+            fn type_() -> &'static str {
+                T::type_()
+            }
+            fn doc() -> Option<String> {
+                T::doc()
+            }
+            fn format() -> Option<String> {
+                T::format()
+            }
+            fn example() -> Option<Value> {
+                T::example()
+            }
+            fn fields() -> Vec<(&'static str, EnhancedSchema)> {
+                T::fields()
+            }
+            fn elements() -> Option<Vec<EnhancedSchema>> {
+                T::elements()
+            }
+            fn generate_schema() -> EnhancedSchema {
+                T::generate_schema()
+            }
+            fn flatten_schema() -> Option<Value> {
+                T::flatten_schema()
+            }
+        }
+    )*};
+}
+
+impl_pointer!(Box, Arc, Rc);
+
+impl<T: Schematic> Schematic for VecDeque<T> {
+    fn name() -> &'static str {
+        T::name()
+    }
+    fn component_name() -> String {
+        format!("Vec_{}", T::component_name())
+    }
+    fn required() -> bool {
+        true
+    }
+    fn type_() -> &'static str {
+        "array"
+    }
+    fn generate_schema() -> EnhancedSchema {
+        schema_with("array", Self::doc(), json!({ "items": T::generate_schema().schema.to_value() }))
+    }
+}
+
+impl<T: Schematic> Schematic for BTreeSet<T> {
+    fn name() -> &'static str {
+        T::name()
+    }
+    fn component_name() -> String {
+        format!("Set_{}", T::component_name())
+    }
+    fn required() -> bool {
+        true
+    }
+    fn type_() -> &'static str {
+        "array"
+    }
+    fn generate_schema() -> EnhancedSchema {
+        schema_with(
+            "array",
+            Self::doc(),
+            json!({ "items": T::generate_schema().schema.to_value(), "uniqueItems": true }),
+        )
+    }
+}
+
+impl<K: ToString, V: Schematic> Schematic for BTreeMap<K, V> {
+    fn name() -> &'static str {
+        V::name()
+    }
+    fn component_name() -> String {
+        format!("Map_{}", V::component_name())
+    }
+    fn required() -> bool {
+        true
+    }
+    fn type_() -> &'static str {
+        "object"
+    }
+    fn generate_schema() -> EnhancedSchema {
+        schema_with("object", Self::doc(), json!({ "additionalProperties": V::generate_schema().schema.to_value() }))
+    }
+}
+
+/// serde writes `[T; N]` as a JSON array of exactly `N` elements.
+impl<T: Schematic, const N: usize> Schematic for [T; N] {
+    fn name() -> &'static str {
+        T::name()
+    }
+    fn component_name() -> String {
+        format!("Array{}_{}", N, T::component_name())
+    }
+    fn required() -> bool {
+        true
+    }
+    fn type_() -> &'static str {
+        "array"
+    }
+    fn generate_schema() -> EnhancedSchema {
+        schema_with(
+            "array",
+            Self::doc(),
+            json!({ "items": T::generate_schema().schema.to_value(), "minItems": N, "maxItems": N }),
+        )
+    }
+}
+
+/// serde writes a tuple as a JSON array with one element per field.
+macro_rules! impl_tuple {
+    ($len:expr => $($name:ident),+) => {
+        impl<$($name: Schematic),+> Schematic for ($($name,)+) {
+            fn name() -> &'static str {
+                "tuple"
+            }
+            fn component_name() -> String {
+                ["Tuple".to_string(), $($name::component_name()),+].join("_")
+            }
+            fn required() -> bool {
+                true
+            }
+            fn type_() -> &'static str {
+                "array"
+            }
+            fn elements() -> Option<Vec<EnhancedSchema>> {
+                Some(vec![$($name::generate_schema()),+])
+            }
+            fn generate_schema() -> EnhancedSchema {
+                let elements = vec![$($name::generate_schema().schema.to_value()),+];
+                schema_with("array", Self::doc(), json!({ "items": items_of(elements), "minItems": $len, "maxItems": $len }))
+            }
+        }
+    };
+}
+
+impl_tuple!(1 => T1);
+impl_tuple!(2 => T1, T2);
+impl_tuple!(3 => T1, T2, T3);
+impl_tuple!(4 => T1, T2, T3, T4);
+impl_tuple!(5 => T1, T2, T3, T4, T5);
+impl_tuple!(6 => T1, T2, T3, T4, T5, T6);
+impl_tuple!(7 => T1, T2, T3, T4, T5, T6, T7);
+impl_tuple!(8 => T1, T2, T3, T4, T5, T6, T7, T8);
+
+#[cfg(feature = "url")]
+impl_formatted_string! {
+    url::Url => "url", Some("uri");
+}
+
+// `time` serializes these as strings only with its human-readable serde formats or with
+// `time::serde::rfc3339`; the schema documents that string form.
+#[cfg(feature = "time")]
+impl_formatted_string! {
+    time::OffsetDateTime => "datetime", Some("date-time");
+    time::PrimitiveDateTime => "datetime", Some("date-time");
+    time::Date => "date", Some("date");
+    time::Time => "time", Some("time");
+}
+
+#[cfg(feature = "jiff")]
+impl_formatted_string! {
+    jiff::Timestamp => "datetime", Some("date-time");
+    // RFC 9557: an RFC 3339 timestamp followed by the `[time zone]`, which `date-time` rejects.
+    jiff::Zoned => "zoned", None::<&str>;
+    jiff::civil::DateTime => "datetime", Some("date-time");
+    jiff::civil::Date => "date", Some("date");
+    jiff::civil::Time => "time", Some("time");
+    jiff::Span => "duration", Some("duration");
+    jiff::SignedDuration => "duration", Some("duration");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema<T: Schematic>() -> Value {
+        T::generate_schema().schema.to_value()
+    }
+
+    #[test]
+    fn fixed_arrays_and_tuples_have_their_length() {
+        let array = schema::<[u8; 4]>();
+        assert_eq!((array["minItems"].clone(), array["maxItems"].clone()), (json!(4), json!(4)));
+
+        let pair = schema::<(String, u32)>();
+        assert_eq!(pair["maxItems"], 2);
+        assert_eq!(pair["items"]["oneOf"].as_array().unwrap().len(), 2);
+        assert_eq!(schema::<(u8, u8)>()["items"]["type"], "integer", "identical elements need no oneOf");
+    }
+
+    #[test]
+    fn pointers_are_transparent() {
+        assert_eq!(schema::<Box<chrono::NaiveTime>>(), schema::<chrono::NaiveTime>());
+        assert_eq!(schema::<Arc<Vec<u8>>>(), schema::<Vec<u8>>());
+        assert!(<Arc<(u8, bool)> as Schematic>::elements().is_some());
+    }
+
+    #[test]
+    fn non_zero_integers_exclude_zero() {
+        assert_eq!(schema::<NonZeroU32>()["minimum"], 1);
+        assert_eq!(schema::<NonZeroI64>()["not"]["enum"], json!([0]));
+    }
+
+    #[test]
+    fn duration_is_seconds_and_nanoseconds() {
+        let duration = schema::<Duration>();
+        assert_eq!(duration["required"], json!(["secs", "nanos"]));
+        assert_eq!(
+            serde_json::to_value(Duration::from_millis(1500)).unwrap(),
+            json!({"secs": 1, "nanos": 500_000_000})
+        );
+    }
+
+    #[test]
+    fn strings_carry_their_format() {
+        assert_eq!(schema::<Ipv4Addr>()["format"], "ipv4");
+        assert_eq!(schema::<char>()["maxLength"], 1);
+        assert_eq!(schema::<BTreeSet<String>>()["uniqueItems"], true);
+        assert_eq!(schema::<chrono::DateTime<chrono::FixedOffset>>()["format"], "date-time");
+    }
+}
@@ -7,6 +7,8 @@
 //!
 //! - `#[api]` - Generates OpenAPI documentation for route handlers
 //! - `#[derive(Schematic)]` - Generates OpenAPI schemas for request/response types
+//! - `#[task]` - Declares a scheduled task, started with the application
+//! - `#[subscribe]` - Declares a subscriber to events published on a `Messager`
 //!
 //! ## Example Usage
 //!
@@ -30,10 +32,12 @@
 
 use darling::FromMeta;
 use proc_macro::TokenStream;
-use proc_macro_error::{abort, proc_macro_error};
 
+mod multipart;
 mod route;
 mod schematic;
+mod subscribe;
+mod task;
 
 pub(crate) mod utils;
 
@@ -49,6 +53,11 @@ pub(crate) mod utils;
 /// - `summary` - Human-readable operation summary (defaults to the id in Title Case)
 /// - `deprecated` - Marks the operation as deprecated, e.g. `#[api(deprecated)]`
 /// - `security` - Name of a required security scheme, e.g. `#[api(security = "bearerAuth")]`
+/// - `since` / `until` - The API versions the operation exists in, `[since, until)`, when the
+///   router serves several; see `gotcha::versioning`. E.g. `#[api(since = "2", until = "3")]`
+///
+/// Without gotcha's `openapi` feature nothing is documented, and only `since` and `until` have an
+/// effect.
 ///
 /// ## Example
 ///
@@ -69,6 +78,65 @@ pub fn api(args: TokenStream, input_stream: TokenStream) -> TokenStream {
     route::request_handler(args, input_stream)
 }
 
+/// Declares an async function as a scheduled task, registered when the application starts.
+///
+/// The function's arguments are extracted from the application context on every run, like a
+/// handler's: `State<AppState>`, `State<Config>`, `Messager<S, C>`, `GotchaContext<S, C>`, or
+/// anything implementing `gotcha::task::FromTaskContext`. It returns `()` or a `Result` whose
+/// error is logged and recorded.
+///
+/// ## Attributes
+///
+/// - `cron` - A cron expression with a seconds field, e.g. `cron = "0 */5 * * * *"`
+/// - `interval` - A fixed interval, e.g. `interval = "30s"`; units are `ms`, `s`, `m`, `h` and `d`
+/// - `timezone` - The time zone `cron` is evaluated in, e.g. `timezone = "Europe/Berlin"`
+/// - `name` - The name the task registers under; the function's name by default
+///
+/// Exactly one of `cron` and `interval` is required.
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// use gotcha::prelude::*;
+/// use gotcha::task;
+///
+/// #[task(interval = "30s")]
+/// async fn refresh_cache(State(state): State<AppState>) {
+///     state.cache.refresh().await;
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn task(args: TokenStream, input_stream: TokenStream) -> TokenStream {
+    task::handler(args, input_stream)
+}
+
+/// Declares an async function as a subscriber to the events of its first argument's type, which
+/// `Messager::publish` delivers to it.
+///
+/// The optional second argument is the publisher's `GotchaContext<S, C>` or `Messager<S, C>`; a
+/// subscriber that takes one only receives events published with a context of that type. It
+/// returns `()` or a `Result` whose error is logged. Events are cloned for each subscriber.
+///
+/// ## Attributes
+///
+/// - `name` - The name the subscriber is logged under; the function's name by default
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// use gotcha::prelude::*;
+/// use gotcha::subscribe;
+///
+/// #[subscribe]
+/// async fn send_receipt(order: OrderPlaced, messager: Messager<AppState, Config>) -> Result<(), MailError> {
+///     messager.state().mailer.receipt(order.id).await
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn subscribe(args: TokenStream, input_stream: TokenStream) -> TokenStream {
+    subscribe::handler(args, input_stream)
+}
+
 /// Derives OpenAPI schema generation for structs and enums.
 ///
 /// This derive macro automatically implements the `Schematic` trait,
@@ -122,13 +190,36 @@ pub fn api(args: TokenStream, input_stream: TokenStream) -> TokenStream {
 /// }
 /// ```
 #[proc_macro_derive(Schematic, attributes(schematic))]
-#[proc_macro_error]
 pub fn derive_parameter(input: TokenStream) -> TokenStream {
     let stream2 = proc_macro2::TokenStream::from(input);
-    match schematic::handler(stream2) {
-        Ok(stream) => proc_macro::TokenStream::from(stream),
-        Err((span, msg)) => abort! {span, msg},
-    }
+    proc_macro::TokenStream::from(schematic::handler(stream2))
+}
+
+/// Derives `gotcha::multipart::MultipartForm`, so the struct can be read from a
+/// `multipart/form-data` body with `TypedMultipart<T>`.
+///
+/// Each named field is one part, named like serde names it (`#[serde(rename)]` and
+/// `#[serde(rename_all)]` are honoured, and `#[serde(skip)]` fields take their default), so the
+/// parts match the schema `#[derive(Schematic)]` documents. A field's type is a text type parsed
+/// with `FromStr`, `UploadedFile`, or `Option`/`Vec` of either.
+///
+/// ## Field attributes
+///
+/// - `limit` - The part's largest size: bytes (`limit = 1024`) or with a unit (`limit = "5MiB"`)
+/// - `content_type` - An accepted content type, repeatable; `image/*` accepts any image
+/// - `temp_file` - Stream an `UploadedFile` to a temporary file instead of memory
+///
+/// ```rust,ignore
+/// #[derive(MultipartForm, Schematic)]
+/// struct Upload {
+///     title: String,
+///     #[multipart(limit = "5MiB", content_type = "image/*")]
+///     avatar: UploadedFile,
+/// }
+/// ```
+#[proc_macro_derive(MultipartForm, attributes(multipart, serde))]
+pub fn derive_multipart_form(input: TokenStream) -> TokenStream {
+    multipart::handler(proc_macro2::TokenStream::from(input)).into()
 }
 
 /// Marks a struct as a Gotcha application state so it can be extracted directly
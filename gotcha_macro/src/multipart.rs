@@ -0,0 +1,128 @@
+use darling::ast::Data;
+use darling::util::{Flag, Ignored};
+use darling::{FromDeriveInput, FromField};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse2, DeriveInput};
+
+use crate::utils::{get_serde_name, has_serde_skip, parse_serde_rename, parse_serde_rename_all};
+
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(multipart), forward_attrs(serde), supports(struct_named))]
+struct MultipartOpts {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: Data<Ignored, MultipartFieldOpt>,
+    attrs: Vec<syn::Attribute>,
+}
+
+#[derive(Debug, FromField)]
+#[darling(attributes(multipart), forward_attrs(serde))]
+struct MultipartFieldOpt {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    attrs: Vec<syn::Attribute>,
+
+    limit: Option<ByteSize>,
+    #[darling(multiple)]
+    content_type: Vec<String>,
+    temp_file: Flag,
+}
+
+/// `limit = 1024` or `limit = "5MiB"`, in bytes.
+#[derive(Debug, Clone, Copy)]
+struct ByteSize(u64);
+
+impl darling::FromMeta for ByteSize {
+    fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+        match value {
+            syn::Lit::Int(v) => v.base10_parse::<u64>().map(ByteSize).map_err(darling::Error::from),
+            syn::Lit::Str(v) => {
+                let text = v.value();
+                let text = text.trim();
+                let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+                let (number, unit) = text.split_at(split);
+                let multiplier = match unit.trim() {
+                    "" | "B" => 1,
+                    "KB" => 1_000,
+                    "KiB" => 1 << 10,
+                    "MB" => 1_000_000,
+                    "MiB" => 1 << 20,
+                    "GB" => 1_000_000_000,
+                    "GiB" => 1 << 30,
+                    _ => return Err(darling::Error::custom("unknown unit; use B, KB, KiB, MB, MiB, GB or GiB").with_span(v)),
+                };
+                let number: u64 = number
+                    .parse()
+                    .map_err(|_| darling::Error::custom("expected a size such as `5MiB`").with_span(v))?;
+                number
+                    .checked_mul(multiplier)
+                    .map(ByteSize)
+                    .ok_or_else(|| darling::Error::custom("the size does not fit in 64 bits").with_span(v))
+            }
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+}
+
+pub(crate) fn handler(input: TokenStream2) -> TokenStream2 {
+    let x1 = match parse2::<DeriveInput>(input) {
+        Ok(input) => input,
+        Err(e) => return e.to_compile_error(),
+    };
+    let opts = match MultipartOpts::from_derive_input(&x1) {
+        Ok(opts) => opts,
+        Err(e) => return e.write_errors(),
+    };
+    if !opts.generics.params.is_empty() {
+        return syn::Error::new_spanned(&opts.generics, "MultipartForm does not support generic structs").to_compile_error();
+    }
+    let ident = &opts.ident;
+    let rename_all = parse_serde_rename_all(&opts.attrs);
+    let fields = opts.data.take_struct().expect("only structs are supported").fields;
+
+    let mut parts = vec![];
+    let mut assignments = vec![];
+    for field in fields {
+        let field_ident = field.ident.expect("named fields");
+        let ty = &field.ty;
+        // serde skips the field in the schema, so there is no part for it either.
+        if has_serde_skip(&field.attrs) {
+            assignments.push(quote! { #field_ident: ::core::default::Default::default() });
+            continue;
+        }
+        let name = get_serde_name(&field_ident.to_string(), parse_serde_rename(&field.attrs).as_deref(), rename_all);
+        let limit = match field.limit {
+            Some(ByteSize(limit)) => quote! { ::core::option::Option::Some(#limit) },
+            None => quote! { ::core::option::Option::None },
+        };
+        let content_types = &field.content_type;
+        let temp_file = field.temp_file.is_present();
+        parts.push(quote! {
+            ::gotcha::multipart::PartSpec {
+                name: #name,
+                file: <#ty as ::gotcha::multipart::MultipartValue>::FILE,
+                limit: #limit,
+                content_types: &[#(#content_types),*],
+                temp_file: #temp_file,
+            }
+        });
+        assignments.push(quote! {
+            #field_ident: <#ty as ::gotcha::multipart::MultipartValue>::collect(values.next().unwrap_or_default(), #name)?
+        });
+    }
+
+    quote! {
+        impl ::gotcha::multipart::MultipartForm for #ident {
+            fn parts() -> ::std::vec::Vec<::gotcha::multipart::PartSpec> {
+                ::std::vec![#(#parts),*]
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn from_parts(values: ::std::vec::Vec<::std::vec::Vec<::gotcha::multipart::PartValue>>) -> ::core::result::Result<Self, ::gotcha::multipart::MultipartRejection> {
+                let mut values = values.into_iter();
+                ::core::result::Result::Ok(Self { #(#assignments),* })
+            }
+        }
+    }
+}
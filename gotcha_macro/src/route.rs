@@ -1,11 +1,11 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, AttributeArgs, FnArg, ItemFn, ReturnType};
+use syn::{parse_macro_input, AttributeArgs, FnArg, ItemFn, NestedMeta, ReturnType};
 use uuid::Uuid;
 
 use darling::util::Flag;
 
-use crate::utils::AttributesExt;
+use crate::utils::{AttrArg, AttrValue, AttributesExt};
 use crate::FromMeta;
 
 #[derive(Debug, FromMeta)]
@@ -18,10 +18,85 @@ pub struct RouteMeta {
     deprecated: Flag,
     /// Name of a security scheme required for this operation (empty scopes).
     security: Option<String>,
+    /// First API version the operation exists in.
+    since: Option<String>,
+    /// API version the operation is removed in.
+    until: Option<String>,
+}
+
+/// `example(name = "...", value = <expr>, summary = "...", response)`: a named example of the
+/// request body, or with `response` (`= "404"`, default `"200"`) of that response's body.
+struct ExampleMeta {
+    name: syn::LitStr,
+    summary: Option<syn::LitStr>,
+    response: Option<String>,
+    value: syn::Expr,
+}
+
+impl ExampleMeta {
+    fn from_args(span: proc_macro2::Span, args: impl IntoIterator<Item = AttrArg>) -> syn::Result<Self> {
+        let (mut name, mut summary, mut response, mut value) = (None, None, None, None);
+        for arg in args {
+            let expr = match arg.value {
+                AttrValue::Expr(expr) => Some(*expr),
+                AttrValue::Flag => None,
+                AttrValue::List(_) => return Err(syn::Error::new(arg.name.span(), "unexpected list")),
+            };
+            let text = |expr: &syn::Expr| match expr {
+                syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }) => Some(lit.clone()),
+                _ => None,
+            };
+            match (arg.name.to_string().as_str(), expr) {
+                ("name", Some(expr)) if text(&expr).is_some() => name = text(&expr),
+                ("summary", Some(expr)) if text(&expr).is_some() => summary = text(&expr),
+                ("response", None) => response = Some("200".to_string()),
+                ("response", Some(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(lit), .. }))) => response = Some(lit.value()),
+                ("response", Some(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(lit), .. }))) => response = Some(lit.base10_digits().to_string()),
+                ("value", Some(expr)) => value = Some(expr),
+                _ => {
+                    return Err(syn::Error::new(
+                        arg.name.span(),
+                        "expected `name = \"..\"`, `value = ..`, `summary = \"..\"` or `response`",
+                    ))
+                }
+            }
+        }
+        Ok(ExampleMeta {
+            name: name.ok_or_else(|| syn::Error::new(span, "example needs a `name`"))?,
+            summary,
+            response,
+            value: value.ok_or_else(|| syn::Error::new(span, "example needs a `value`"))?,
+        })
+    }
+}
+
+/// Splits the `example(..)` entries, whose values are expressions, from the options darling parses.
+fn split_examples(args: proc_macro2::TokenStream) -> syn::Result<(Vec<ExampleMeta>, AttributeArgs)> {
+    let mut examples = vec![];
+    let mut rest = vec![];
+    for arg in AttrArg::parse_list(args)? {
+        if arg.name != "example" {
+            rest.push(arg);
+            continue;
+        }
+        let span = arg.name.span();
+        match arg.value {
+            AttrValue::List(items) => examples.push(ExampleMeta::from_args(span, items)?),
+            _ => return Err(syn::Error::new(span, "expected `example(name = \"..\", value = ..)`")),
+        }
+    }
+    let rest = syn::parse::Parser::parse2(
+        syn::punctuated::Punctuated::<NestedMeta, syn::Token![,]>::parse_terminated,
+        quote! { #(#rest),* },
+    )?;
+    Ok((examples, rest.into_iter().collect()))
 }
 
 pub(crate) fn request_handler(args: TokenStream, input_stream: TokenStream) -> TokenStream {
-    let attr_args = parse_macro_input!(args as AttributeArgs);
+    let (examples, attr_args) = match split_examples(args.into()) {
+        Ok(v) => v,
+        Err(e) => return TokenStream::from(e.to_compile_error()),
+    };
 
     let args = match RouteMeta::from_list(&attr_args) {
         Ok(v) => v,
@@ -46,11 +121,61 @@ pub(crate) fn request_handler(args: TokenStream, input_stream: TokenStream) -> T
     } else {
         quote! { None }
     };
+    // Serialized through serde, so each example is exactly what goes over the wire.
+    let examples = examples.into_iter().map(|example| {
+        let ExampleMeta {
+            name,
+            summary,
+            response,
+            value,
+        } = example;
+        let summary = match summary {
+            Some(summary) => quote! { Some(#summary) },
+            None => quote! { None },
+        };
+        let response = match response {
+            Some(response) => quote! { Some(#response) },
+            None => quote! { None },
+        };
+        quote! {
+            ::gotcha::openapi::OperationExample {
+                name: #name,
+                summary: #summary,
+                response: #response,
+                value: || ::gotcha::serde_json::to_value(&(#value)).expect("#[api(example)] must serialize"),
+            }
+        }
+    });
     let mut input = parse_macro_input!(input_stream as ItemFn);
 
     let fn_ident = input.sig.ident.clone();
     let fn_ident_string = fn_ident.to_string();
 
+    // Version bounds are registered apart from the operation, so they narrow the routes even
+    // without the `openapi` feature.
+    let version_bounds = match (&meta.since, &meta.until) {
+        (None, None) => quote! {},
+        (since, until) => {
+            let since = match since {
+                Some(since) => quote! { Some(#since) },
+                None => quote! { None },
+            };
+            let until = match until {
+                Some(until) => quote! { Some(#until) },
+                None => quote! { None },
+            };
+            quote! {
+                ::gotcha::inventory::submit! {
+                    ::gotcha::versioning::VersionBounds {
+                        type_name: concat!(module_path!(), "::", #fn_ident_string),
+                        since: #since,
+                        until: #until,
+                    }
+                }
+            }
+        }
+    };
+
     let operation_id = meta.id.unwrap_or(fn_ident_string.clone());
 
     let docs = match input.attrs.get_doc() {
@@ -116,10 +241,20 @@ pub(crate) fn request_handler(args: TokenStream, input_stream: TokenStream) -> T
         }
     });
 
+    if !cfg!(feature = "openapi") {
+        // Without the `openapi` feature there is no spec to document the handler in.
+        return TokenStream::from(quote! {
+            #input
+            #version_bounds
+        });
+    }
+
     let ret = quote! {
 
         #input
 
+        #version_bounds
+
         static #uuid_ident : ::gotcha::Lazy<Vec<Box<dyn Fn(String) -> ::gotcha::Either<Vec<::gotcha::oas::Parameter>, ::gotcha::oas::RequestBody> + Send + Sync + 'static>>> = ::gotcha::Lazy::new(||{
                     vec![
                     #( #params_token , )*
@@ -137,8 +272,10 @@ pub(crate) fn request_handler(args: TokenStream, input_stream: TokenStream) -> T
                 description: #docs,
                 deprecated: #deprecated,
                 security: #security,
+                examples: &[#(#examples),*],
                 parameters: &#uuid_ident,
                 responses: &#ret_uuid_ident,
+                package: ::gotcha::__package!(),
             }
         }
     };
@@ -6,18 +6,25 @@ use crate::utils::{get_serde_name, parse_serde_rename, RenameAll};
 
 /// Handler for adjacently tagged enums: #[serde(tag = "kind", content = "data")]
 /// JSON format: { "kind": "VariantName", "data": { ...variant fields... } }
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn handler(
-    ident: syn::Ident, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>, tag_name: String, content_name: String,
+    name: String, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>, rename_all_fields: Option<RenameAll>, tag_name: String, content_name: String,
+    conversion: &TokenStream2,
 ) -> Result<TokenStream2, (Span, &'static str)> {
-    let ident_string = ident.to_string();
     let tag_name_str = tag_name.as_str();
     let content_name_str = content_name.as_str();
+    let variant_idents: Vec<String> = variants.iter().map(|variant| variant.ident.to_string()).collect();
+    let variant_strings: Vec<String> = variants
+        .iter()
+        .map(|variant| get_serde_name(&variant.ident.to_string(), parse_serde_rename(&variant.attrs).as_deref(), rename_all))
+        .collect();
 
     let variants_codegen: Vec<TokenStream2> = variants
         .into_iter()
         .map(|variant| {
             let variant_ident_str = variant.ident.to_string();
             let variant_rename = parse_serde_rename(&variant.attrs);
+            let fields_rename_all = variant.fields_rename_all(rename_all_fields);
             let variant_string = get_serde_name(&variant_ident_str, variant_rename.as_deref(), rename_all);
 
             let fields = variant.fields;
@@ -26,7 +33,7 @@ pub(crate) fn handler(
             if is_newtype {
                 // Newtype variant: Variant(InnerType)
                 // The content is the inner type's schema
-                let inner_ty = &fields.fields[0].ty;
+                let inner_schema = fields.fields[0].schema_expr();
                 quote! {
                     {
                         // Tag enum schema
@@ -35,7 +42,7 @@ pub(crate) fn handler(
                         tag_enum.insert("enum".to_string(), ::gotcha_core::serde_json::to_value(vec![#variant_string]).unwrap());
 
                         // Content is the inner type's schema
-                        let content_schema = <#inner_ty as ::gotcha_core::Schematic>::generate_schema().schema.to_value();
+                        let content_schema = #inner_schema.schema.to_value();
 
                         // Build variant object
                         let mut properties: ::std::collections::HashMap<String, ::gotcha_core::serde_json::Value> = ::std::collections::HashMap::new();
@@ -80,12 +87,12 @@ pub(crate) fn handler(
                         let field_ident = field.ident.as_ref()?;
                         let field_ident_str = field_ident.to_string();
                         let field_rename = parse_serde_rename(&field.attrs);
-                        let field_name = get_serde_name(&field_ident_str, field_rename.as_deref(), None);
+                        let field_name = get_serde_name(&field_ident_str, field_rename.as_deref(), fields_rename_all);
                         let (field_description, customizations) = field.schema_customizations();
-                        let field_ty = &field.ty;
+                        let schema_expr = field.schema_expr();
                         Some(quote! {
                             {
-                                let mut field_schema = <#field_ty as ::gotcha_core::Schematic>::generate_schema();
+                                let mut field_schema = #schema_expr;
                                 field_schema.schema.description = #field_description;
                                 #( #customizations )*
                                 content_properties.insert(#field_name.to_string(), field_schema.schema.to_value());
@@ -135,7 +142,7 @@ pub(crate) fn handler(
 
     let ret = quote! {
         fn name() -> &'static str {
-            #ident_string
+            #name
         }
 
         fn required() -> bool {
@@ -151,7 +158,8 @@ pub(crate) fn handler(
         }
 
         fn generate_schema() -> ::gotcha_core::EnhancedSchema {
-            ::gotcha_core::registry::schema_or_ref(Self::name(), Self::required(), || {
+            #conversion
+            ::gotcha_core::registry::component::<Self>(&Self::component_name(), Self::required(), || {
                 let mut schema = ::gotcha_core::EnhancedSchema {
                     schema: ::gotcha_core::oas::Schema {
                         _type: None,
@@ -163,14 +171,23 @@ pub(crate) fn handler(
                     required: Self::required(),
                 };
 
-                let branches: Vec<::std::collections::HashMap<String, ::gotcha_core::serde_json::Value>> = vec![
-                    #(
-                        #variants_codegen,
-                    )*
-                ];
+                let mut branches: Vec<::gotcha_core::serde_json::Value> = vec![];
+                let mut mapping = ::gotcha_core::serde_json::Map::new();
+                #(
+                    let branch = ::gotcha_core::registry::variant::<Self>(&format!("{}{}", Self::component_name(), #variant_idents), || {
+                        ::gotcha_core::serde_json::to_value(#variants_codegen).unwrap()
+                    });
+                    if let Some(target) = branch.get("$ref") {
+                        mapping.insert(#variant_strings.to_string(), target.clone());
+                    }
+                    branches.push(branch);
+                )*
 
                 let mut discriminator: ::std::collections::HashMap<String, ::gotcha_core::serde_json::Value> = ::std::collections::HashMap::new();
                 discriminator.insert("propertyName".to_string(), ::gotcha_core::serde_json::to_value(#tag_name_str).unwrap());
+                if !mapping.is_empty() {
+                    discriminator.insert("mapping".to_string(), ::gotcha_core::serde_json::Value::Object(mapping));
+                }
 
                 schema.schema.extras.insert("oneOf".to_string(), ::gotcha_core::serde_json::to_value(branches).unwrap());
                 schema.schema.extras.insert("discriminator".to_string(), ::gotcha_core::serde_json::to_value(discriminator).unwrap());
@@ -5,15 +5,14 @@ use crate::schematic::ParameterEnumVariantOpt;
 use crate::utils::{get_serde_name, parse_serde_rename, RenameAll};
 
 pub(crate) fn handler(
-    ident: syn::Ident, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>,
+    name: String, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>, rename_all_fields: Option<RenameAll>, conversion: &TokenStream2,
 ) -> Result<TokenStream2, (Span, &'static str)> {
-    let ident_string = ident.to_string();
-
     let variants_codegen: Vec<TokenStream2> = variants
         .into_iter()
         .map(|variant| {
             let variant_ident_str = variant.ident.to_string();
             let variant_rename = parse_serde_rename(&variant.attrs);
+            let fields_rename_all = variant.fields_rename_all(rename_all_fields);
             let varient_string = get_serde_name(&variant_ident_str, variant_rename.as_deref(), rename_all);
 
             // A newtype variant `V(T)` serializes as `{"V": <T>}`, so its content is exactly
@@ -22,9 +21,9 @@ pub(crate) fn handler(
             let is_newtype = matches!(variant.fields.style, darling::ast::Style::Tuple) && variant.fields.fields.len() == 1;
 
             let content_expr: TokenStream2 = if is_newtype {
-                let inner_ty = variant.fields.fields[0].ty.clone();
+                let inner_schema = variant.fields.fields[0].schema_expr();
                 quote! {
-                    <#inner_ty as ::gotcha_core::Schematic>::generate_schema().schema.to_value()
+                    #inner_schema.schema.to_value()
                 }
             } else {
                 let fields_stream: Vec<TokenStream2> = variant
@@ -33,13 +32,14 @@ pub(crate) fn handler(
                     .into_iter()
                     .map(|field| {
                         let (field_description, customizations) = field.schema_customizations();
+                        let schema_expr = field.schema_expr();
                         let field_ty = field.ty.clone();
                         if let Some(ident) = field.ident.as_ref() {
                             let field_ident_str = ident.to_string();
                             let field_rename = parse_serde_rename(&field.attrs);
-                            let field_name = get_serde_name(&field_ident_str, field_rename.as_deref(), None);
+                            let field_name = get_serde_name(&field_ident_str, field_rename.as_deref(), fields_rename_all);
                             quote! {
-                                let mut field_schema = <#field_ty as ::gotcha_core::Schematic>::generate_schema();
+                                let mut field_schema = #schema_expr;
                                 field_schema.schema.description = #field_description;
                                 #( #customizations )*
                                 properties.insert(#field_name.to_string(), field_schema.schema.to_value());
@@ -93,7 +93,7 @@ pub(crate) fn handler(
 
     let ret = quote! {
         fn name() -> &'static str {
-            #ident_string
+            #name
         }
 
         fn required() -> bool {
@@ -107,7 +107,8 @@ pub(crate) fn handler(
             #doc
         }
         fn generate_schema() -> ::gotcha_core::EnhancedSchema {
-            ::gotcha_core::registry::schema_or_ref(Self::name(), Self::required(), || {
+            #conversion
+            ::gotcha_core::registry::component::<Self>(&Self::component_name(), Self::required(), || {
                 let mut schema = ::gotcha_core::EnhancedSchema {
                     schema: ::gotcha_core::oas::Schema {
                         _type: None,
@@ -12,7 +12,7 @@ pub mod simple_enum;
 pub mod tagged_enum;
 pub mod untagged_enum;
 
-use crate::utils::{parse_serde_rename_all, AttributesExt, RenameAll};
+use crate::utils::{has_serde_flag, has_serde_skip, parse_serde_rename_all, serde_value, AttrArg, AttrValue, AttributesExt, RenameAll};
 
 #[derive(Debug, PartialEq, Eq)]
 enum SerdeTagKind {
@@ -28,6 +28,16 @@ enum SerdeTagKind {
 struct ParameterExtraField {
     tag_kind: Option<SerdeTagKind>,
     rename_all: Option<RenameAll>,
+    /// `#[serde(rename_all_fields = "...")]`: the case convention for every struct variant's fields.
+    rename_all_fields: Option<RenameAll>,
+    /// `#[serde(deny_unknown_fields)]`: documented as `additionalProperties: false`.
+    deny_unknown_fields: bool,
+    /// `#[serde(transparent)]`: serialized exactly like the single non-skipped field.
+    transparent: bool,
+    /// `#[serde(from = "..")]` / `#[serde(try_from = "..")]`: the type a request is deserialized through.
+    from: Option<syn::Type>,
+    /// `#[serde(into = "..")]`: the type a response is serialized through.
+    into: Option<syn::Type>,
 }
 
 impl ParameterExtraField {
@@ -36,6 +46,7 @@ impl ParameterExtraField {
         let mut content_name: Option<String> = None;
         let mut is_untagged = false;
         let rename_all = parse_serde_rename_all(attrs);
+        let rename_all_fields = serde_value(attrs, "rename_all_fields").and_then(|value| RenameAll::from_str(&value));
 
         for attr in attrs {
             if attr.path.is_ident("serde") {
@@ -75,18 +86,30 @@ impl ParameterExtraField {
             }
         };
 
-        ParameterExtraField { tag_kind, rename_all }
+        // An unparsable type is left for serde's own derive to report.
+        let conversion = |key| serde_value(attrs, key).and_then(|ty| syn::parse_str::<syn::Type>(&ty).ok());
+        ParameterExtraField {
+            tag_kind,
+            rename_all,
+            rename_all_fields,
+            deny_unknown_fields: has_serde_flag(attrs, "deny_unknown_fields"),
+            transparent: has_serde_flag(attrs, "transparent"),
+            from: conversion("from").or_else(|| conversion("try_from")),
+            into: conversion("into"),
+        }
     }
 }
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(attributes(parameter), forward_attrs(allow, doc, cfg, serde))]
+#[darling(attributes(schematic), forward_attrs(allow, doc, cfg, serde))]
 pub(crate) struct ParameterOpts {
     ident: syn::Ident,
     generics: syn::Generics,
-    where_clause: Option<syn::WhereClause>,
     data: Data<ParameterEnumVariantOpt, ParameterStructFieldOpt>,
     attrs: Vec<syn::Attribute>,
+
+    /// `#[schematic(rename = "...")]`: the component name to use instead of the type's ident.
+    rename: Option<String>,
 }
 
 /// A literal JSON value (`= 42`, `= "x"`, `= true`) whose JSON type is preserved. Used for
@@ -129,30 +152,87 @@ pub(crate) struct ParameterStructFieldOpt {
     // `#[schematic(...)]` field customizations — pure schema *documentation* only. darling
     // treats `Option<_>` fields as optional automatically, so an absent attribute maps to `None`.
     //
-    // Validation constraints (min/max, length, pattern, multiple_of, items, …) are deliberately
-    // NOT here: those belong to `#[validation(...)]` (issue #9) as the single source of truth for
-    // both runtime request validation and the schema, so a constraint is never written twice.
+    // Validation constraints validator can check (min/max, length, pattern, …) are deliberately
+    // NOT here: `#[validate(...)]` is the single source of truth for both runtime request
+    // validation and the schema, so a constraint is never written twice. Only the ones validator
+    // has no rule for (`unique_items`, `multiple_of`, `enum_values`) are documented here.
     title: Option<String>,
     description: Option<String>,
     example: Option<SchemaValue>,
     default: Option<SchemaValue>,
     format: Option<String>,
+    /// Set by the server (`id`, `created_at`): documented on responses only.
+    read_only: darling::util::Flag,
+    /// Only ever sent (`password`): documented on requests only.
+    write_only: darling::util::Flag,
+    /// `uniqueItems: true` on a collection.
+    unique_items: darling::util::Flag,
+    multiple_of: Option<SchemaValue>,
+    /// `enum_values = [..]`: the values the field may take, each serialized through serde.
+    enum_values: Option<EnumValues>,
+    /// `with = "path::to::fn"`: a `fn() -> EnhancedSchema` documenting the field instead of its
+    /// type, which then need not be `Schematic` (a type from another crate, say).
+    with: Option<syn::Path>,
+}
+
+/// The items of `#[schematic(enum_values = [..])]`. syn 1 cannot hand darling an array, so
+/// [`encode_enum_values`] turns it into a string literal first and this parses it back.
+#[derive(Debug)]
+struct EnumValues(Vec<syn::Expr>);
+
+impl darling::FromMeta for EnumValues {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        let array = syn::parse_str::<syn::ExprArray>(value).map_err(|_| darling::Error::custom("expected `enum_values = [value, ...]`"))?;
+        Ok(EnumValues(array.elems.into_iter().collect()))
+    }
+}
+
+/// Rewrites every field's `#[schematic(enum_values = [..])]` into `enum_values = "[..]"` for
+/// [`EnumValues`].
+fn encode_enum_values(data: &mut syn::Data) {
+    let fields: Vec<&mut syn::Field> = match data {
+        syn::Data::Struct(data) => data.fields.iter_mut().collect(),
+        syn::Data::Enum(data) => data.variants.iter_mut().flat_map(|variant| variant.fields.iter_mut()).collect(),
+        syn::Data::Union(_) => return,
+    };
+    for attr in fields.into_iter().flat_map(|field| field.attrs.iter_mut()) {
+        if !attr.path.is_ident("schematic") {
+            continue;
+        }
+        // Anything unparsable is left for darling to report.
+        let Ok(mut args) = attr.parse_args_with(syn::punctuated::Punctuated::<AttrArg, syn::Token![,]>::parse_terminated) else {
+            continue;
+        };
+        for arg in args.iter_mut().filter(|arg| arg.name == "enum_values") {
+            if let AttrValue::Expr(expr) = &arg.value {
+                let encoded = syn::LitStr::new(&quote! { #expr }.to_string(), Span::call_site());
+                arg.value = AttrValue::Expr(Box::new(syn::parse_quote! { #encoded }));
+            }
+        }
+        attr.tokens = quote! { (#args) };
+    }
 }
 
 /// The subset of `#[validate(...)]` (validator crate) rules that map cleanly to JSON-Schema
-/// keywords. Unmodelled validators (`regex`, `custom`, `must_match`, `contains`, …) are ignored
-/// so they stay runtime-only without breaking the derive.
+/// keywords. Bounds and regexes may be any expression (`range(max = MAX_AGE)`,
+/// `regex(path = *SLUG)`); they are evaluated when the schema is built. Unmodelled validators
+/// (`custom`, `must_match`, `nested`, …) are ignored so they stay runtime-only without breaking
+/// the derive — a `nested` type documents its own constraints in its own schema.
 #[derive(Default)]
 struct ValidateConstraints {
-    minimum: Option<f64>,
-    maximum: Option<f64>,
+    minimum: Option<TokenStream2>,
+    maximum: Option<TokenStream2>,
     /// Whether the corresponding bound came from `exclusive_min` / `exclusive_max`.
     exclusive_minimum: bool,
     exclusive_maximum: bool,
     /// `length(min/max/equal)` — becomes minLength/maxLength for strings, minItems/maxItems for collections.
-    min_length: Option<u64>,
-    max_length: Option<u64>,
+    min_length: Option<TokenStream2>,
+    max_length: Option<TokenStream2>,
     format: Option<&'static str>,
+    /// `regex(path = ..)` and the escaped substring of `contains(pattern = "..")`, each as a `&str`.
+    patterns: Vec<TokenStream2>,
+    /// The escaped substring of `does_not_contain(pattern = "..")`.
+    excluded_pattern: Option<String>,
 }
 
 impl ValidateConstraints {
@@ -162,51 +242,73 @@ impl ValidateConstraints {
             if !attr.path.is_ident("validate") {
                 continue;
             }
-            let items = match attr
-                .parse_args_with(|input: syn::parse::ParseStream| syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated(input))
-            {
+            let items = match attr.parse_args_with(syn::punctuated::Punctuated::<AttrArg, syn::Token![,]>::parse_terminated) {
                 Ok(items) => items,
                 Err(_) => continue,
             };
             for item in items {
-                match item {
-                    syn::Meta::List(list) if list.path.is_ident("range") => {
-                        for (name, lit) in name_values(&list) {
+                let name = item.name.to_string();
+                match (name.as_str(), &item.value) {
+                    ("range", AttrValue::List(list)) => {
+                        for (name, value) in exprs(list) {
+                            let value = quote! { (#value) as f64 };
                             match name.as_str() {
-                                "min" => c.minimum = lit_to_f64(lit),
-                                "max" => c.maximum = lit_to_f64(lit),
+                                "min" => c.minimum = Some(value),
+                                "max" => c.maximum = Some(value),
                                 // OpenAPI 3.0 models exclusivity as a `minimum` + `exclusiveMinimum: true` pair.
                                 "exclusive_min" => {
-                                    c.minimum = lit_to_f64(lit);
+                                    c.minimum = Some(value);
                                     c.exclusive_minimum = true;
                                 }
                                 "exclusive_max" => {
-                                    c.maximum = lit_to_f64(lit);
+                                    c.maximum = Some(value);
                                     c.exclusive_maximum = true;
                                 }
                                 _ => {}
                             }
                         }
                     }
-                    syn::Meta::List(list) if list.path.is_ident("length") => {
-                        for (name, lit) in name_values(&list) {
+                    ("length", AttrValue::List(list)) => {
+                        for (name, value) in exprs(list) {
+                            let value = quote! { (#value) as u64 };
                             match name.as_str() {
-                                "min" => c.min_length = lit_to_u64(lit),
-                                "max" => c.max_length = lit_to_u64(lit),
+                                "min" => c.min_length = Some(value),
+                                "max" => c.max_length = Some(value),
                                 // `equal` fixes both bounds.
                                 "equal" => {
-                                    c.min_length = lit_to_u64(lit);
-                                    c.max_length = lit_to_u64(lit);
+                                    c.min_length = Some(value.clone());
+                                    c.max_length = Some(value);
                                 }
                                 _ => {}
                             }
                         }
                     }
                     // `email` / `url` accept both the bare word and the `email(message = ...)` form.
-                    syn::Meta::Path(p) if p.is_ident("email") => c.format = Some("email"),
-                    syn::Meta::List(list) if list.path.is_ident("email") => c.format = Some("email"),
-                    syn::Meta::Path(p) if p.is_ident("url") => c.format = Some("uri"),
-                    syn::Meta::List(list) if list.path.is_ident("url") => c.format = Some("uri"),
+                    ("email", AttrValue::Flag | AttrValue::List(_)) => c.format = Some("email"),
+                    ("url", AttrValue::Flag | AttrValue::List(_)) => c.format = Some("uri"),
+                    // `regex(path = *RE)`, or the older `regex = "RE"` / `regex(path = "RE")` naming
+                    // the static. The regex is only resolved when the schema is built.
+                    ("regex", value) => {
+                        let path = match value {
+                            AttrValue::Expr(expr) => Some(&**expr),
+                            AttrValue::List(list) => exprs(list).into_iter().find(|(name, _)| name == "path").map(|(_, expr)| expr),
+                            AttrValue::Flag => None,
+                        };
+                        let path = match path {
+                            Some(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(path), .. })) => path.parse::<syn::Expr>().ok(),
+                            path => path.cloned(),
+                        };
+                        if let Some(path) = path {
+                            c.patterns.push(quote! { (#path).as_str() });
+                        }
+                    }
+                    ("contains", value) => {
+                        if let Some(needle) = substring(value) {
+                            let pattern = escape_regex(&needle);
+                            c.patterns.push(quote! { #pattern });
+                        }
+                    }
+                    ("does_not_contain", value) => c.excluded_pattern = substring(value).map(|needle| escape_regex(&needle)),
                     _ => {}
                 }
             }
@@ -215,30 +317,42 @@ impl ValidateConstraints {
     }
 }
 
-/// The `name = <lit>` pairs inside a `range(..)` / `length(..)` list.
-fn name_values(list: &syn::MetaList) -> Vec<(String, &syn::Lit)> {
-    list.nested
-        .iter()
-        .filter_map(|n| match n {
-            syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) => Some((nv.path.get_ident()?.to_string(), &nv.lit)),
+/// The `name = <expr>` pairs inside a `range(..)` / `length(..)` / `regex(..)` list.
+fn exprs(list: &syn::punctuated::Punctuated<AttrArg, syn::Token![,]>) -> Vec<(String, &syn::Expr)> {
+    list.iter()
+        .filter_map(|arg| match &arg.value {
+            AttrValue::Expr(expr) => Some((arg.name.to_string(), &**expr)),
             _ => None,
         })
         .collect()
 }
 
-fn lit_to_f64(lit: &syn::Lit) -> Option<f64> {
-    match lit {
-        syn::Lit::Int(i) => i.base10_parse().ok(),
-        syn::Lit::Float(f) => f.base10_parse().ok(),
+/// The literal substring of `contains(pattern = "..")` or the older `contains = ".."`.
+fn substring(value: &AttrValue) -> Option<String> {
+    let expr = match value {
+        AttrValue::Expr(expr) => &**expr,
+        AttrValue::List(list) => exprs(list).into_iter().find(|(name, _)| name == "pattern")?.1,
+        AttrValue::Flag => return None,
+    };
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(needle), ..
+        }) => Some(needle.value()),
         _ => None,
     }
 }
 
-fn lit_to_u64(lit: &syn::Lit) -> Option<u64> {
-    match lit {
-        syn::Lit::Int(i) => i.base10_parse().ok(),
-        _ => None,
+/// `needle` as a regex matching it literally. JSON-Schema patterns are unanchored, so this matches
+/// any string containing it.
+fn escape_regex(needle: &str) -> String {
+    let mut escaped = String::with_capacity(needle.len());
+    for c in needle.chars() {
+        if "\\^$.*+?()[]{}|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
     }
+    escaped
 }
 
 /// Whether `ty` is a collection (`Vec<_>`), peeling one layer of `Option<_>`. Used to decide
@@ -267,6 +381,24 @@ fn is_collection(ty: &syn::Type) -> bool {
 }
 
 impl ParameterStructFieldOpt {
+    /// The expression producing this field's `EnhancedSchema`: `#[schematic(with = "..")]` if
+    /// given, else its type's. A field serialized through
+    /// `#[serde(with / serialize_with / deserialize_with = "..")]` has a wire format the derive
+    /// cannot see, so without `#[schematic(with)]` it is documented as any value.
+    pub(crate) fn schema_expr(&self) -> TokenStream2 {
+        let ty = &self.ty;
+        if let Some(with) = &self.with {
+            quote! { #with() }
+        } else if ["with", "serialize_with", "deserialize_with"]
+            .iter()
+            .any(|key| serde_value(&self.attrs, key).is_some())
+        {
+            quote! { ::gotcha_core::SchemaBuilder::any().build() }
+        } else {
+            quote! { <#ty as ::gotcha_core::Schematic>::generate_schema() }
+        }
+    }
+
     /// Builds the `description` token and the list of `#[schematic(...)]` customization
     /// statements for this field. Each statement mutates a local `field_schema` binding
     /// (its `.schema.format` / `.schema.extras`). An explicit `#[schematic(description = "...")]`
@@ -283,6 +415,9 @@ impl ParameterStructFieldOpt {
             quote! { Some(#desc.to_string()) }
         } else if let Some(doc) = self.attrs.get_doc() {
             quote! { Some(#doc.to_string()) }
+        } else if self.with.is_some() {
+            // Whatever the `#[schematic(with)]` function described it as.
+            quote! { field_schema.schema.description.clone() }
         } else {
             quote! { None }
         };
@@ -319,6 +454,18 @@ impl ParameterStructFieldOpt {
         if let Some(format) = validated.format {
             customizations.push(quote! { field_schema.schema.format = Some(#format.to_string()); });
         }
+        // A schema has a single `pattern`, so several must each hold through `allOf`.
+        match validated.patterns.as_slice() {
+            [] => {}
+            [pattern] => customizations.push(extra("pattern", pattern.clone())),
+            patterns => customizations.push(extra(
+                "allOf",
+                quote! { [#(::gotcha_core::serde_json::json!({ "pattern": #patterns })),*] },
+            )),
+        }
+        if let Some(pattern) = validated.excluded_pattern {
+            customizations.push(extra("not", quote! { ::gotcha_core::serde_json::json!({ "pattern": #pattern }) }));
+        }
 
         if let Some(format) = &self.format {
             customizations.push(quote! { field_schema.schema.format = Some(#format.to_string()); });
@@ -335,6 +482,25 @@ impl ParameterStructFieldOpt {
             let value = &v.0;
             customizations.push(quote! { field_schema.schema.extras.insert("default".to_string(), #value); });
         }
+        if self.read_only.is_present() {
+            customizations.push(extra("readOnly", quote! { true }));
+        }
+        if self.write_only.is_present() {
+            customizations.push(extra("writeOnly", quote! { true }));
+        }
+        if self.unique_items.is_present() {
+            customizations.push(extra("uniqueItems", quote! { true }));
+        }
+        if let Some(v) = &self.multiple_of {
+            let value = &v.0;
+            customizations.push(quote! { field_schema.schema.extras.insert("multipleOf".to_string(), #value); });
+        }
+        if let Some(EnumValues(values)) = &self.enum_values {
+            customizations.push(extra(
+                "enum",
+                quote! { vec![#(::gotcha_core::serde_json::to_value(&(#values)).expect("#[schematic(enum_values)] must serialize")),*] },
+            ));
+        }
 
         (description, customizations)
     }
@@ -349,11 +515,59 @@ pub(crate) struct ParameterEnumVariantOpt {
     fields: darling::ast::Fields<ParameterStructFieldOpt>,
 }
 
-pub(crate) fn handler(input: TokenStream2) -> Result<TokenStream2, (Span, &'static str)> {
-    let x1 = parse2::<DeriveInput>(input).unwrap();
-    let param_opts: ParameterOpts = ParameterOpts::from_derive_input(&x1).unwrap();
+impl ParameterEnumVariantOpt {
+    /// The case convention for this variant's fields: its own `rename_all`, else the
+    /// container's `rename_all_fields`.
+    fn fields_rename_all(&self, rename_all_fields: Option<RenameAll>) -> Option<RenameAll> {
+        parse_serde_rename_all(&self.attrs).or(rename_all_fields)
+    }
+}
+
+/// Takes the type-level `#[schematic(example = <expr>)]` out of `attrs`, leaving the other options
+/// for darling; see [`AttrArg`] for why an expression cannot go through it.
+fn take_type_example(attrs: &mut [syn::Attribute]) -> Result<Option<syn::Expr>, (Span, &'static str)> {
+    let mut example = None;
+    for attr in attrs.iter_mut().filter(|attr| attr.path.is_ident("schematic")) {
+        let args = attr
+            .parse_args_with(syn::punctuated::Punctuated::<AttrArg, syn::Token![,]>::parse_terminated)
+            .map_err(|error| (error.span(), "expected `#[schematic(key = value, ...)]`"))?;
+        let mut rest = vec![];
+        for arg in args {
+            if arg.name != "example" {
+                rest.push(arg);
+                continue;
+            }
+            match arg.value {
+                AttrValue::Expr(expr) => example = Some(*expr),
+                _ => return Err((arg.name.span(), "expected `example = <value>`")),
+            }
+        }
+        attr.tokens = quote! { (#(#rest),*) };
+    }
+    Ok(example)
+}
+
+pub(crate) fn handler(input: TokenStream2) -> TokenStream2 {
+    let mut x1 = match parse2::<DeriveInput>(input) {
+        Ok(input) => input,
+        Err(e) => return e.to_compile_error(),
+    };
+    let example = match take_type_example(&mut x1.attrs) {
+        Ok(example) => example,
+        Err((span, msg)) => return syn::Error::new(span, msg).to_compile_error(),
+    };
+    encode_enum_values(&mut x1.data);
+    let param_opts = match ParameterOpts::from_derive_input(&x1) {
+        Ok(opts) => opts,
+        Err(e) => return e.write_errors(),
+    };
+    expand(param_opts, example).unwrap_or_else(|(span, msg)| syn::Error::new(span, msg).to_compile_error())
+}
+
+fn expand(param_opts: ParameterOpts, example: Option<syn::Expr>) -> Result<TokenStream2, (Span, &'static str)> {
     let extra_field = ParameterExtraField::from_attr(&param_opts.attrs);
     let ident = param_opts.ident.clone();
+    let name = param_opts.rename.clone().unwrap_or_else(|| ident.to_string());
     let doc = match param_opts.attrs.get_doc() {
         None => {
             quote! { None }
@@ -387,47 +601,112 @@ pub(crate) fn handler(input: TokenStream2) -> Result<TokenStream2, (Span, &'stat
     } else {
         quote! {<#(#generics_single),*> }
     };
-    let where_clause = if let Some(where_clause) = param_opts.where_clause {
-        quote! { where #where_clause }
-    } else {
+    // Every type parameter must itself be `Schematic`: its schema is part of ours, and its
+    // component name is spelled into ours (`Page<User>` registers as `Page_User`).
+    let type_params = param_opts.generics.type_params().map(|ty| ty.ident.clone()).collect::<Vec<_>>();
+    let mut where_predicates = param_opts
+        .generics
+        .where_clause
+        .as_ref()
+        .map(|where_clause| where_clause.predicates.iter().map(|p| quote! { #p }).collect::<Vec<_>>())
+        .unwrap_or_default();
+    where_predicates.extend(type_params.iter().map(|ty| quote! { #ty: ::gotcha_core::Schematic }));
+    let where_clause = if where_predicates.is_empty() {
         quote! {}
+    } else {
+        quote! { where #(#where_predicates),* }
+    };
+    let mut component_name = quote! {
+        fn component_name() -> String {
+            let mut name = String::from(#name);
+            #(
+                name.push('_');
+                name.push_str(&<#type_params as ::gotcha_core::Schematic>::component_name());
+            )*
+            name
+        }
+    };
+
+    // Serialized through serde, so the documented example is exactly what goes over the wire.
+    let mut example = example.map(|example| {
+        quote! {
+            fn example() -> Option<::gotcha_core::serde_json::Value> {
+                Some(::gotcha_core::serde_json::to_value(&(#example)).expect("#[schematic(example)] must serialize"))
+            }
+        }
+    });
+
+    // A conversion on only one side leaves the type's own shape on the other: `from` is what a
+    // request is read through, `into` what a response is written through. A component shared by
+    // both sides (anywhere but under `try_collect_by_direction`) keeps the type's own shape.
+    let conversion = match (&extra_field.from, &extra_field.into) {
+        (Some(from), None) => quote! {
+            if ::gotcha_core::registry::component_direction() == Some(::gotcha_core::registry::Direction::Request) {
+                return <#from as ::gotcha_core::Schematic>::generate_schema();
+            }
+        },
+        (None, Some(into)) => quote! {
+            if ::gotcha_core::registry::component_direction() == Some(::gotcha_core::registry::Direction::Response) {
+                return <#into as ::gotcha_core::Schematic>::generate_schema();
+            }
+        },
+        _ => quote! {},
     };
 
     let impl_stream = match param_opts.data {
+        // Converted both ways, the type's own shape never reaches the wire.
+        _ if extra_field.from.is_some() && extra_field.into.is_some() => {
+            component_name = quote! {};
+            let request_ty = extra_field.from.as_ref().filter(|_| extra_field.from != extra_field.into);
+            newtype_struct::handler(extra_field.into.as_ref().unwrap(), request_ty, example.take())
+        }
         Data::Enum(enum_variants) => {
             // Check if all enum variants have empty fields
             let is_simple_enum = enum_variants.iter().all(|variant| variant.fields.is_empty());
             if is_simple_enum {
-                simple_enum::handler(ident.clone(), doc, enum_variants, extra_field.rename_all)?
+                simple_enum::handler(name.clone(), doc, enum_variants, extra_field.rename_all, &conversion)?
             } else {
                 match extra_field.tag_kind {
                     None => {
                         // Default: externally tagged
-                        external_tagged_enum::handler(ident.clone(), doc, enum_variants, extra_field.rename_all)?
+                        external_tagged_enum::handler(name.clone(), doc, enum_variants, extra_field.rename_all, extra_field.rename_all_fields, &conversion)?
                     }
                     Some(SerdeTagKind::Internal(ref tag_name)) => {
-                        tagged_enum::handler(ident.clone(), doc, enum_variants, extra_field.rename_all, tag_name.clone())?
-                    }
-                    Some(SerdeTagKind::Adjacent { ref tag, ref content }) => {
-                        adjacent_tagged_enum::handler(ident.clone(), doc, enum_variants, extra_field.rename_all, tag.clone(), content.clone())?
+                        tagged_enum::handler(name.clone(), doc, enum_variants, extra_field.rename_all, extra_field.rename_all_fields, tag_name.clone(), &conversion)?
                     }
-                    Some(SerdeTagKind::Untagged) => untagged_enum::handler(ident.clone(), doc, enum_variants, extra_field.rename_all)?,
+                    Some(SerdeTagKind::Adjacent { ref tag, ref content }) => adjacent_tagged_enum::handler(
+                        name.clone(),
+                        doc,
+                        enum_variants,
+                        extra_field.rename_all,
+                        extra_field.rename_all_fields,
+                        tag.clone(),
+                        content.clone(),
+                        &conversion,
+                    )?,
+                    Some(SerdeTagKind::Untagged) => untagged_enum::handler(name.clone(), doc, enum_variants, extra_field.rename_all, extra_field.rename_all_fields, &conversion)?,
                 }
             }
         }
         Data::Struct(fields) => {
             let is_tuple = matches!(fields.style, darling::ast::Style::Tuple);
             let field_count = fields.fields.len();
-            if is_tuple && field_count == 1 {
-                // Newtype struct (e.g. `struct UserId(Uuid);`) — transparent to the inner type.
-                newtype_struct::handler(fields.fields)
+            if (is_tuple && field_count == 1) || extra_field.transparent {
+                // Newtype struct (e.g. `struct UserId(Uuid);`) or `#[serde(transparent)]` —
+                // transparent to the inner type, including its component name.
+                let mut inner = fields.fields.iter().filter(|field| !has_serde_skip(&field.attrs));
+                let (Some(inner), None) = (inner.next(), inner.next()) else {
+                    return Err((ident.span(), "#[serde(transparent)] needs exactly one field that is not skipped"));
+                };
+                component_name = quote! {};
+                newtype_struct::handler(&inner.ty, None, example.take())
             } else if is_tuple {
                 return Err((
                     ident.span(),
                     "#[derive(Schematic)] does not support multi-field tuple structs; use a named struct",
                 ));
             } else {
-                named_struct::handler(ident.clone(), doc, fields, extra_field.rename_all)?
+                named_struct::handler(name.clone(), doc, fields, extra_field.rename_all, extra_field.deny_unknown_fields, &conversion)?
             }
         }
     };
@@ -435,6 +714,8 @@ pub(crate) fn handler(input: TokenStream2) -> Result<TokenStream2, (Span, &'stat
     let ret = quote! {
         impl #generics Schematic for #ident #generics_single #where_clause {
             #impl_stream
+            #component_name
+            #example
         }
     };
 
@@ -2,13 +2,12 @@ use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
 
 use crate::schematic::ParameterStructFieldOpt;
-use crate::utils::{get_serde_name, has_serde_flatten, has_serde_skip, is_serde_optional, parse_serde_rename, RenameAll};
+use crate::utils::{get_serde_name, has_serde_default, has_serde_flatten, has_serde_skip, parse_serde_rename, serde_value, RenameAll};
 
 pub(crate) fn handler(
-    ident: syn::Ident, doc: TokenStream2, fields: darling::ast::Fields<ParameterStructFieldOpt>, rename_all: Option<RenameAll>,
+    name: String, doc: TokenStream2, fields: darling::ast::Fields<ParameterStructFieldOpt>, rename_all: Option<RenameAll>, deny_unknown_fields: bool,
+    conversion: &TokenStream2,
 ) -> Result<TokenStream2, (Span, &'static str)> {
-    let ident_string = ident.to_string();
-
     let mut normal_fields_stream: Vec<TokenStream2> = Vec::new();
     let mut flatten_fields_stream: Vec<TokenStream2> = Vec::new();
     let mut flatten_schema_stream: Vec<TokenStream2> = Vec::new();
@@ -38,22 +37,31 @@ pub(crate) fn handler(
             let ident_str = field.ident.as_ref().unwrap().to_string();
             let rename = parse_serde_rename(&field.attrs);
             let field_name = get_serde_name(&ident_str, rename.as_deref(), rename_all);
-            // `#[serde(default)]` / `skip_serializing_if` make a field optional in the payload,
-            // so it must not be listed as required even if its type is otherwise required.
-            let optional_override = if is_serde_optional(&field.attrs) {
+            // `#[serde(default)]` makes a field optional in the payload, so it must not be listed as
+            // required even if its type is otherwise required. `skip_serializing_if` only drops it
+            // from what is sent back: when request and response components are documented
+            // separately a request must still carry it, otherwise it is optional everywhere.
+            let optional_override = if has_serde_default(&field.attrs) {
                 quote! { field_schema.required = false; }
+            } else if serde_value(&field.attrs, "skip_serializing_if").is_some() {
+                quote! {
+                    if ::gotcha_core::registry::component_direction() != Some(::gotcha_core::registry::Direction::Request) {
+                        field_schema.required = false;
+                    }
+                }
             } else {
                 quote! {}
             };
             // Description (an explicit `#[schematic(description = ...)]` overrides the doc comment)
             // plus the `#[schematic(...)]` validation/metadata customizations.
             let (field_description, customizations) = field.schema_customizations();
+            let schema_expr = field.schema_expr();
 
             normal_fields_stream.push(quote! {
                 (
                     #field_name,
                     {
-                        let mut field_schema = <#field_ty as ::gotcha_core::Schematic>::generate_schema();
+                        let mut field_schema = #schema_expr;
                         field_schema.schema.description = #field_description;
                         #optional_override
                         #( #customizations )*
@@ -64,6 +72,14 @@ pub(crate) fn handler(
         }
     }
 
+    // `#[serde(deny_unknown_fields)]` rejects anything not listed; serde does not support it
+    // together with `flatten`, so only the plain object schema needs it.
+    let additional_properties = if deny_unknown_fields {
+        quote! { schema.schema.extras.insert("additionalProperties".to_string(), ::gotcha_core::serde_json::Value::Bool(false)); }
+    } else {
+        quote! {}
+    };
+
     let has_flatten = !flatten_schema_stream.is_empty();
 
     let generate_schema_impl = if has_flatten {
@@ -125,6 +141,7 @@ pub(crate) fn handler(
             }
             schema.schema.extras.insert("properties".to_string(), ::gotcha_core::serde_json::to_value(properties).unwrap());
             schema.schema.extras.insert("required".to_string(), ::gotcha_core::serde_json::to_value(required_fields).unwrap());
+            #additional_properties
             schema
         }
     };
@@ -157,7 +174,7 @@ pub(crate) fn handler(
 
     let ret = quote! {
         fn name() -> &'static str {
-            #ident_string
+            #name
         }
 
         fn required() -> bool {
@@ -185,10 +202,11 @@ pub(crate) fn handler(
         #flatten_schema_impl
 
         fn generate_schema() -> ::gotcha_core::EnhancedSchema {
+            #conversion
             // During spec assembly this registers the schema under `name()` and returns a `$ref`
             // to it (which is also what makes recursive types terminate); outside that scope it
             // just builds the schema inline.
-            ::gotcha_core::registry::schema_or_ref(Self::name(), Self::required(), || {
+            ::gotcha_core::registry::component::<Self>(&Self::component_name(), Self::required(), || {
                 let mut schema = ::gotcha_core::EnhancedSchema {
                     schema: ::gotcha_core::oas::Schema {
                         _type: Some(Self::type_().to_string()),
@@ -1,19 +1,40 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 
-use crate::schematic::ParameterStructFieldOpt;
-
-/// Handle a single-field tuple struct (a "newtype", e.g. `struct UserId(Uuid);`)
-/// by delegating every `Schematic` method to the wrapped type. The newtype is
-/// therefore transparent in the generated schema — `UserId` looks exactly like
-/// `Uuid`, matching how serde serializes such wrappers.
-pub(crate) fn handler(fields: Vec<ParameterStructFieldOpt>) -> TokenStream2 {
-    let inner_ty = &fields[0].ty;
+/// Handle a type serde serializes as another one by delegating every `Schematic` method to it:
+/// a single-field tuple struct (a "newtype", e.g. `struct UserId(Uuid);`), a
+/// `#[serde(transparent)]` struct, or one converted `#[serde(from/try_from = "..", into = "..")]`.
+/// The type is therefore invisible in the generated schema — `UserId` looks exactly like `Uuid`.
+/// Only a type-level example, if given, replaces the inner type's.
+///
+/// `request_ty` is the type a request is read through when it differs from `inner_ty`
+/// (`from` alongside `into`); see `gotcha_core::registry::component_direction`. A schema shared by
+/// both sides documents the response's.
+pub(crate) fn handler(inner_ty: &syn::Type, request_ty: Option<&syn::Type>, example: Option<TokenStream2>) -> TokenStream2 {
+    let example = example.unwrap_or_else(|| {
+        quote! {
+            fn example() -> Option<::gotcha_core::serde_json::Value> {
+                <#inner_ty as ::gotcha_core::Schematic>::example()
+            }
+        }
+    });
+    let generate_schema = match request_ty {
+        Some(request_ty) => quote! {
+            match ::gotcha_core::registry::component_direction() {
+                Some(::gotcha_core::registry::Direction::Request) => <#request_ty as ::gotcha_core::Schematic>::generate_schema(),
+                _ => <#inner_ty as ::gotcha_core::Schematic>::generate_schema(),
+            }
+        },
+        None => quote! { <#inner_ty as ::gotcha_core::Schematic>::generate_schema() },
+    };
 
     quote! {
         fn name() -> &'static str {
             <#inner_ty as ::gotcha_core::Schematic>::name()
         }
+        fn component_name() -> String {
+            <#inner_ty as ::gotcha_core::Schematic>::component_name()
+        }
         fn required() -> bool {
             <#inner_ty as ::gotcha_core::Schematic>::required()
         }
@@ -29,11 +50,12 @@ pub(crate) fn handler(fields: Vec<ParameterStructFieldOpt>) -> TokenStream2 {
         fn format() -> Option<String> {
             <#inner_ty as ::gotcha_core::Schematic>::format()
         }
+        #example
         fn fields() -> Vec<(&'static str, ::gotcha_core::EnhancedSchema)> {
             <#inner_ty as ::gotcha_core::Schematic>::fields()
         }
         fn generate_schema() -> ::gotcha_core::EnhancedSchema {
-            <#inner_ty as ::gotcha_core::Schematic>::generate_schema()
+            #generate_schema
         }
         fn flatten_schema() -> Option<::gotcha_core::serde_json::Value> {
             <#inner_ty as ::gotcha_core::Schematic>::flatten_schema()
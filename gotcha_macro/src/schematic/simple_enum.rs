@@ -5,10 +5,8 @@ use crate::schematic::ParameterEnumVariantOpt;
 use crate::utils::{get_serde_name, parse_serde_rename, RenameAll};
 
 pub(crate) fn handler(
-    ident: syn::Ident, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>,
+    name: String, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>, conversion: &TokenStream2,
 ) -> Result<TokenStream2, (Span, &'static str)> {
-    let ident_string = ident.to_string();
-
     let variant_vec: Vec<TokenStream2> = variants
         .into_iter()
         .map(|variant| {
@@ -21,7 +19,7 @@ pub(crate) fn handler(
 
     let ret = quote! {
         fn name() -> &'static str {
-            #ident_string
+            #name
         }
 
         fn required() -> bool {
@@ -35,7 +33,8 @@ pub(crate) fn handler(
             #doc
         }
         fn generate_schema() -> ::gotcha_core::EnhancedSchema {
-            ::gotcha_core::registry::schema_or_ref(Self::name(), Self::required(), || {
+            #conversion
+            ::gotcha_core::registry::component::<Self>(&Self::component_name(), Self::required(), || {
                 let mut schema = ::gotcha_core::EnhancedSchema {
                     schema: ::gotcha_core::oas::Schema {
                         _type: Some(Self::type_().to_string()),
@@ -5,16 +5,21 @@ use crate::schematic::ParameterEnumVariantOpt;
 use crate::utils::{get_serde_name, parse_serde_rename, RenameAll};
 
 pub(crate) fn handler(
-    ident: syn::Ident, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>, tag_name: String,
+    name: String, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>, rename_all_fields: Option<RenameAll>, tag_name: String, conversion: &TokenStream2,
 ) -> Result<TokenStream2, (Span, &'static str)> {
-    let ident_string = ident.to_string();
     let tag_name_str = tag_name.as_str();
+    let variant_idents: Vec<String> = variants.iter().map(|variant| variant.ident.to_string()).collect();
+    let variant_strings: Vec<String> = variants
+        .iter()
+        .map(|variant| get_serde_name(&variant.ident.to_string(), parse_serde_rename(&variant.attrs).as_deref(), rename_all))
+        .collect();
 
     let variants_codegen: Vec<TokenStream2> = variants
         .into_iter()
         .map(|variant| {
             let variant_ident_str = variant.ident.to_string();
             let variant_rename = parse_serde_rename(&variant.attrs);
+            let fields_rename_all = variant.fields_rename_all(rename_all_fields);
             let varient_string = get_serde_name(&variant_ident_str, variant_rename.as_deref(), rename_all);
 
             let fields_stream: Vec<TokenStream2> = variant
@@ -22,15 +27,16 @@ pub(crate) fn handler(
                 .into_iter()
                 .map(|field| {
                     let (field_description, customizations) = field.schema_customizations();
+                    let schema_expr = field.schema_expr();
                     let field_ty = field.ty.clone();
 
                     if let Some(ident) = field.ident.as_ref() {
                         // named variant
                         let field_ident_str = ident.to_string();
                         let field_rename = parse_serde_rename(&field.attrs);
-                        let field_name = get_serde_name(&field_ident_str, field_rename.as_deref(), None);
+                        let field_name = get_serde_name(&field_ident_str, field_rename.as_deref(), fields_rename_all);
                         quote! {
-                            let mut field_schema = <#field_ty as ::gotcha_core::Schematic>::generate_schema();
+                            let mut field_schema = #schema_expr;
                             field_schema.schema.description = #field_description;
                             #( #customizations )*
                             properties.insert(#field_name.to_string(), field_schema.schema.to_value());
@@ -77,7 +83,7 @@ pub(crate) fn handler(
 
     let ret = quote! {
         fn name() -> &'static str {
-            #ident_string
+            #name
         }
 
         fn required() -> bool {
@@ -91,7 +97,8 @@ pub(crate) fn handler(
             #doc
         }
         fn generate_schema() -> ::gotcha_core::EnhancedSchema {
-            ::gotcha_core::registry::schema_or_ref(Self::name(), Self::required(), || {
+            #conversion
+            ::gotcha_core::registry::component::<Self>(&Self::component_name(), Self::required(), || {
                 let mut schema = ::gotcha_core::EnhancedSchema {
                     schema: ::gotcha_core::oas::Schema {
                         _type: None,
@@ -103,13 +110,23 @@ pub(crate) fn handler(
                     required: Self::required(),
                 };
                 let mut branches = vec![];
+                let mut mapping = ::gotcha_core::serde_json::Map::new();
 
                 #(
-                    #variants_codegen
-                    branches.push(variant_object);
+                    let branch = ::gotcha_core::registry::variant::<Self>(&format!("{}{}", Self::component_name(), #variant_idents), || {
+                        #variants_codegen
+                        ::gotcha_core::serde_json::to_value(variant_object).expect("cannot convert variant to value")
+                    });
+                    if let Some(target) = branch.get("$ref") {
+                        mapping.insert(#variant_strings.to_string(), target.clone());
+                    }
+                    branches.push(branch);
                 )*
                 let mut discriminator = ::std::collections::HashMap::new();
                 discriminator.insert("propertyName".to_string(), ::gotcha_core::serde_json::to_value(#tag_name_str).unwrap());
+                if !mapping.is_empty() {
+                    discriminator.insert("mapping".to_string(), ::gotcha_core::serde_json::Value::Object(mapping));
+                }
                 schema.schema.extras.insert("oneOf".to_string(), ::gotcha_core::serde_json::to_value(branches).unwrap());
                 schema.schema.extras.insert("discriminator".to_string(), ::gotcha_core::serde_json::to_value(discriminator).unwrap());
                 schema
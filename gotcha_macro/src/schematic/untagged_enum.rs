@@ -5,15 +5,16 @@ use crate::schematic::ParameterEnumVariantOpt;
 use crate::utils::{get_serde_name, parse_serde_rename, RenameAll};
 
 pub(crate) fn handler(
-    ident: syn::Ident, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>,
+    name: String, doc: TokenStream2, variants: Vec<ParameterEnumVariantOpt>, rename_all: Option<RenameAll>, rename_all_fields: Option<RenameAll>, conversion: &TokenStream2,
 ) -> Result<TokenStream2, (Span, &'static str)> {
-    let ident_string = ident.to_string();
-
     let variants_codegen: Vec<TokenStream2> = variants
         .into_iter()
         .map(|variant| {
             let variant_ident_str = variant.ident.to_string();
             let variant_rename = parse_serde_rename(&variant.attrs);
+            // Untagged variants never show their names, so a container `rename_all` falls through
+            // to their fields.
+            let fields_rename_all = variant.fields_rename_all(rename_all_fields).or(rename_all);
             let variant_string = get_serde_name(&variant_ident_str, variant_rename.as_deref(), rename_all);
 
             let is_tuple = matches!(variant.fields.style, darling::ast::Style::Tuple);
@@ -21,9 +22,9 @@ pub(crate) fn handler(
 
             if is_tuple && field_count == 1 {
                 // Single unnamed field (newtype): the inner type's schema directly.
-                let inner_ty = variant.fields.fields[0].ty.clone();
+                let inner_schema = variant.fields.fields[0].schema_expr();
                 quote! {
-                    <#inner_ty as ::gotcha_core::Schematic>::generate_schema().schema.to_value()
+                    #inner_schema.schema.to_value()
                 }
             } else if is_tuple {
                 // Multi-field tuple variant `V(A, B)` serializes as `[a, b]`. Emit an array that
@@ -33,8 +34,8 @@ pub(crate) fn handler(
                     .fields
                     .iter()
                     .map(|field| {
-                        let field_ty = field.ty.clone();
-                        quote! { <#field_ty as ::gotcha_core::Schematic>::generate_schema().schema.to_value() }
+                        let field_schema = field.schema_expr();
+                        quote! { #field_schema.schema.to_value() }
                     })
                     .collect();
                 quote! {
@@ -57,12 +58,12 @@ pub(crate) fn handler(
                     .into_iter()
                     .map(|field| {
                         let (field_description, customizations) = field.schema_customizations();
-                        let field_ty = field.ty.clone();
+                        let schema_expr = field.schema_expr();
                         let field_ident_str = field.ident.as_ref().map(|i| i.to_string()).unwrap_or_default();
                         let field_rename = parse_serde_rename(&field.attrs);
-                        let field_name = get_serde_name(&field_ident_str, field_rename.as_deref(), rename_all);
+                        let field_name = get_serde_name(&field_ident_str, field_rename.as_deref(), fields_rename_all);
                         quote! {
-                            let mut field_schema = <#field_ty as ::gotcha_core::Schematic>::generate_schema();
+                            let mut field_schema = #schema_expr;
                             field_schema.schema.description = #field_description;
                             #( #customizations )*
                             properties.insert(#field_name.to_string(), field_schema.schema.to_value());
@@ -94,7 +95,7 @@ pub(crate) fn handler(
 
     let ret = quote! {
         fn name() -> &'static str {
-            #ident_string
+            #name
         }
 
         fn required() -> bool {
@@ -110,7 +111,8 @@ pub(crate) fn handler(
         }
 
         fn generate_schema() -> ::gotcha_core::EnhancedSchema {
-            ::gotcha_core::registry::schema_or_ref(Self::name(), Self::required(), || {
+            #conversion
+            ::gotcha_core::registry::component::<Self>(&Self::component_name(), Self::required(), || {
                 let mut schema = ::gotcha_core::EnhancedSchema {
                     schema: ::gotcha_core::oas::Schema {
                         _type: None,
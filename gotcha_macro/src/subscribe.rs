@@ -0,0 +1,86 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, AttributeArgs, FnArg, ItemFn};
+use uuid::Uuid;
+
+use crate::FromMeta;
+
+#[derive(Debug, FromMeta)]
+struct SubscribeMeta {
+    /// The name the subscriber is logged under; the function's name by default.
+    name: Option<String>,
+}
+
+pub(crate) fn handler(args: TokenStream, input_stream: TokenStream) -> TokenStream {
+    let attr_args = parse_macro_input!(args as AttributeArgs);
+    let meta = match SubscribeMeta::from_list(&attr_args) {
+        Ok(meta) => meta,
+        Err(e) => return TokenStream::from(e.write_errors()),
+    };
+    let input = parse_macro_input!(input_stream as ItemFn);
+
+    let mut types = Vec::new();
+    let mut signature_error = if input.sig.asyncness.is_none() {
+        Some(syn::Error::new_spanned(input.sig.fn_token, "#[subscribe] functions must be async"))
+    } else if !input.sig.generics.params.is_empty() {
+        Some(syn::Error::new_spanned(&input.sig.generics, "#[subscribe] functions cannot be generic"))
+    } else {
+        None
+    };
+    for param in &input.sig.inputs {
+        match param {
+            FnArg::Receiver(receiver) => {
+                signature_error = signature_error.or_else(|| Some(syn::Error::new_spanned(receiver, "#[subscribe] functions cannot take `self`")))
+            }
+            FnArg::Typed(typed) => types.push(typed.ty.clone()),
+        }
+    }
+    if signature_error.is_none() && !(1..=2).contains(&types.len()) {
+        signature_error = Some(syn::Error::new_spanned(
+            &input.sig.inputs,
+            "#[subscribe] functions take the event, then optionally a `GotchaContext<S, C>` or `Messager<S, C>`",
+        ));
+    }
+    if let Some(error) = signature_error {
+        return TokenStream::from(error.to_compile_error());
+    }
+
+    let fn_ident = input.sig.ident.clone();
+    let name = meta.name.unwrap_or_else(|| fn_ident.to_string());
+    let event = &types[0];
+    let (context, call) = match types.get(1) {
+        Some(context) => (
+            quote! {
+                let context = <#context as ::gotcha::event::FromEventContext>::from_event_context(context)?;
+            },
+            quote! { #fn_ident(event, context) },
+        ),
+        None => (quote! { let _ = context; }, quote! { #fn_ident(event) }),
+    };
+
+    let random_uuid = Uuid::new_v4().simple().to_string();
+    let deliver_ident = format_ident!("__gotcha_subscriber_deliver_{}", random_uuid);
+
+    let ret = quote! {
+
+        #input
+
+        #[doc(hidden)]
+        fn #deliver_ident(event: &dyn ::core::any::Any, context: &dyn ::core::any::Any) -> ::core::option::Option<::gotcha::event::EventFuture> {
+            let event = <#event as ::core::clone::Clone>::clone(event.downcast_ref::<#event>()?);
+            #context
+            ::core::option::Option::Some(::std::boxed::Box::pin(async move {
+                ::gotcha::outcome::Outcome::into_result(#call.await)
+            }))
+        }
+
+        ::gotcha::inventory::submit! {
+            ::gotcha::event::SubscriberDeclaration {
+                name: #name,
+                event: ::core::any::TypeId::of::<#event>,
+                deliver: #deliver_ident,
+            }
+        }
+    };
+    TokenStream::from(ret)
+}
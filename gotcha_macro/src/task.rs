@@ -0,0 +1,131 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{parse_macro_input, AttributeArgs, FnArg, ItemFn};
+use uuid::Uuid;
+
+use crate::FromMeta;
+
+#[derive(Debug, FromMeta)]
+struct TaskMeta {
+    /// A cron expression, with a seconds field.
+    cron: Option<String>,
+    /// A fixed interval, such as `"30s"`, `"5m"` or `"1h30m"`. Units are `ms`, `s`, `m`, `h` and
+    /// `d`, as in the `[tasks]` configuration, whose parser checks it while the crate compiles.
+    interval: Option<syn::LitStr>,
+    /// The name the task registers under; the function's name by default.
+    name: Option<String>,
+    /// The time zone a cron expression is evaluated in.
+    timezone: Option<String>,
+}
+
+pub(crate) fn handler(args: TokenStream, input_stream: TokenStream) -> TokenStream {
+    let attr_args = parse_macro_input!(args as AttributeArgs);
+    let meta = match TaskMeta::from_list(&attr_args) {
+        Ok(meta) => meta,
+        Err(e) => return TokenStream::from(e.write_errors()),
+    };
+    let input = parse_macro_input!(input_stream as ItemFn);
+
+    let schedule_error = match (&meta.cron, &meta.interval) {
+        (Some(_), Some(_)) => Some("a task takes either `cron` or `interval`, not both"),
+        (None, None) => Some("a task needs a schedule: `#[task(cron = \"..\")]` or `#[task(interval = \"..\")]`"),
+        _ => None,
+    };
+    let signature_error = if input.sig.asyncness.is_none() {
+        Some(syn::Error::new_spanned(input.sig.fn_token, "#[task] functions must be async"))
+    } else if !input.sig.generics.params.is_empty() {
+        Some(syn::Error::new_spanned(&input.sig.generics, "#[task] functions cannot be generic"))
+    } else {
+        input.sig.inputs.iter().find_map(|param| match param {
+            FnArg::Receiver(receiver) => Some(syn::Error::new_spanned(receiver, "#[task] functions cannot take `self`")),
+            FnArg::Typed(_) => None,
+        })
+    };
+    if let Some(error) = schedule_error
+        .map(|message| syn::Error::new_spanned(&input.sig.ident, message))
+        .or(signature_error)
+    {
+        return TokenStream::from(error.to_compile_error());
+    }
+
+    let fn_ident = input.sig.ident.clone();
+    let name = meta.name.unwrap_or_else(|| fn_ident.to_string());
+    let cron = match meta.cron {
+        Some(cron) => quote! { Some(#cron) },
+        None => quote! { None },
+    };
+    // Evaluated in the declaration's static, so a bad interval fails the build.
+    let interval = match meta.interval {
+        Some(interval) => quote_spanned! {interval.span()=>
+            Some(match ::gotcha::task::parse_duration(#interval) {
+                ::core::result::Result::Ok(interval) if !interval.is_zero() => interval,
+                ::core::result::Result::Ok(_) => ::core::panic!("#[task] interval must not be zero"),
+                ::core::result::Result::Err(error) => ::core::panic!("{}", error.message()),
+            })
+        },
+        None => quote! { None },
+    };
+    let timezone = match meta.timezone {
+        Some(timezone) => quote! { Some(#timezone) },
+        None => quote! { None },
+    };
+
+    let types: Vec<_> = input
+        .sig
+        .inputs
+        .iter()
+        .filter_map(|param| match param {
+            FnArg::Typed(typed) => Some(typed.ty.clone()),
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+    let arguments: Vec<_> = (0..types.len()).map(|index| format_ident!("argument_{}", index)).collect();
+
+    let random_uuid = Uuid::new_v4().simple().to_string();
+    let extract_ident = format_ident!("__gotcha_task_extract_{}", random_uuid);
+    let check_ident = format_ident!("__gotcha_task_check_{}", random_uuid);
+    let run_ident = format_ident!("__gotcha_task_run_{}", random_uuid);
+
+    let ret = quote! {
+
+        #input
+
+        #[doc(hidden)]
+        fn #extract_ident(context: &::gotcha::task::TaskContext) -> ::core::result::Result<(#(#types,)*), &'static str> {
+            ::core::result::Result::Ok((#(
+                match <#types as ::gotcha::task::FromTaskContext>::from_task_context(context) {
+                    ::core::option::Option::Some(argument) => argument,
+                    ::core::option::Option::None => return ::core::result::Result::Err(::core::any::type_name::<#types>()),
+                },
+            )*))
+        }
+
+        #[doc(hidden)]
+        fn #check_ident(context: &::gotcha::task::TaskContext) -> ::core::result::Result<(), &'static str> {
+            #extract_ident(context).map(|_| ())
+        }
+
+        #[doc(hidden)]
+        fn #run_ident(context: ::gotcha::task::TaskContext) -> ::gotcha::task::TaskFuture {
+            ::std::boxed::Box::pin(async move {
+                let (#(#arguments,)*) = match #extract_ident(&context) {
+                    ::core::result::Result::Ok(arguments) => arguments,
+                    ::core::result::Result::Err(argument) => return ::core::result::Result::Err(::std::format!("cannot extract {}", argument)),
+                };
+                ::gotcha::outcome::Outcome::into_result(#fn_ident(#(#arguments),*).await)
+            })
+        }
+
+        ::gotcha::inventory::submit! {
+            ::gotcha::task::TaskDeclaration {
+                name: #name,
+                cron: #cron,
+                interval: #interval,
+                timezone: #timezone,
+                check: #check_ident,
+                run: #run_ident,
+            }
+        }
+    };
+    TokenStream::from(ret)
+}
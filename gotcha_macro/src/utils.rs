@@ -1,3 +1,7 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 use syn::{Attribute, Lit, Meta};
 
 pub trait AttributesExt {
@@ -203,16 +207,45 @@ pub fn has_serde_skip(attrs: &[Attribute]) -> bool {
     )
 }
 
-/// Whether a field is effectively optional for serde — `default` (`default` or
-/// `default = "..."`) or `skip_serializing_if = "..."` — so it need not be present.
-pub fn is_serde_optional(attrs: &[Attribute]) -> bool {
+/// Whether serde fills the field in when it is missing (`default` or `default = "..."`).
+pub fn has_serde_default(attrs: &[Attribute]) -> bool {
     serde_nested_any(attrs, |meta| match meta {
         syn::Meta::Path(path) => path.is_ident("default"),
-        syn::Meta::NameValue(nv) => nv.path.is_ident("default") || nv.path.is_ident("skip_serializing_if"),
+        syn::Meta::NameValue(nv) => nv.path.is_ident("default"),
         _ => false,
     })
 }
 
+/// Whether a bare `#[serde(flag)]` (`transparent`, `deny_unknown_fields`, ...) is present.
+pub fn has_serde_flag(attrs: &[Attribute], flag: &str) -> bool {
+    serde_nested_any(attrs, |meta| matches!(meta, syn::Meta::Path(path) if path.is_ident(flag)))
+}
+
+/// The string value of `#[serde(key = "...")]`, if present.
+pub fn serde_value(attrs: &[Attribute], key: &str) -> Option<String> {
+    for attr in attrs {
+        if attr.path.is_ident("serde") {
+            if let Ok(nested) =
+                attr.parse_args_with(|input: syn::parse::ParseStream| syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated(input))
+            {
+                for meta in nested {
+                    if let syn::Meta::NameValue(syn::MetaNameValue {
+                        path,
+                        lit: syn::Lit::Str(lit_str),
+                        ..
+                    }) = meta
+                    {
+                        if path.is_ident(key) {
+                            return Some(lit_str.value());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
 /// Parse serde rename_all attribute from container attributes
 pub fn parse_serde_rename_all(attrs: &[Attribute]) -> Option<RenameAll> {
     for attr in attrs {
@@ -246,7 +279,59 @@ pub fn get_serde_name(ident: &str, rename: Option<&str>, rename_all: Option<Rena
     }
 }
 
+/// One `key`, `key = <expr>` or `key(...)` entry of an attribute's arguments.
+///
+/// syn 1's `Meta` only accepts a literal after `=`, so an option whose value is an expression —
+/// `example = json!({ .. })` — would fail the whole attribute. Such options are parsed with this,
+/// taken out, and the remaining entries handed to darling as before.
+pub struct AttrArg {
+    pub name: syn::Ident,
+    pub value: AttrValue,
+}
+
+pub enum AttrValue {
+    Flag,
+    Expr(Box<syn::Expr>),
+    List(Punctuated<AttrArg, syn::Token![,]>),
+}
+
+impl AttrArg {
+    /// Parse a comma-separated argument list.
+    pub fn parse_list(tokens: TokenStream2) -> syn::Result<Punctuated<AttrArg, syn::Token![,]>> {
+        syn::parse::Parser::parse2(Punctuated::parse_terminated, tokens)
+    }
+}
+
+impl Parse for AttrArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let value = if input.peek(syn::Token![=]) {
+            input.parse::<syn::Token![=]>()?;
+            AttrValue::Expr(Box::new(input.parse()?))
+        } else if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            AttrValue::List(Punctuated::parse_terminated(&content)?)
+        } else {
+            AttrValue::Flag
+        };
+        Ok(AttrArg { name, value })
+    }
+}
+
+impl ToTokens for AttrArg {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        let name = &self.name;
+        tokens.extend(match &self.value {
+            AttrValue::Flag => quote! { #name },
+            AttrValue::Expr(expr) => quote! { #name = #expr },
+            AttrValue::List(items) => quote! { #name(#items) },
+        });
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
 
@@ -306,6 +391,8 @@ impl AttributesExt for Vec<Attribute> {
     fn get_doc(&self) -> Option<String> {
         let docs: Vec<String> = self
             .iter()
+            // Only doc attributes: others (`#[validate(regex(path = *RE))]`) need not be syn 1 `Meta`.
+            .filter(|attr| attr.path.is_ident("doc"))
             .filter_map(|attr| match attr.parse_meta().expect("Failed to parse attribute to get doc") {
                 Meta::NameValue(doc) => {
                     if doc.path.is_ident("doc") {